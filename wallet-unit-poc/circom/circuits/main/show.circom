@@ -3,4 +3,4 @@ pragma circom 2.2.3;
 
 include "../show.circom";
 
-component main {public[deviceKeyX, deviceKeyY]} = Show(128);
+component main {public[deviceKeyX, deviceKeyY, messageHash]} = Show(128);
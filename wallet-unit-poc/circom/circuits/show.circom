@@ -9,13 +9,14 @@ template Show(maxClaimsLength) {
     signal input deviceKeyX;
     signal input deviceKeyY;
     signal input messageHash;
+    // Public, so that the relying party checks the date ageAbove18 is computed on
+    signal input currentYear;
+    signal input currentMonth;
+    signal input currentDay;
     signal input sig_r;
     signal input sig_s_inverse;
 
     signal input claim[decodedLen];
-    signal input currentYear;
-    signal input currentMonth;
-    signal input currentDay;
     signal output ageAbove18;
 
     component ecdsa = ECDSA();
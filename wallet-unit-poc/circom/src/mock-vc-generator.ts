@@ -27,6 +27,9 @@ const PRIVATE_KEYS: Record<string, string> = {
   "key-3": "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210",
 };
 
+// Fixed device binding key of the default inputs, so that tests can sign fresh challenges with it
+export const MOCK_DEVICE_PRIVATE_KEY = "9a8b7c6d5e4f30211203f4e5d6c7b8a99a8b7c6d5e4f30211203f4e5d6c7b8a9";
+
 let publicKeysConfigCache: PublicKeysConfig | null = null;
 const DEFAULT_KEYS_URL =
   "https://raw.githubusercontent.com/privacy-ethereum/zkID/refs/heads/main/wallet-unit-poc/circom/keys.json";
@@ -60,8 +63,8 @@ function generateSalt(): string {
   return nodeCrypto.randomBytes(16).toString("base64url");
 }
 
-function generateDeviceBindingKey(): { privateKey: Uint8Array; publicKey: JwkEcdsaPublicKey } {
-  const privateKey = p256.utils.randomPrivateKey();
+function generateDeviceBindingKey(privateKeyHex?: string): { privateKey: Uint8Array; publicKey: JwkEcdsaPublicKey } {
+  const privateKey = privateKeyHex ? Uint8Array.from(Buffer.from(privateKeyHex, "hex")) : p256.utils.randomPrivateKey();
   const point = p256.ProjectivePoint.fromPrivateKey(privateKey);
   const publicKey = pointToJwk({ x: point.x, y: point.y }) as JwkEcdsaPublicKey;
   return { privateKey, publicKey };
@@ -99,6 +102,8 @@ export interface MockDataOptions {
   matches?: string[];
   decodeFlags?: number[];
   kid?: string;
+  // Hex device binding private key, random when not given
+  devicePrivateKey?: string;
 }
 
 export interface MockDataResult {
@@ -121,7 +126,7 @@ export async function generateMockData(options: MockDataOptions = {}): Promise<M
   const claims = options.claims || defaultClaims;
   const kid = options.kid || "key-1";
   const issuerKeyData = await getIssuerKey(kid);
-  const { privateKey: devicePrivateKey, publicKey: deviceKey } = generateDeviceBindingKey();
+  const { privateKey: devicePrivateKey, publicKey: deviceKey } = generateDeviceBindingKey(options.devicePrivateKey);
 
   const privateKeyHex = Buffer.from(issuerKeyData.privateKey).toString("hex");
   const issuerJwkPrivate = {
@@ -0,0 +1,46 @@
+import * as fs from "fs";
+import * as path from "path";
+import { generateMockData, MOCK_DEVICE_PRIVATE_KEY } from "./mock-vc-generator";
+import { generateShowCircuitParams, generateShowInputs, signDeviceNonce } from "./show";
+
+// Challenge signed by the device key in the default Show inputs
+export const DEFAULT_CHALLENGE = "zkid-default-challenge";
+
+const INPUTS_DIR = path.join(__dirname, "..", "inputs");
+
+function save(name: string, inputs: object) {
+  const json = JSON.stringify(inputs, (_, value) => (typeof value === "bigint" ? value.toString() : value), 2);
+  fs.writeFileSync(path.join(INPUTS_DIR, name, "default.json"), json);
+}
+
+// Writes inputs/jwt/default.json and inputs/show/default.json: a credential issued by key-1 to a
+// holder born 1986-06-05, presented on 2025-01-01 with the fixed mock device key
+async function main() {
+  const circuits = JSON.parse(fs.readFileSync(path.join(__dirname, "..", "circuits.json"), "utf8"));
+  const mockData = await generateMockData({
+    claims: [
+      { key: "name", value: "John Doe" },
+      { key: "roc_birthday", value: "0750605" },
+    ],
+    circuitParams: circuits.jwt.params,
+    decodeFlags: [0, 1],
+    devicePrivateKey: MOCK_DEVICE_PRIVATE_KEY,
+  });
+  save("jwt", mockData.circuitInputs);
+
+  const signature = signDeviceNonce(DEFAULT_CHALLENGE, mockData.devicePrivateKey);
+  const showInputs = generateShowInputs(
+    generateShowCircuitParams(mockData.circuitParams),
+    DEFAULT_CHALLENGE,
+    signature,
+    mockData.deviceKey,
+    mockData.claims[1],
+    { year: 2025, month: 1, day: 1 }
+  );
+  save("show", showInputs);
+}
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
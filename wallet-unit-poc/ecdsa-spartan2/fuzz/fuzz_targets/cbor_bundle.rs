@@ -0,0 +1,15 @@
+//! CBOR presentation bundles as received over NFC or BLE
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkid_verifier::{inspect_bundle, DeserializeLimits, PresentationBundle};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = DeserializeLimits::default();
+    if inspect_bundle(data, &limits).is_ok() {
+        if let Ok(bundle) = PresentationBundle::from_cbor(data, &limits) {
+            // Accepted bundles are deterministic, so they re-encode to the same bytes
+            assert_eq!(bundle.to_cbor().unwrap(), data);
+        }
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use ecdsa_spartan2::utils::decode_base64;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(encoded) = std::str::from_utf8(data) {
+        let _ = decode_base64(encoded);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use ecdsa_spartan2::{parse_jwt_inputs, JwtCircuitParams};
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = serde_json::from_slice::<Value>(data) {
+        let _ = parse_jwt_inputs(&json, &JwtCircuitParams::default());
+    }
+});
@@ -0,0 +1,11 @@
+//! Presentation bundles as received by a relying party
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkid_verifier::{check_linkage, DeserializeLimits, Presentation};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(presentation) = Presentation::from_bytes(data, &DeserializeLimits::default()) {
+        let _ = check_linkage(&presentation.prepare_proof, &presentation.show_proof);
+    }
+});
@@ -0,0 +1,28 @@
+//! Proof bytes from an untrusted wallet: decode, then verify against a real key when available.
+//!
+//! Set `FUZZ_VERIFYING_KEY` to a verifying key file (default `keys/show_verifying.key`, from
+//! `show setup`); without one only deserialization is fuzzed.
+#![no_main]
+
+use std::{env, sync::OnceLock};
+
+use libfuzzer_sys::fuzz_target;
+use zkid_verifier::{load_verifying_key, proof_from_bytes, verify, DeserializeLimits, VerifierKey};
+
+fn verifying_key() -> Option<&'static VerifierKey> {
+    static VK: OnceLock<Option<VerifierKey>> = OnceLock::new();
+    VK.get_or_init(|| {
+        let path = env::var("FUZZ_VERIFYING_KEY")
+            .unwrap_or_else(|_| "keys/show_verifying.key".to_string());
+        load_verifying_key(&path).ok()
+    })
+    .as_ref()
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = proof_from_bytes(data, &DeserializeLimits::default()) {
+        if let Some(vk) = verifying_key() {
+            let _ = verify(&proof, vk);
+        }
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkid_verifier::{verifying_key_from_bytes, DeserializeLimits};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = verifying_key_from_bytes(data, &DeserializeLimits::default());
+});
@@ -0,0 +1,157 @@
+//! Async façade over the blocking prover, for Tokio-based wallet backends (`async` feature).
+//!
+//! Witness generation and proving run on Tokio's blocking pool (Spartan2 parallelises internally
+//! with rayon), so the async executor is never blocked. An [`AsyncProver`] owns one loaded
+//! proving key behind an `Arc` and a semaphore bounding how many proofs run at once; clones share
+//! both, so one prover can be handed to every request handler.
+
+use std::sync::Arc;
+
+use spartan2::{
+    r1cs::{R1CSWitness, SplitR1CSInstance},
+    traits::circuit::SpartanCircuit,
+};
+use tokio::{
+    sync::Semaphore,
+    task::{spawn_blocking, JoinError},
+};
+
+use crate::{
+    circuits::CircomCircuit,
+    observer::{NoopObserver, ProofObserver},
+    prover::{generate_proof_with_observer, reblind_proof_with_observer, ProofArtifacts},
+    secret::SecretBlinds,
+    setup::load_proving_key,
+    ProverError, ProverKey, Scalar, E,
+};
+
+#[derive(Clone)]
+pub struct AsyncProver {
+    pk: Arc<ProverKey>,
+    permits: Arc<Semaphore>,
+}
+
+impl AsyncProver {
+    /// Prover running at most `max_concurrent` proofs at a time (at least one)
+    pub fn new(pk: Arc<ProverKey>, max_concurrent: usize) -> Self {
+        Self {
+            pk,
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Load a proving key off the async executor and wrap it in a prover
+    pub async fn load(
+        pk_path: &str,
+        max_concurrent: usize,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let pk_path = pk_path.to_owned();
+        let pk = spawn_blocking(move || load_proving_key(&pk_path).map_err(|e| e.to_string()))
+            .await
+            .map_err(|e| e.to_string())??;
+        Ok(Self::new(Arc::new(pk), max_concurrent))
+    }
+
+    pub fn proving_key(&self) -> &Arc<ProverKey> {
+        &self.pk
+    }
+
+    /// Async [`crate::generate_proof`]
+    pub async fn prove<C>(&self, circuit: C) -> Result<ProofArtifacts, ProverError>
+    where
+        C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug + Send + 'static,
+    {
+        self.prove_with_observer(circuit, Arc::new(NoopObserver))
+            .await
+    }
+
+    /// Async [`crate::generate_proof_with_observer`]
+    pub async fn prove_with_observer<C>(
+        &self,
+        circuit: C,
+        observer: Arc<dyn ProofObserver>,
+    ) -> Result<ProofArtifacts, ProverError>
+    where
+        C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug + Send + 'static,
+    {
+        self.run(move |pk| generate_proof_with_observer(circuit, pk, observer.as_ref()))
+            .await
+    }
+
+    /// Async [`crate::reblind_proof`]
+    pub async fn reblind<C>(
+        &self,
+        circuit: C,
+        instance: SplitR1CSInstance<E>,
+        witness: R1CSWitness<E>,
+        randomness: SecretBlinds<Scalar>,
+    ) -> Result<ProofArtifacts, ProverError>
+    where
+        C: SpartanCircuit<E> + Send + 'static,
+    {
+        self.reblind_with_observer(
+            circuit,
+            instance,
+            witness,
+            randomness,
+            Arc::new(NoopObserver),
+        )
+        .await
+    }
+
+    /// Async [`crate::reblind_proof_with_observer`]
+    pub async fn reblind_with_observer<C>(
+        &self,
+        circuit: C,
+        instance: SplitR1CSInstance<E>,
+        witness: R1CSWitness<E>,
+        randomness: SecretBlinds<Scalar>,
+        observer: Arc<dyn ProofObserver>,
+    ) -> Result<ProofArtifacts, ProverError>
+    where
+        C: SpartanCircuit<E> + Send + 'static,
+    {
+        self.run(move |pk| {
+            reblind_proof_with_observer(
+                circuit,
+                pk,
+                instance,
+                witness,
+                &randomness,
+                observer.as_ref(),
+            )
+        })
+        .await
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, ProverError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ProverKey) -> Result<T, ProverError> + Send + 'static,
+    {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ProverError::Cancelled)?;
+        let pk = Arc::clone(&self.pk);
+
+        // The permit moves into the blocking task so it is held until the proof actually finishes,
+        // even if the caller stops awaiting it
+        spawn_blocking(move || {
+            let _permit = permit;
+            f(&pk)
+        })
+        .await
+        .unwrap_or_else(join_error)
+    }
+}
+
+fn join_error<T>(e: JoinError) -> Result<T, ProverError> {
+    match e.try_into_panic() {
+        Ok(panic) => std::panic::resume_unwind(panic),
+        // Blocking tasks are only cancelled when the runtime shuts down
+        Err(_) => Err(ProverError::Cancelled),
+    }
+}
@@ -0,0 +1,288 @@
+//! Witness satisfiability check, to diagnose bad circuit inputs before spending time on a proof.
+//!
+//! The circuit is synthesized into a bellpepper `TestConstraintSystem`; if a constraint fails, its
+//! wires are looked up in the `.r1cs` and named with the circom `.sym` file next to it.
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use bellpepper::util_cs::test_cs::TestConstraintSystem;
+use bellpepper_core::{ConstraintSystem, SynthesisError};
+use circom_scotia::reader::load_r1cs;
+use ff::Field;
+use spartan2::traits::circuit::SpartanCircuit;
+
+use crate::{circuits::CircomCircuit, inputs::InputError, Scalar, E};
+
+/// A wire of a failing constraint, with its circom signal name when the `.sym` file has one
+#[derive(Debug, Clone)]
+pub struct SignalValue {
+    pub wire: usize,
+    pub name: Option<String>,
+    pub value: Option<Scalar>,
+}
+
+/// First constraint `A * B = C` not satisfied by the witness
+#[derive(Debug, Clone)]
+pub struct UnsatisfiedConstraint {
+    /// Index of the constraint in the `.r1cs` file
+    pub index: Option<usize>,
+    /// Path of the constraint in the test constraint system
+    pub path: String,
+    pub a: Option<Scalar>,
+    pub b: Option<Scalar>,
+    pub c: Option<Scalar>,
+    pub signals: Vec<SignalValue>,
+}
+
+#[derive(Debug)]
+pub enum CheckError {
+    /// An input field is missing or does not fit the circuit parameters
+    Input(InputError),
+    /// The inputs could not be turned into a witness
+    Synthesis(SynthesisError),
+    Unsatisfied(Box<UnsatisfiedConstraint>),
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Input(e) => write!(f, "{e}"),
+            CheckError::Synthesis(e) => write!(f, "witness generation failed: {e}"),
+            CheckError::Unsatisfied(constraint) => {
+                match constraint.index {
+                    Some(index) => write!(f, "constraint #{index} is not satisfied")?,
+                    None => write!(f, "constraint '{}' is not satisfied", constraint.path)?,
+                }
+                if let (Some(a), Some(b), Some(c)) = (constraint.a, constraint.b, constraint.c) {
+                    write!(f, "\n  A = {a:?}\n  B = {b:?}\n  C = {c:?}")?;
+                }
+                for signal in &constraint.signals {
+                    let name = signal.name.as_deref().unwrap_or("<unnamed>");
+                    match signal.value {
+                        Some(value) => write!(f, "\n  wire {} {name} = {value:?}", signal.wire)?,
+                        None => write!(f, "\n  wire {} {name} = <missing>", signal.wire)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+impl From<InputError> for CheckError {
+    fn from(e: InputError) -> Self {
+        CheckError::Input(e)
+    }
+}
+
+impl From<SynthesisError> for CheckError {
+    fn from(e: SynthesisError) -> Self {
+        CheckError::Synthesis(e)
+    }
+}
+
+/// Validate the inputs of `circuit`, then synthesize it with its witness and check every
+/// constraint.
+///
+/// Returns the number of constraints checked, or the first input field or constraint that fails.
+pub fn check_witness<C: SpartanCircuit<E> + CircomCircuit>(
+    circuit: &C,
+) -> Result<usize, CheckError> {
+    circuit.validate_inputs(&circuit.inputs()?)?;
+    // Cached on the circuit, so synthesis below reuses it
+    let witness = circuit.witness()?;
+
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let shared = circuit.shared(&mut cs.namespace(|| "shared"))?;
+    let precommitted = circuit.precommitted(&mut cs.namespace(|| "precommitted"), &shared)?;
+    circuit.synthesize(&mut cs, &shared, &precommitted, None)?;
+
+    let Some(path) = cs.which_is_unsatisfied().map(str::to_owned) else {
+        return Ok(cs.num_constraints());
+    };
+
+    let index = constraint_index(&path);
+    let mut unsatisfied = UnsatisfiedConstraint {
+        index,
+        path,
+        a: None,
+        b: None,
+        c: None,
+        signals: vec![],
+    };
+    if let Some(index) = index {
+        let r1cs_path = circuit.r1cs_path();
+        let r1cs = load_r1cs(&r1cs_path);
+        if let Some((a, b, c)) = r1cs.constraints.get(index) {
+            let names = load_signal_names(&r1cs_path.with_extension("sym"));
+            describe_constraint([a, b, c], &witness, &names, &mut unsatisfied);
+        }
+    }
+    Err(CheckError::Unsatisfied(Box::new(unsatisfied)))
+}
+
+/// Index in the `.r1cs` file of a failing constraint, from its path in the test constraint system.
+///
+/// R1CS constraints are named `constraint {index}`; the others tie shared values to circuit
+/// signals.
+fn constraint_index(path: &str) -> Option<usize> {
+    path.rsplit_once("constraint ")
+        .and_then(|(_, digits)| digits.parse::<usize>().ok())
+}
+
+/// Fill in the values of `A`, `B` and `C` and the wires of a failing constraint from the witness
+fn describe_constraint(
+    [a, b, c]: [&[(usize, Scalar)]; 3],
+    witness: &[Scalar],
+    names: &HashMap<usize, String>,
+    unsatisfied: &mut UnsatisfiedConstraint,
+) {
+    let eval = |terms: &[(usize, Scalar)]| -> Option<Scalar> {
+        terms.iter().try_fold(Scalar::ZERO, |acc, (wire, coeff)| {
+            witness.get(*wire).map(|value| acc + *value * coeff)
+        })
+    };
+    unsatisfied.a = eval(a);
+    unsatisfied.b = eval(b);
+    unsatisfied.c = eval(c);
+
+    let mut wires: Vec<usize> = a.iter().chain(b).chain(c).map(|(wire, _)| *wire).collect();
+    wires.sort_unstable();
+    wires.dedup();
+    unsatisfied.signals = wires
+        .into_iter()
+        .map(|wire| SignalValue {
+            wire,
+            name: names.get(&wire).cloned(),
+            value: witness.get(wire).copied(),
+        })
+        .collect();
+}
+
+/// Map wire index to signal name from a circom `.sym` file (`signal,wire,component,name` lines).
+///
+/// Signals optimised away by circom have wire `-1` and are skipped; a missing file yields no names.
+pub fn load_signal_names(sym_path: &Path) -> HashMap<usize, String> {
+    let Ok(contents) = fs::read_to_string(sym_path) else {
+        return HashMap::new();
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, ',');
+            let _signal = fields.next()?;
+            let wire = fields.next()?.trim().parse::<i64>().ok()?;
+            let _component = fields.next()?;
+            let name = fields.next()?.trim();
+            usize::try_from(wire)
+                .ok()
+                .map(|wire| (wire, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(terms: &[(usize, u64)]) -> Vec<(usize, Scalar)> {
+        terms
+            .iter()
+            .map(|&(wire, coeff)| (wire, Scalar::from(coeff)))
+            .collect()
+    }
+
+    fn unsatisfied(path: &str) -> UnsatisfiedConstraint {
+        UnsatisfiedConstraint {
+            index: constraint_index(path),
+            path: path.to_string(),
+            a: None,
+            b: None,
+            c: None,
+            signals: vec![],
+        }
+    }
+
+    #[test]
+    fn signal_names_skip_optimised_and_malformed_lines() {
+        let path = std::env::temp_dir().join(format!("zkid-check-{}.sym", std::process::id()));
+        fs::write(
+            &path,
+            "1,1,0,main.out\n2,2,0,main.in[0]\n3,-1,0,main.optimised\nnot a symbol\n4,3,1,main.c.x, y\n",
+        )
+        .unwrap();
+
+        let names = load_signal_names(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[&1], "main.out");
+        assert_eq!(names[&2], "main.in[0]");
+        assert_eq!(names[&3], "main.c.x, y");
+
+        assert!(load_signal_names(Path::new("/nonexistent/circuit.sym")).is_empty());
+    }
+
+    #[test]
+    fn constraint_index_comes_from_the_r1cs_constraint_name() {
+        assert_eq!(constraint_index("constraint 0"), Some(0));
+        assert_eq!(constraint_index("circuit/constraint 4127"), Some(4127));
+        assert_eq!(constraint_index("shared_1_equals_signal"), None);
+        assert_eq!(constraint_index("constraint x"), None);
+    }
+
+    #[test]
+    fn failing_constraint_is_described_from_the_witness() {
+        // Hand-built R1CS over wires [1, out, x, y]: x * y = out, with out wrongly set to 7
+        let witness = [1u64, 7, 2, 3].map(Scalar::from);
+        let (a, b, c) = (terms(&[(2, 1)]), terms(&[(3, 1)]), terms(&[(1, 1)]));
+        let names = HashMap::from([(1, "main.out".to_string()), (2, "main.x".to_string())]);
+
+        let mut constraint = unsatisfied("constraint 0");
+        describe_constraint([&a, &b, &c], &witness, &names, &mut constraint);
+
+        assert_eq!(constraint.index, Some(0));
+        assert_eq!(
+            (constraint.a, constraint.b, constraint.c),
+            (
+                Some(Scalar::from(2)),
+                Some(Scalar::from(3)),
+                Some(Scalar::from(7))
+            )
+        );
+        let signals: Vec<_> = constraint
+            .signals
+            .iter()
+            .map(|signal| (signal.wire, signal.name.as_deref(), signal.value))
+            .collect();
+        assert_eq!(
+            signals,
+            vec![
+                (1, Some("main.out"), Some(Scalar::from(7))),
+                (2, Some("main.x"), Some(Scalar::from(2))),
+                (3, None, Some(Scalar::from(3))),
+            ]
+        );
+        assert!(CheckError::Unsatisfied(Box::new(constraint))
+            .to_string()
+            .starts_with("constraint #0 is not satisfied"));
+    }
+
+    #[test]
+    fn wires_outside_the_witness_are_missing() {
+        let witness = [1u64, 5].map(Scalar::from);
+        let (a, b, c) = (terms(&[(1, 2)]), terms(&[(0, 1)]), terms(&[(9, 1)]));
+
+        let mut constraint = unsatisfied("constraint 3");
+        describe_constraint([&a, &b, &c], &witness, &HashMap::new(), &mut constraint);
+
+        assert_eq!(constraint.a, Some(Scalar::from(10)));
+        assert_eq!(constraint.c, None);
+        assert!(constraint
+            .signals
+            .iter()
+            .any(|signal| signal.wire == 9 && signal.value.is_none()));
+    }
+}
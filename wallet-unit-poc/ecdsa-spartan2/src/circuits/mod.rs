@@ -1,2 +1,239 @@
 pub mod prepare_circuit;
 pub mod show_circuit;
+#[cfg(feature = "test-utils")]
+mod toy_circuit;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
+use circom_scotia::r1cs::R1CS;
+use ff::Field;
+use serde_json::Value;
+
+use crate::{inputs::InputError, params::CircuitParams, secret::CircomWitness, Scalar};
+
+/// A circom circuit synthesized from its `.r1cs` file with a rust-witness generator
+pub trait CircomCircuit {
+    /// Location of the compiled `.r1cs`; the `.sym` file is expected next to it
+    fn r1cs_path(&self) -> PathBuf;
+
+    /// Full circom witness (wire 0 is the constant 1) computed from the circuit inputs, generated
+    /// once per circuit and shared by its clones until [`CircomCircuit::clear_witness`]
+    fn witness(&self) -> Result<CircomWitness, SynthesisError>;
+
+    /// Wipe the cached witness, for the circuit and its clones, once proving is done
+    fn clear_witness(&self);
+
+    /// Circuit inputs as JSON, from the input file or the in-memory value
+    fn inputs(&self) -> Result<Value, SynthesisError>;
+
+    /// Check the inputs against the circuit parameters, naming the first field that does not fit
+    fn validate_inputs(&self, inputs: &Value) -> Result<(), InputError>;
+
+    /// Template instantiation the circuit was compiled with, recorded next to its setup keys
+    fn circuit_params(&self) -> CircuitParams;
+}
+
+/// Witness of a circuit, generated on first use so that `shared` and `synthesize` do not each run
+/// the witness generator. Clones share the cached value, which is wiped when cleared or dropped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WitnessCache(Arc<Mutex<Option<CircomWitness>>>);
+
+impl WitnessCache {
+    pub(crate) fn get_or_generate(
+        &self,
+        generate: impl FnOnce() -> Result<CircomWitness, SynthesisError>,
+    ) -> Result<CircomWitness, SynthesisError> {
+        let mut cached = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(witness) = cached.as_ref() {
+            return Ok(witness.clone());
+        }
+        Ok(cached.insert(generate()?).clone())
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+/// Synthesize a circom R1CS and return the variables of wires `1..num_variables` (wire `w` at index
+/// `w - 1`), so that callers can constrain them.
+///
+/// Allocation and constraint names follow `circom_scotia::synthesize`, which does not hand the
+/// variables back: `public_{wire}` for public wires and `aux_{k}` for the `k`-th private wire.
+/// Without a witness (setup) every wire is assigned one.
+pub(crate) fn synthesize_wires<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    r1cs: R1CS<Scalar>,
+    witness: Option<&[Scalar]>,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+    let mut wires = Vec::with_capacity(r1cs.num_variables.saturating_sub(1));
+    for i in 1..r1cs.num_variables {
+        let value = match witness {
+            Some(witness) => *witness.get(i).ok_or(SynthesisError::AssignmentMissing)?,
+            None => Scalar::ONE,
+        };
+        let name = if i < r1cs.num_inputs {
+            format!("public_{i}")
+        } else {
+            format!("aux_{}", i - r1cs.num_inputs)
+        };
+        wires.push(AllocatedNum::alloc(cs.namespace(|| name), || Ok(value))?);
+    }
+
+    let lc = |terms: &[(usize, Scalar)]| {
+        terms
+            .iter()
+            .fold(LinearCombination::zero(), |lc, &(wire, coeff)| match wire {
+                0 => lc + (coeff, CS::one()),
+                w => lc + (coeff, wires[w - 1].get_variable()),
+            })
+    };
+    for (i, (a, b, c)) in r1cs.constraints.iter().enumerate() {
+        cs.enforce(
+            || format!("constraint {i}"),
+            |_| lc(a),
+            |_| lc(b),
+            |_| lc(c),
+        );
+    }
+    Ok(wires)
+}
+
+/// Enforce `shared[k] == wire signal_wires[k]`, tying the shared commitment to the circom signals
+pub(crate) fn enforce_shared_wires<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    shared: &[AllocatedNum<Scalar>],
+    wires: &[AllocatedNum<Scalar>],
+    signal_wires: &[usize],
+) -> Result<(), SynthesisError> {
+    if shared.len() != signal_wires.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    for (k, (value, &wire)) in shared.iter().zip(signal_wires).enumerate() {
+        let signal = wire
+            .checked_sub(1)
+            .and_then(|index| wires.get(index))
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        cs.enforce(
+            || format!("shared_{k}_equals_signal"),
+            |lc| lc + value.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + signal.get_variable(),
+        );
+    }
+    Ok(())
+}
+
+/// Expose wires `signal_wires` as the public values of the proof, in that order, which must match
+/// `SpartanCircuit::public_values`
+pub(crate) fn inputize_wires<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    wires: &[AllocatedNum<Scalar>],
+    signal_wires: &[usize],
+) -> Result<(), SynthesisError> {
+    for (k, &wire) in signal_wires.iter().enumerate() {
+        let signal = wire
+            .checked_sub(1)
+            .and_then(|index| wires.get(index))
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        signal.inputize(cs.namespace(|| format!("public_value_{k}")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper::util_cs::test_cs::TestConstraintSystem;
+
+    use super::*;
+
+    fn alloc_all(
+        cs: &mut TestConstraintSystem<Scalar>,
+        prefix: &str,
+        values: &[u64],
+    ) -> Vec<AllocatedNum<Scalar>> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("{prefix}_{i}")), || {
+                    Ok(Scalar::from(value))
+                })
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shared_values_equal_to_signals_are_satisfied() {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let wires = alloc_all(&mut cs, "wire", &[7, 11, 13, 17]);
+        let shared = alloc_all(&mut cs, "shared", &[11, 17]);
+
+        enforce_shared_wires(&mut cs, &shared, &wires, &[2, 4]).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn mismatched_shared_value_is_unsatisfied() {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let wires = alloc_all(&mut cs, "wire", &[7, 11, 13, 17]);
+        let shared = alloc_all(&mut cs, "shared", &[11, 18]);
+
+        enforce_shared_wires(&mut cs, &shared, &wires, &[2, 4]).unwrap();
+        assert!(!cs.is_satisfied());
+        assert_eq!(cs.which_is_unsatisfied(), Some("shared_1_equals_signal"));
+    }
+
+    #[test]
+    fn shared_length_or_wire_out_of_range_errors() {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let wires = alloc_all(&mut cs, "wire", &[7, 11]);
+        let shared = alloc_all(&mut cs, "shared", &[7]);
+
+        assert!(enforce_shared_wires(&mut cs, &shared, &wires, &[1, 2]).is_err());
+        assert!(enforce_shared_wires(&mut cs, &shared, &wires, &[0]).is_err());
+        assert!(enforce_shared_wires(&mut cs, &shared, &wires, &[3]).is_err());
+    }
+
+    #[test]
+    fn inputized_wires_become_public_inputs_in_order() {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let wires = alloc_all(&mut cs, "wire", &[7, 11, 13, 17]);
+
+        inputize_wires(&mut cs, &wires, &[4, 1]).unwrap();
+        assert!(cs.is_satisfied());
+        assert!(cs.verify(&[Scalar::from(17), Scalar::from(7)]));
+        assert!(!cs.verify(&[Scalar::from(7), Scalar::from(17)]));
+        assert!(inputize_wires(&mut cs, &wires, &[0]).is_err());
+    }
+
+    #[test]
+    fn witness_is_generated_once_and_shared_by_clones() {
+        use std::cell::Cell;
+
+        let cache = WitnessCache::default();
+        let calls = Cell::new(0);
+        let generate = || {
+            calls.set(calls.get() + 1);
+            Ok(CircomWitness::new(vec![Scalar::ONE, Scalar::from(5)]))
+        };
+
+        assert!(cache
+            .get_or_generate(|| Err(SynthesisError::AssignmentMissing))
+            .is_err());
+        let first = cache.get_or_generate(generate).unwrap();
+        let second = cache.clone().get_or_generate(generate).unwrap();
+        assert_eq!(*first, *second);
+        assert_eq!(calls.get(), 1);
+
+        // Cleared for every clone, then generated again on the next use
+        cache.clone().clear();
+        cache.get_or_generate(generate).unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+}
@@ -1,29 +1,92 @@
 use crate::{
-    prover::generate_prepare_witness,
+    circuits::{enforce_shared_wires, synthesize_wires, CircomCircuit, WitnessCache},
+    inputs::{InputError, JwtInputs},
+    params::{CircuitParams, JwtCircuitParams},
+    prover::generate_prepare_witness_with,
+    secret::CircomWitness,
     utils::{compute_prepare_shared_scalars, PrepareSharedScalars},
     Scalar, E,
 };
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
+use circom_scotia::reader::load_r1cs;
+use rust_witness::BigInt;
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
-use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
+use std::{any::type_name, collections::HashMap, env::current_dir, fs::File, path::PathBuf};
+use tracing::{error, info};
 
 rust_witness::witness!(jwt);
 
+/// rust-witness generator of a compiled JWT circuit; `rust_witness::witness!(name)` defines
+/// `<name>_witness` for each circuit transpiled by `build.rs`
+pub type WitnessGenerator = fn(HashMap<String, Vec<BigInt>>) -> Vec<BigInt>;
+
 // jwt.circom
 #[derive(Debug, Clone, Default)]
 pub struct PrepareCircuit {
     input_path: Option<PathBuf>,
+    input_json: Option<Value>,
+    r1cs_path: Option<PathBuf>,
+    params: JwtCircuitParams,
+    witness_generator: Option<WitnessGenerator>,
+    witness: WitnessCache,
 }
 
 impl PrepareCircuit {
     pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
         Self {
             input_path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Build the circuit from in-memory inputs instead of an input JSON file
+    pub fn from_json(inputs: Value) -> Self {
+        Self {
+            input_json: Some(inputs),
+            ..Default::default()
         }
     }
 
+    /// Override the location of `jwt.r1cs` (defaults to `../circom/build/jwt/jwt_js/jwt.r1cs`)
+    pub fn with_r1cs_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.r1cs_path = Some(path.into());
+        self
+    }
+
+    /// Instantiation of the compiled circuit (defaults to `JWT(1920, 1900, 4, 50, 128)`)
+    pub fn with_params(mut self, params: JwtCircuitParams) -> Self {
+        self.params = params;
+        self.witness = WitnessCache::default();
+        self
+    }
+
+    pub fn params(&self) -> &JwtCircuitParams {
+        &self.params
+    }
+
+    /// Witness generator of the compiled variant (defaults to `jwt_witness`)
+    pub fn with_witness_generator(mut self, generator: WitnessGenerator) -> Self {
+        self.witness_generator = Some(generator);
+        self.witness = WitnessCache::default();
+        self
+    }
+
+    /// Witness positions of the shared values: KeyBindingX, KeyBindingY, then `ageClaim[]`
+    fn shared_wires(&self) -> Vec<usize> {
+        let layout = self.params.output_layout();
+        [layout.keybinding_x_index, layout.keybinding_y_index]
+            .into_iter()
+            .chain(layout.age_claim_range())
+            .collect()
+    }
+
+    fn resolve_r1cs(&self, cwd: &PathBuf) -> PathBuf {
+        self.r1cs_path
+            .clone()
+            .unwrap_or_else(|| cwd.join("../circom/build/jwt/jwt_js/jwt.r1cs"))
+    }
+
     fn input_path_absolute(&self, cwd: &PathBuf) -> Option<PathBuf> {
         self.input_path.as_ref().map(|p| {
             if p.is_absolute() {
@@ -38,40 +101,75 @@ impl PrepareCircuit {
         self.input_path_absolute(cwd)
             .unwrap_or_else(|| cwd.join("../circom/inputs/jwt/default.json"))
     }
+
+    fn load_inputs(&self, cwd: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(inputs) = &self.input_json {
+            return Ok(inputs.clone());
+        }
+
+        let json_path = self.resolve_input_json(cwd);
+        info!("Loading prepare inputs from {}", json_path.display());
+        let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)
+    }
+}
+
+impl CircomCircuit for PrepareCircuit {
+    fn r1cs_path(&self) -> PathBuf {
+        self.resolve_r1cs(&current_dir().unwrap())
+    }
+
+    fn witness(&self) -> Result<CircomWitness, SynthesisError> {
+        self.witness.get_or_generate(|| {
+            let json_value = self.load_inputs(&current_dir().unwrap())?;
+            let generator = self.witness_generator.unwrap_or(jwt_witness);
+            generate_prepare_witness_with(&json_value, &self.params, generator)
+                .map(CircomWitness::new)
+        })
+    }
+
+    fn clear_witness(&self) {
+        self.witness.clear();
+    }
+
+    fn inputs(&self) -> Result<Value, SynthesisError> {
+        self.load_inputs(&current_dir().unwrap())
+    }
+
+    fn validate_inputs(&self, inputs: &Value) -> Result<(), InputError> {
+        JwtInputs::from_json(inputs, &self.params).map(|_| ())
+    }
+
+    fn circuit_params(&self) -> CircuitParams {
+        self.params.into()
+    }
 }
 
 impl SpartanCircuit<E> for PrepareCircuit {
     fn synthesize<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
-        _: &[AllocatedNum<Scalar>],
+        shared: &[AllocatedNum<Scalar>],
         _: &[AllocatedNum<Scalar>],
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
         let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
-        let witness_dir = root.join("build/jwt/jwt_js");
-        let r1cs = witness_dir.join("jwt.r1cs");
+        let r1cs = self.resolve_r1cs(&cwd);
 
         // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
         // During setup, we only need constraint structure instead of actual witness values
         let cs_type = type_name::<CS>();
         let is_setup_phase = cs_type.contains("ShapeCS");
 
-        if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
+        let witness = if is_setup_phase {
             // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
-        }
+            None
+        } else {
+            Some(self.witness()?)
+        };
 
-        // Generate witness using the dedicated function
-        let input_path = self.input_path_absolute(&cwd);
-        let witness = generate_prepare_witness(input_path.as_ref().map(|p| p.as_path()))?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
-        Ok(())
+        let wires = synthesize_wires(cs, load_r1cs(r1cs), witness.as_deref())?;
+        enforce_shared_wires(cs, shared, &wires, &self.shared_wires())
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
@@ -82,18 +180,38 @@ impl SpartanCircuit<E> for PrepareCircuit {
         cs: &mut CS,
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
         let cwd = current_dir().unwrap();
-        let json_path = self.resolve_input_json(&cwd);
-
-        let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
-
-        let json_value: Value =
-            serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)?;
+        let json_value = self.load_inputs(&cwd)?;
+
+        // The shared values are the circuit outputs, read from the witness at their wires
+        let witness = self.witness()?;
+        let outputs = self
+            .shared_wires()
+            .into_iter()
+            .map(|wire| witness.get(wire).copied())
+            .collect::<Option<Vec<Scalar>>>()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        let (keybinding, claim_scalars) = outputs.split_at(2);
+        let outputs = PrepareSharedScalars {
+            keybinding_x: keybinding[0],
+            keybinding_y: keybinding[1],
+            claim_scalars: claim_scalars.to_vec(),
+        };
+
+        // Cross-check against the Rust-side decoding of the JWT, which the Show inputs are built
+        // from
+        let decoded = compute_prepare_shared_scalars(&json_value, &self.params)?;
+        if decoded != outputs {
+            error!(
+                "Prepare circuit outputs disagree with the decoded JWT (KeyBindingX/Y or ageClaim)"
+            );
+            return Err(SynthesisError::Unsatisfiable);
+        }
 
         let PrepareSharedScalars {
             keybinding_x,
             keybinding_y,
             claim_scalars,
-        } = compute_prepare_shared_scalars(&json_value)?;
+        } = outputs;
 
         let keybinding_x_alloc =
             AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
@@ -1,6 +1,15 @@
-use crate::{utils::*, Scalar, E};
+use crate::{
+    circuits::{
+        enforce_shared_wires, inputize_wires, synthesize_wires, CircomCircuit, WitnessCache,
+    },
+    inputs::{InputError, ShowInputs},
+    params::{CircuitParams, ShowCircuitParams},
+    secret::CircomWitness,
+    utils::*,
+    Scalar, E,
+};
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
+use circom_scotia::reader::load_r1cs;
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
 use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
@@ -12,15 +21,71 @@ rust_witness::witness!(show);
 #[derive(Debug, Clone, Default)]
 pub struct ShowCircuit {
     input_path: Option<PathBuf>,
+    input_json: Option<Value>,
+    r1cs_path: Option<PathBuf>,
+    params: ShowCircuitParams,
+    witness: WitnessCache,
 }
 
 impl ShowCircuit {
     pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
         Self {
             input_path: path.into(),
+            ..Default::default()
         }
     }
 
+    /// Build the circuit from in-memory inputs instead of an input JSON file
+    pub fn from_json(inputs: Value) -> Self {
+        Self {
+            input_json: Some(inputs),
+            ..Default::default()
+        }
+    }
+
+    /// Override the location of `show.r1cs` (defaults to `../circom/build/show/show_js/show.r1cs`)
+    pub fn with_r1cs_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.r1cs_path = Some(path.into());
+        self
+    }
+
+    /// Instantiation of the compiled circuit (defaults to `Show(128)`)
+    pub fn with_params(mut self, params: ShowCircuitParams) -> Self {
+        self.params = params;
+        self.witness = WitnessCache::default();
+        self
+    }
+
+    pub fn params(&self) -> &ShowCircuitParams {
+        &self.params
+    }
+
+    /// Witness positions of the shared values: `deviceKeyX`, `deviceKeyY`, then `claim[]`
+    fn shared_wires(&self) -> Vec<usize> {
+        let layout = self.params.input_layout();
+        [layout.device_key_x_index, layout.device_key_y_index]
+            .into_iter()
+            .chain(layout.claim_range())
+            .collect()
+    }
+
+    /// Witness positions of the public values: `ageAbove18`; `messageHash`, which binds the proof
+    /// to the challenge the device key signed; then `currentYear`, `currentMonth` and
+    /// `currentDay`, the date `ageAbove18` is computed on, which the relying party checks
+    fn public_wires(&self) -> Vec<usize> {
+        let layout = self.params.input_layout();
+        [layout.age_above_18_index, layout.message_hash_index]
+            .into_iter()
+            .chain(layout.current_date_range())
+            .collect()
+    }
+
+    fn resolve_r1cs(&self, cwd: &PathBuf) -> PathBuf {
+        self.r1cs_path
+            .clone()
+            .unwrap_or_else(|| cwd.join("../circom/build/show/show_js/show.r1cs"))
+    }
+
     fn input_path_absolute(&self, cwd: &PathBuf) -> PathBuf {
         self.input_path
             .as_ref()
@@ -35,6 +100,10 @@ impl ShowCircuit {
     }
 
     fn load_inputs(&self, cwd: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(inputs) = &self.input_json {
+            return Ok(inputs.clone());
+        }
+
         let path = self.input_path_absolute(cwd);
         info!("Loading show inputs from {}", path.display());
         let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
@@ -42,46 +111,75 @@ impl ShowCircuit {
     }
 }
 
+impl CircomCircuit for ShowCircuit {
+    fn r1cs_path(&self) -> PathBuf {
+        self.resolve_r1cs(&current_dir().unwrap())
+    }
+
+    fn witness(&self) -> Result<CircomWitness, SynthesisError> {
+        self.witness.get_or_generate(|| {
+            // Parse inputs using declarative field definitions
+            let json_value = self.load_inputs(&current_dir().unwrap())?;
+            let inputs = parse_show_inputs(&json_value, &self.params).map_err(input_error)?;
+
+            // Generate witness using native Rust (rust-witness)
+            let witness_bigint = show_witness(inputs);
+            convert_bigint_to_scalar(witness_bigint).map(CircomWitness::new)
+        })
+    }
+
+    fn clear_witness(&self) {
+        self.witness.clear();
+    }
+
+    fn inputs(&self) -> Result<Value, SynthesisError> {
+        self.load_inputs(&current_dir().unwrap())
+    }
+
+    fn validate_inputs(&self, inputs: &Value) -> Result<(), InputError> {
+        ShowInputs::from_json(inputs, &self.params).map(|_| ())
+    }
+
+    fn circuit_params(&self) -> CircuitParams {
+        self.params.into()
+    }
+}
+
 impl SpartanCircuit<E> for ShowCircuit {
     fn synthesize<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
-        _: &[AllocatedNum<Scalar>],
+        shared: &[AllocatedNum<Scalar>],
         _: &[AllocatedNum<Scalar>],
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
         let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
-        let witness_dir = root.join("build/show/show_js");
-        let r1cs = witness_dir.join("show.r1cs");
-        let json_value = self.load_inputs(&cwd)?;
-
-        // Parse inputs using declarative field definitions
-        let inputs = parse_show_inputs(&json_value)?;
+        let r1cs = self.resolve_r1cs(&cwd);
 
         // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
         // During setup, we only need constraint structure instead of actual witness values
         let cs_type = type_name::<CS>();
         let is_setup_phase = cs_type.contains("ShapeCS");
 
-        if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
+        let witness = if is_setup_phase {
             // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
-        }
-
-        // Generate witness using native Rust (rust-witness)
-        let witness_bigint = show_witness(inputs);
-        let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
-        Ok(())
+            None
+        } else {
+            Some(self.witness()?)
+        };
+
+        let wires = synthesize_wires(cs, load_r1cs(r1cs), witness.as_deref())?;
+        enforce_shared_wires(cs, shared, &wires, &self.shared_wires())?;
+        inputize_wires(cs, &wires, &self.public_wires())
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
-        Ok(vec![])
+        let witness = self.witness()?;
+        self.public_wires()
+            .into_iter()
+            .map(|wire| witness.get(wire).copied())
+            .collect::<Option<Vec<Scalar>>>()
+            .ok_or(SynthesisError::AssignmentMissing)
     }
     fn shared<CS: ConstraintSystem<Scalar>>(
         &self,
@@ -90,17 +188,14 @@ impl SpartanCircuit<E> for ShowCircuit {
         let cwd = current_dir().unwrap();
         let json_value = self.load_inputs(&cwd)?;
 
-        let inputs = parse_show_inputs(&json_value)?;
-        let keybinding_x_bigint = inputs.get("deviceKeyX").unwrap()[0].clone();
-        let keybinding_y_bigint = inputs.get("deviceKeyY").unwrap()[0].clone();
-        let claim_bigints = inputs
-            .get("claim")
-            .cloned()
-            .ok_or(SynthesisError::AssignmentMissing)?;
-
-        let keybinding_x = bigint_to_scalar(keybinding_x_bigint)?;
-        let keybinding_y = bigint_to_scalar(keybinding_y_bigint)?;
-        let claim_scalars = convert_bigint_to_scalar(claim_bigints)?;
+        let inputs = ShowInputs::from_json(&json_value, &self.params).map_err(input_error)?;
+        let keybinding_x = bigint_to_scalar(inputs.device_key_x)?;
+        let keybinding_y = bigint_to_scalar(inputs.device_key_y)?;
+        let claim_scalars: Vec<Scalar> = inputs
+            .claim
+            .iter()
+            .map(|&byte| Scalar::from(u64::from(byte)))
+            .collect();
 
         let kb_x = AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
         let kb_y = AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
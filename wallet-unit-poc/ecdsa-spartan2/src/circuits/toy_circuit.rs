@@ -0,0 +1,45 @@
+//! [`CircomCircuit`] for the stand-in circuit of `zkid_verifier::test_utils`, so that the prover
+//! tests run in milliseconds without `../circom/build`. Enabled by the `test-utils` feature.
+
+use crate::{
+    circuits::CircomCircuit,
+    inputs::InputError,
+    params::{CircuitParams, JwtCircuitParams},
+    secret::CircomWitness,
+    Scalar,
+};
+use bellpepper_core::SynthesisError;
+use ff::Field;
+use serde_json::Value;
+use std::path::PathBuf;
+use zkid_verifier::test_utils::ToyCircuit;
+
+impl CircomCircuit for ToyCircuit {
+    fn r1cs_path(&self) -> PathBuf {
+        PathBuf::from("toy.r1cs")
+    }
+
+    /// The constant 1 followed by the shared values
+    fn witness(&self) -> Result<CircomWitness, SynthesisError> {
+        Ok(CircomWitness::new(
+            [Scalar::ONE]
+                .into_iter()
+                .chain(self.shared.clone())
+                .collect(),
+        ))
+    }
+
+    fn clear_witness(&self) {}
+
+    fn inputs(&self) -> Result<Value, SynthesisError> {
+        Ok(Value::Null)
+    }
+
+    fn validate_inputs(&self, _: &Value) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn circuit_params(&self) -> CircuitParams {
+        JwtCircuitParams::default().into()
+    }
+}
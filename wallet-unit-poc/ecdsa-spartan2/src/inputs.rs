@@ -0,0 +1,545 @@
+//! Typed circuit inputs, validated against the circuit parameters before witness generation.
+//!
+//! rust-witness does not check its inputs: a short array or an out-of-range byte either panics
+//! inside the generated witness code or yields a witness that fails a constraint deep in the
+//! circuit. Parsing into [`JwtInputs`] / [`ShowInputs`] first reports the offending field instead.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use rust_witness::BigInt;
+use serde_json::Value;
+
+use crate::{
+    params::{JwtCircuitParams, ShowCircuitParams},
+    utils::bigint_to_scalar,
+};
+
+/// An input field that is missing or does not fit the circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    /// Field name, with indices for array elements (e.g. `claims[1][7]`)
+    pub field: String,
+    pub reason: String,
+}
+
+impl InputError {
+    fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid input '{}': {}", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Inputs of the Prepare circuit, `JWT(maxMessageLength, ...)`
+#[derive(Debug, Clone)]
+pub struct JwtInputs {
+    pub message: Vec<u8>,
+    pub message_length: usize,
+    pub period_index: usize,
+    pub matches_count: usize,
+    pub match_substring: Vec<Vec<u8>>,
+    pub match_length: Vec<usize>,
+    pub match_index: Vec<usize>,
+    pub decode_flags: Vec<bool>,
+    pub claims: Vec<Vec<u8>>,
+    pub claim_lengths: Vec<usize>,
+    pub age_claim_index: usize,
+    pub sig_r: BigInt,
+    pub sig_s_inverse: BigInt,
+    pub pub_key_x: BigInt,
+    pub pub_key_y: BigInt,
+}
+
+impl JwtInputs {
+    pub fn from_json(json: &Value, params: &JwtCircuitParams) -> Result<Self, InputError> {
+        let message = byte_array(json, "message", params.max_message_length)?;
+        let message_length = uint(json, "messageLength")?;
+        at_most("messageLength", message_length, params.max_message_length)?;
+        let period_index = uint(json, "periodIndex")?;
+        if period_index >= message_length {
+            return Err(InputError::new(
+                "periodIndex",
+                format!("{period_index} is not below messageLength ({message_length})"),
+            ));
+        }
+
+        let matches_count = uint(json, "matchesCount")?;
+        at_most("matchesCount", matches_count, params.max_matches)?;
+        let match_substring = byte_matrix(
+            json,
+            "matchSubstring",
+            params.max_matches,
+            params.max_substring_length,
+        )?;
+        let match_length = uint_array(json, "matchLength", params.max_matches)?;
+        for (i, &length) in match_length.iter().enumerate() {
+            at_most(
+                &format!("matchLength[{i}]"),
+                length,
+                params.max_substring_length,
+            )?;
+        }
+        let match_index = uint_array(json, "matchIndex", params.max_matches)?;
+        for (i, &index) in match_index.iter().enumerate() {
+            at_most(
+                &format!("matchIndex[{i}]"),
+                index,
+                params.max_message_length,
+            )?;
+        }
+        let decode_flags = uint_array(json, "decodeFlags", params.max_matches)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, flag)| match flag {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(InputError::new(
+                    format!("decodeFlags[{i}]"),
+                    format!("{flag} is not 0 or 1"),
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let claims = byte_matrix(json, "claims", params.max_matches, params.max_claims_length)?;
+        let claim_lengths = uint_array(json, "claimLengths", params.max_matches)?;
+        for (i, &length) in claim_lengths.iter().enumerate() {
+            at_most(
+                &format!("claimLengths[{i}]"),
+                length,
+                params.max_claims_length,
+            )?;
+        }
+        let age_claim_index = uint(json, "ageClaimIndex")?;
+        if age_claim_index >= matches_count {
+            return Err(InputError::new(
+                "ageClaimIndex",
+                format!("{age_claim_index} is not below matchesCount ({matches_count})"),
+            ));
+        }
+
+        Ok(Self {
+            message,
+            message_length,
+            period_index,
+            matches_count,
+            match_substring,
+            match_length,
+            match_index,
+            decode_flags,
+            claims,
+            claim_lengths,
+            age_claim_index,
+            sig_r: field_element(json, "sig_r")?,
+            sig_s_inverse: field_element(json, "sig_s_inverse")?,
+            pub_key_x: field_element(json, "pubKeyX")?,
+            pub_key_y: field_element(json, "pubKeyY")?,
+        })
+    }
+
+    /// Signal name to flattened values, as taken by the rust-witness generator
+    pub fn to_witness_inputs(&self) -> HashMap<String, Vec<BigInt>> {
+        HashMap::from([
+            ("sig_r".into(), vec![self.sig_r.clone()]),
+            ("sig_s_inverse".into(), vec![self.sig_s_inverse.clone()]),
+            ("pubKeyX".into(), vec![self.pub_key_x.clone()]),
+            ("pubKeyY".into(), vec![self.pub_key_y.clone()]),
+            (
+                "messageLength".into(),
+                vec![BigInt::from(self.message_length)],
+            ),
+            ("periodIndex".into(), vec![BigInt::from(self.period_index)]),
+            (
+                "matchesCount".into(),
+                vec![BigInt::from(self.matches_count)],
+            ),
+            ("message".into(), bytes_to_bigints(&self.message)),
+            ("matchIndex".into(), uints_to_bigints(&self.match_index)),
+            ("matchLength".into(), uints_to_bigints(&self.match_length)),
+            ("claimLengths".into(), uints_to_bigints(&self.claim_lengths)),
+            (
+                "decodeFlags".into(),
+                self.decode_flags
+                    .iter()
+                    .map(|&flag| BigInt::from(u8::from(flag)))
+                    .collect(),
+            ),
+            (
+                "matchSubstring".into(),
+                bytes_to_bigints(&self.match_substring.concat()),
+            ),
+            ("claims".into(), bytes_to_bigints(&self.claims.concat())),
+            (
+                "ageClaimIndex".into(),
+                vec![BigInt::from(self.age_claim_index)],
+            ),
+        ])
+    }
+}
+
+/// Inputs of the Show circuit, `Show(maxClaimsLength)`
+#[derive(Debug, Clone)]
+pub struct ShowInputs {
+    pub device_key_x: BigInt,
+    pub device_key_y: BigInt,
+    pub sig_r: BigInt,
+    pub sig_s_inverse: BigInt,
+    pub message_hash: BigInt,
+    /// Decoded age claim, `(maxClaimsLength * 3) / 4` bytes
+    pub claim: Vec<u8>,
+    pub current_year: usize,
+    pub current_month: usize,
+    pub current_day: usize,
+}
+
+impl ShowInputs {
+    pub fn from_json(json: &Value, params: &ShowCircuitParams) -> Result<Self, InputError> {
+        let claim = byte_array(json, "claim", params.decoded_claim_length())?;
+        let current_month = uint(json, "currentMonth")?;
+        in_range("currentMonth", current_month, 1, 12)?;
+        let current_day = uint(json, "currentDay")?;
+        in_range("currentDay", current_day, 1, 31)?;
+
+        Ok(Self {
+            device_key_x: field_element(json, "deviceKeyX")?,
+            device_key_y: field_element(json, "deviceKeyY")?,
+            sig_r: field_element(json, "sig_r")?,
+            sig_s_inverse: field_element(json, "sig_s_inverse")?,
+            message_hash: field_element(json, "messageHash")?,
+            claim,
+            current_year: uint(json, "currentYear")?,
+            current_month,
+            current_day,
+        })
+    }
+
+    /// Signal name to flattened values, as taken by the rust-witness generator
+    pub fn to_witness_inputs(&self) -> HashMap<String, Vec<BigInt>> {
+        HashMap::from([
+            ("deviceKeyX".into(), vec![self.device_key_x.clone()]),
+            ("deviceKeyY".into(), vec![self.device_key_y.clone()]),
+            ("sig_r".into(), vec![self.sig_r.clone()]),
+            ("sig_s_inverse".into(), vec![self.sig_s_inverse.clone()]),
+            ("messageHash".into(), vec![self.message_hash.clone()]),
+            ("claim".into(), bytes_to_bigints(&self.claim)),
+            ("currentYear".into(), vec![BigInt::from(self.current_year)]),
+            (
+                "currentMonth".into(),
+                vec![BigInt::from(self.current_month)],
+            ),
+            ("currentDay".into(), vec![BigInt::from(self.current_day)]),
+        ])
+    }
+}
+
+fn bytes_to_bigints(bytes: &[u8]) -> Vec<BigInt> {
+    bytes.iter().map(|&byte| BigInt::from(byte)).collect()
+}
+
+fn uints_to_bigints(values: &[usize]) -> Vec<BigInt> {
+    values.iter().map(|&value| BigInt::from(value)).collect()
+}
+
+fn at_most(field: &str, value: usize, max: usize) -> Result<(), InputError> {
+    if value > max {
+        return Err(InputError::new(field, format!("{value} exceeds {max}")));
+    }
+    Ok(())
+}
+
+fn in_range(field: &str, value: usize, min: usize, max: usize) -> Result<(), InputError> {
+    if !(min..=max).contains(&value) {
+        return Err(InputError::new(
+            field,
+            format!("{value} is outside {min}..={max}"),
+        ));
+    }
+    Ok(())
+}
+
+fn get<'a>(json: &'a Value, field: &str) -> Result<&'a Value, InputError> {
+    json.get(field)
+        .ok_or_else(|| InputError::new(field, "missing"))
+}
+
+fn array<'a>(value: &'a Value, field: &str, len: usize) -> Result<&'a [Value], InputError> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| InputError::new(field, "must be an array"))?;
+    if items.len() != len {
+        return Err(InputError::new(
+            field,
+            format!("has {} elements, expected {len}", items.len()),
+        ));
+    }
+    Ok(items)
+}
+
+/// Decimal digits only: `BigInt::from_str` would also accept a sign
+fn decimal(value: &Value, field: &str) -> Result<BigInt, InputError> {
+    let digits = value
+        .as_str()
+        .ok_or_else(|| InputError::new(field, "must be a decimal string"))?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InputError::new(
+            field,
+            format!("'{digits}' is not a non-negative decimal integer"),
+        ));
+    }
+    BigInt::from_str(digits).map_err(|e| InputError::new(field, e.to_string()))
+}
+
+/// A non-negative integer given as a JSON number or a decimal string
+fn uint_value(value: &Value, field: &str) -> Result<usize, InputError> {
+    if let Some(n) = value.as_u64() {
+        return usize::try_from(n).map_err(|_| InputError::new(field, format!("{n} is too large")));
+    }
+    if value.is_string() {
+        let n = decimal(value, field)?;
+        return usize::try_from(n.clone())
+            .map_err(|_| InputError::new(field, format!("{n} is too large")));
+    }
+    Err(InputError::new(
+        field,
+        "must be a non-negative integer or decimal string",
+    ))
+}
+
+fn byte_value(value: &Value, field: &str) -> Result<u8, InputError> {
+    let n = uint_value(value, field)?;
+    u8::try_from(n).map_err(|_| InputError::new(field, format!("{n} is not a byte (< 256)")))
+}
+
+fn uint(json: &Value, field: &str) -> Result<usize, InputError> {
+    uint_value(get(json, field)?, field)
+}
+
+/// A scalar signal: decimal string below the field modulus
+fn field_element(json: &Value, field: &str) -> Result<BigInt, InputError> {
+    let n = decimal(get(json, field)?, field)?;
+    bigint_to_scalar(n.clone())
+        .map_err(|_| InputError::new(field, "is not below the field modulus"))?;
+    Ok(n)
+}
+
+fn uint_array(json: &Value, field: &str, len: usize) -> Result<Vec<usize>, InputError> {
+    array(get(json, field)?, field, len)?
+        .iter()
+        .enumerate()
+        .map(|(i, value)| uint_value(value, &format!("{field}[{i}]")))
+        .collect()
+}
+
+fn byte_array(json: &Value, field: &str, len: usize) -> Result<Vec<u8>, InputError> {
+    bytes(get(json, field)?, field, len)
+}
+
+fn bytes(value: &Value, field: &str, len: usize) -> Result<Vec<u8>, InputError> {
+    array(value, field, len)?
+        .iter()
+        .enumerate()
+        .map(|(i, value)| byte_value(value, &format!("{field}[{i}]")))
+        .collect()
+}
+
+fn byte_matrix(
+    json: &Value,
+    field: &str,
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<Vec<u8>>, InputError> {
+    array(get(json, field)?, field, rows)?
+        .iter()
+        .enumerate()
+        .map(|(i, row)| bytes(row, &format!("{field}[{i}]"), cols))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeField;
+    use serde_json::json;
+
+    use super::*;
+    use crate::Scalar;
+
+    /// Prepare parameters small enough to write the inputs out by hand
+    const JWT: JwtCircuitParams = JwtCircuitParams {
+        max_message_length: 8,
+        max_b64_payload_length: 4,
+        max_matches: 2,
+        max_substring_length: 3,
+        max_claims_length: 4,
+    };
+
+    const SHOW: ShowCircuitParams = ShowCircuitParams {
+        max_claims_length: 4,
+    };
+
+    fn jwt_json() -> Value {
+        json!({
+            "message": [101, 121, 74, 46, 101, 121, 0, 0],
+            "messageLength": 6,
+            "periodIndex": 3,
+            "matchesCount": 2,
+            "matchSubstring": [[1, 2, 3], ["4", "5", "6"]],
+            "matchLength": [3, 3],
+            "matchIndex": [0, "4"],
+            "decodeFlags": [0, 1],
+            "claims": [[1, 2, 3, 4], [5, 6, 7, 8]],
+            "claimLengths": [4, 2],
+            "ageClaimIndex": 1,
+            "sig_r": "12",
+            "sig_s_inverse": "34",
+            "pubKeyX": "56",
+            "pubKeyY": "78",
+        })
+    }
+
+    fn show_json() -> Value {
+        json!({
+            "deviceKeyX": "1",
+            "deviceKeyY": "2",
+            "sig_r": "3",
+            "sig_s_inverse": "4",
+            "messageHash": "5",
+            "claim": [1, 2, 255],
+            "currentYear": 2025,
+            "currentMonth": "1",
+            "currentDay": 31,
+        })
+    }
+
+    fn with(mut json: Value, field: &str, value: Value) -> Value {
+        json[field] = value;
+        json
+    }
+
+    fn without(mut json: Value, field: &str) -> Value {
+        json.as_object_mut().unwrap().remove(field);
+        json
+    }
+
+    fn jwt_error(json: Value) -> InputError {
+        JwtInputs::from_json(&json, &JWT).unwrap_err()
+    }
+
+    fn show_error(json: Value) -> InputError {
+        ShowInputs::from_json(&json, &SHOW).unwrap_err()
+    }
+
+    #[test]
+    fn valid_inputs_parse_from_numbers_and_decimal_strings() {
+        let jwt = JwtInputs::from_json(&jwt_json(), &JWT).unwrap();
+        assert_eq!(jwt.match_substring[1], vec![4, 5, 6]);
+        assert_eq!(jwt.match_index, vec![0, 4]);
+        assert_eq!(jwt.decode_flags, vec![false, true]);
+        assert_eq!(jwt.to_witness_inputs()["claims"].len(), 8);
+
+        let show = ShowInputs::from_json(&show_json(), &SHOW).unwrap();
+        assert_eq!(show.claim, vec![1, 2, 255]);
+        assert_eq!(show.current_month, 1);
+        assert_eq!(
+            show.to_witness_inputs()["messageHash"],
+            vec![BigInt::from(5)]
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_named() {
+        for field in ["message", "claimLengths", "ageClaimIndex", "pubKeyY"] {
+            assert_eq!(
+                jwt_error(without(jwt_json(), field)),
+                InputError::new(field, "missing")
+            );
+        }
+        for field in ["deviceKeyX", "messageHash", "claim", "currentDay"] {
+            assert_eq!(
+                show_error(without(show_json(), field)),
+                InputError::new(field, "missing")
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_types_are_rejected() {
+        let err = show_error(with(show_json(), "claim", json!("010203")));
+        assert_eq!(err, InputError::new("claim", "must be an array"));
+
+        let err = show_error(with(show_json(), "deviceKeyX", json!(1)));
+        assert_eq!(
+            err,
+            InputError::new("deviceKeyX", "must be a decimal string")
+        );
+
+        let err = show_error(with(show_json(), "currentYear", json!(true)));
+        assert_eq!(err.field, "currentYear");
+
+        let err = jwt_error(with(jwt_json(), "claims", json!([[1, 2, 3, 4], 5])));
+        assert_eq!(err, InputError::new("claims[1]", "must be an array"));
+
+        let err = jwt_error(with(jwt_json(), "matchIndex", json!([0, "0x4"])));
+        assert_eq!(err.field, "matchIndex[1]");
+    }
+
+    #[test]
+    fn wrong_array_lengths_are_rejected() {
+        let err = show_error(with(show_json(), "claim", json!([1, 2])));
+        assert_eq!(err, InputError::new("claim", "has 2 elements, expected 3"));
+
+        let err = jwt_error(with(jwt_json(), "matchSubstring", json!([[1, 2, 3]])));
+        assert_eq!(err.field, "matchSubstring");
+
+        let err = jwt_error(with(jwt_json(), "claims", json!([[1, 2, 3, 4], [5, 6]])));
+        assert_eq!(err.field, "claims[1]");
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let err = show_error(with(show_json(), "claim", json!([1, 256, 3])));
+        assert_eq!(err.field, "claim[1]");
+
+        let err = show_error(with(show_json(), "currentMonth", json!(13)));
+        assert_eq!(err, InputError::new("currentMonth", "13 is outside 1..=12"));
+
+        let err = show_error(with(show_json(), "currentDay", json!(0)));
+        assert_eq!(err.field, "currentDay");
+
+        let err = show_error(with(show_json(), "sig_r", json!("-3")));
+        assert_eq!(err.field, "sig_r");
+
+        let hex = Scalar::MODULUS.trim_start_matches("0x");
+        let modulus = BigInt::parse_bytes(hex.as_bytes(), 16).unwrap();
+        let err = show_error(with(show_json(), "messageHash", json!(modulus.to_string())));
+        assert_eq!(
+            err,
+            InputError::new("messageHash", "is not below the field modulus")
+        );
+
+        let err = jwt_error(with(jwt_json(), "messageLength", json!(9)));
+        assert_eq!(err, InputError::new("messageLength", "9 exceeds 8"));
+
+        let err = jwt_error(with(jwt_json(), "periodIndex", json!(6)));
+        assert_eq!(err.field, "periodIndex");
+
+        let err = jwt_error(with(jwt_json(), "matchesCount", json!(3)));
+        assert_eq!(err.field, "matchesCount");
+
+        let err = jwt_error(with(jwt_json(), "claimLengths", json!([4, 5])));
+        assert_eq!(err, InputError::new("claimLengths[1]", "5 exceeds 4"));
+
+        let err = jwt_error(with(jwt_json(), "decodeFlags", json!([0, 2])));
+        assert_eq!(err, InputError::new("decodeFlags[1]", "2 is not 0 or 1"));
+
+        let err = jwt_error(with(jwt_json(), "ageClaimIndex", json!(2)));
+        assert_eq!(err.field, "ageClaimIndex");
+    }
+}
@@ -11,24 +11,69 @@ use spartan2::{provider::T256HyraxEngine, traits::Engine};
 pub type E = T256HyraxEngine;
 pub type Scalar = <E as Engine>::Scalar;
 
+#[cfg(feature = "async")]
+pub mod async_prover;
+pub mod check;
 pub mod circuits;
+pub mod inputs;
+pub mod observer;
+pub mod params;
+pub mod presentation;
 pub mod prover;
+pub mod query;
+pub mod secret;
 pub mod setup;
 pub mod utils;
+pub mod variants;
 
 // Re-export commonly used types and functions
-pub use circuits::{prepare_circuit::PrepareCircuit, show_circuit::ShowCircuit};
+#[cfg(feature = "async")]
+pub use async_prover::AsyncProver;
+pub use check::{check_witness, CheckError, SignalValue, UnsatisfiedConstraint};
+pub use circuits::{
+    prepare_circuit::{PrepareCircuit, WitnessGenerator},
+    show_circuit::ShowCircuit,
+    CircomCircuit,
+};
+pub use inputs::{InputError, JwtInputs, ShowInputs};
+pub use observer::{CancellationToken, NoopObserver, ProofObserver, ProofPhase};
+pub use params::{
+    load_jwt_params, load_show_params, CircuitParams, JwtCircuitParams, ParamsError,
+    ShowCircuitParams,
+};
+pub use presentation::{
+    prove_presentation, prove_presentation_with_observer, PresentationArtifacts,
+};
 pub use prover::{
-    generate_prepare_witness, generate_shared_blinds, prove_circuit, prove_circuit_with_pk,
-    reblind, reblind_with_loaded_data, run_circuit, verify_circuit,
-    verify_circuit_with_loaded_data,
+    generate_prepare_witness, generate_prepare_witness_from_json, generate_prepare_witness_with,
+    generate_proof, generate_proof_with_observer, generate_shared_blinds, prove_and_reblind,
+    prove_and_reblind_circuit, prove_and_reblind_with_observer, prove_circuit,
+    prove_circuit_with_pk, reblind, reblind_proof, reblind_proof_with_observer,
+    reblind_with_loaded_data, run_circuit, sample_shared_blinds, verify_circuit,
+    verify_circuit_with_loaded_data, ProofArtifacts, ProverError, ProverKey,
+};
+pub use query::{
+    evaluate_dcql, evaluate_presentation_definition, CircuitRequest, DcqlQuery,
+    PresentationDefinition, QueryError,
+};
+pub use secret::{
+    CircomWitness, SecretBlinds, SecretError, SecretWitness, WipingAllocator, WitnessKey,
 };
 pub use setup::{
-    load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
-    load_witness, save_keys, setup_circuit_keys, setup_circuit_keys_no_save, PREPARE_PROVING_KEY,
+    check_key_params, key_params_path, load_instance, load_instance_with_limits, load_key_params,
+    load_proof, load_proof_with_limits, load_proving_key, load_shared_blinds, load_verifying_key,
+    load_verifying_key_with_limits, load_witness, purge_secrets, save_key_params, save_keys,
+    save_proof, setup_circuit_keys, setup_circuit_keys_no_save, setup_circuit_keys_with_observer,
+    try_setup_circuit_keys, try_setup_circuit_keys_with_observer, PREPARE_PROVING_KEY,
     PREPARE_VERIFYING_KEY, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
 };
 pub use utils::{
-    bigint_to_scalar, calculate_jwt_output_indices, convert_bigint_to_scalar, parse_jwt_inputs,
-    parse_show_inputs,
+    bigint_to_scalar, calculate_jwt_output_indices, calculate_show_input_indices,
+    convert_bigint_to_scalar, parse_jwt_inputs, parse_show_inputs, JwtOutputLayout,
+    ShowInputLayout,
+};
+pub use variants::{CredentialSize, PrepareVariant, VariantError, VariantRegistry};
+pub use zkid_verifier::{
+    challenge_message_hash, check_linkage, verify_presentation, DeserializeLimits, Presentation,
+    VerificationPolicy, VerifiedPresentation, VerifyError,
 };
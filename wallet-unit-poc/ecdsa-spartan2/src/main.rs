@@ -13,23 +13,55 @@
 //! 1. `generate_shared_blinds` — derive shared blinding factors used by both circuits.
 //! 2. `prove_prepare` — produce the initial Prepare proof.
 //! 3. `reblind_prepare` — reblind the Prepare proof without changing its `comm_W_shared`.
-//! 4. `prove_show` — produce the Show proof using the shared witness commitment.
-//! 5. `reblind_show` — reblind the Show proof; the reblinded proof maintains the same `comm_W_shared` as step 3.
+//! 4. `prove_show` — produce and reblind the Show proof in memory; the reblinded proof maintains
+//!    the same `comm_W_shared` as step 3 and the Show witness is never written to disk.
+//!    (`prove_show --persist-witness` followed by `reblind_show` keeps the old two-step flow.)
+//! 5. `presentation encode` — wrap both reblinded proofs in a JSON envelope for HTTP or QR
+//!    transport.
+//!
+//! `inspect --input <file>` decodes any proof, instance or presentation file and reports what it
+//! contains, to debug verifier rejections; `presentation inspect` does the same for the stored
+//! envelope.
 //!
 //! Every proof emitted in this sequence (including the reblinded variants) should verify successfully.
 
 use ecdsa_spartan2::{
-    generate_shared_blinds, load_instance, load_proof, load_shared_blinds, load_witness,
-    prove_circuit, prove_circuit_with_pk, reblind, reblind_with_loaded_data, run_circuit,
-    save_keys, setup::PREPARE_INSTANCE, setup::PREPARE_PROOF, setup::PREPARE_PROVING_KEY,
-    setup::PREPARE_VERIFYING_KEY, setup::PREPARE_WITNESS, setup::SHARED_BLINDS,
-    setup::SHOW_INSTANCE, setup::SHOW_PROOF, setup::SHOW_PROVING_KEY, setup::SHOW_VERIFYING_KEY,
-    setup::SHOW_WITNESS, setup_circuit_keys, setup_circuit_keys_no_save, verify_circuit,
-    verify_circuit_with_loaded_data, PrepareCircuit, ShowCircuit, E,
+    check_linkage, check_witness, generate_proof, generate_shared_blinds, load_instance,
+    load_jwt_params, load_proof, load_proof_with_limits, load_shared_blinds, load_show_params,
+    load_witness, params::CIRCUITS_JSON, prove_and_reblind_circuit, prove_circuit,
+    prove_circuit_with_pk, prove_presentation, purge_secrets, reblind, reblind_proof,
+    reblind_with_loaded_data, run_circuit, save_key_params, save_keys, save_proof,
+    secret::purge_file, setup::PREPARE_INSTANCE, setup::PREPARE_PROOF, setup::PREPARE_PROVING_KEY,
+    setup::PREPARE_VERIFYING_KEY, setup::PREPARE_WITNESS, setup::PRESENTATION,
+    setup::PRESENTATION_BUNDLE, setup::PRESENTATION_BUNDLE_ZSTD, setup::PRESENTATION_ENVELOPE,
+    setup::SHARED_BLINDS, setup::SHOW_INSTANCE, setup::SHOW_PROOF, setup::SHOW_PROVING_KEY,
+    setup::SHOW_VERIFYING_KEY, setup::SHOW_WITNESS, setup_circuit_keys, setup_circuit_keys_no_save,
+    verify_circuit, verify_circuit_with_loaded_data, CircomCircuit, CircuitParams,
+    JwtCircuitParams, PrepareCircuit, ShowCircuit, ShowCircuitParams, WipingAllocator, WitnessKey,
+    E,
+};
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{
+    env::args,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    sync::OnceLock,
+    time::Instant,
 };
-use std::{env::args, fs, path::PathBuf, process, time::Instant};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use zkid_verifier::{
+    bundle_limit, compression, encode_proof_with, inspect,
+    inspect::{Artifact, ComponentSize, ProofReport},
+    inspect_bundle, scalars_to_hex, to_hex, verify, verifying_key_from_bytes, vk_id, BundleInfo,
+    DeserializeLimits, EnvelopeSizes, KnownKey, PointEncoding, Presentation, PresentationBundle,
+    PresentationEnvelope, Proof, Report, VkIds,
+};
+
+/// Wipes freed memory, so that the witnesses Spartan2 copies while proving do not linger
+#[global_allocator]
+static ALLOCATOR: WipingAllocator = WipingAllocator;
 
 const NUM_SHARED: usize = 1;
 
@@ -49,6 +81,9 @@ struct BenchmarkResults {
     reblind_show_ms: u128,
     verify_prepare_ms: u128,
     verify_show_ms: u128,
+    // Wall time of a presentation: Prepare reblind ∥ Show prove + reblind
+    presentation_ms: u128,
+    presentation_threads: Option<usize>,
     // Size measurements in bytes
     prepare_proving_key_bytes: u64,
     prepare_verifying_key_bytes: u64,
@@ -56,10 +91,31 @@ struct BenchmarkResults {
     show_verifying_key_bytes: u64,
     prepare_proof_bytes: u64,
     show_proof_bytes: u64,
+    prepare_proof_sizes: CompressedSizes,
+    show_proof_sizes: CompressedSizes,
     prepare_witness_bytes: u64,
     show_witness_bytes: u64,
 }
 
+/// Sizes of a proof in canonical encoding with compressed points, then also zstd-compressed
+#[derive(Debug)]
+struct CompressedSizes {
+    points: u64,
+    zstd: u64,
+}
+
+impl CompressedSizes {
+    fn measure(proof: &Proof) -> Self {
+        let encoded = encode_proof_with(proof, PointEncoding::Compressed)
+            .expect("proof encoding with compressed points failed");
+        let compressed = compression::compress(&encoded).expect("zstd compression failed");
+        Self {
+            points: encoded.len() as u64,
+            zstd: compressed.len() as u64,
+        }
+    }
+}
+
 impl BenchmarkResults {
     fn format_size(bytes: u64) -> String {
         if bytes < 1024 {
@@ -113,6 +169,16 @@ impl BenchmarkResults {
             "║ Verify Show:            {:>10} ms      ║",
             self.verify_show_ms
         );
+        println!(
+            "║ Presentation (e2e):     {:>10} ms      ║",
+            self.presentation_ms
+        );
+        println!(
+            "║ Presentation Threads:   {:>10}         ║",
+            self.presentation_threads
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "all".into())
+        );
         println!("╠════════════════════════════════════════════════╣");
         println!("║ SIZE MEASUREMENTS                              ║");
         println!("╠════════════════════════════════════════════════╣");
@@ -136,10 +202,12 @@ impl BenchmarkResults {
             "║ Prepare Proof:          {:>12}       ║",
             Self::format_size(self.prepare_proof_bytes)
         );
+        Self::print_compressed_sizes(&self.prepare_proof_sizes);
         println!(
             "║ Show Proof:             {:>12}       ║",
             Self::format_size(self.show_proof_bytes)
         );
+        Self::print_compressed_sizes(&self.show_proof_sizes);
         println!(
             "║ Prepare Witness:        {:>12}       ║",
             Self::format_size(self.prepare_witness_bytes)
@@ -150,6 +218,17 @@ impl BenchmarkResults {
         );
         println!("╚════════════════════════════════════════════════╝\n");
     }
+
+    fn print_compressed_sizes(sizes: &CompressedSizes) {
+        println!(
+            "║   compressed points:    {:>12}       ║",
+            Self::format_size(sizes.points)
+        );
+        println!(
+            "║   + zstd:               {:>12}       ║",
+            Self::format_size(sizes.zstd)
+        );
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -161,24 +240,49 @@ enum CircuitKind {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CircuitAction {
     Run,
+    Check,
     Setup,
     Prove,
     Verify,
     Reblind,
     GenerateSharedBlinds,
-    Benchmark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PresentationAction {
+    Encode,
+    Decode,
 }
 
 #[derive(Debug, Default, Clone)]
 struct CommandOptions {
     input: Option<PathBuf>,
+    threads: Option<usize>,
+    witness_key: Option<PathBuf>,
+    persist_witness: bool,
+    output: Option<PathBuf>,
+    cbor: bool,
+    nonce: Option<Vec<u8>>,
+    compressed_points: bool,
+    zstd: bool,
+    json: bool,
 }
 
 #[derive(Debug, Clone)]
-struct ParsedCommand {
-    circuit: CircuitKind,
-    action: CircuitAction,
-    options: CommandOptions,
+enum Command {
+    /// `prepare <action>` / `show <action>` and their `<action>_<circuit>` aliases
+    Circuit {
+        circuit: CircuitKind,
+        action: CircuitAction,
+        options: CommandOptions,
+    },
+    /// The whole pipeline over both circuits
+    Benchmark(CommandOptions),
+    /// `keys purge`: the stored witnesses and shared blinds of both circuits
+    PurgeKeys,
+    Presentation(PresentationAction, CommandOptions),
+    /// `inspect` and `presentation inspect`: decodes any artifact, whichever circuit it belongs to
+    Inspect(CommandOptions),
 }
 
 fn main() {
@@ -200,21 +304,36 @@ fn main() {
         }
     };
 
-    match command.circuit {
-        CircuitKind::Prepare => execute_prepare(command.action, command.options),
-        CircuitKind::Show => execute_show(command.action, command.options),
+    match command {
+        Command::Circuit {
+            circuit: CircuitKind::Prepare,
+            action,
+            options,
+        } => execute_prepare(action, options),
+        Command::Circuit {
+            circuit: CircuitKind::Show,
+            action,
+            options,
+        } => execute_show(action, options),
+        Command::Benchmark(options) => {
+            run_complete_pipeline(options.input, options.threads).print_summary();
+        }
+        Command::PurgeKeys => purge_keys(),
+        Command::Presentation(action, options) => execute_presentation(action, options),
+        Command::Inspect(options) => execute_inspect(options),
     }
 }
 
 /// Run the complete benchmark pipeline for a given input file
-fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
+fn run_complete_pipeline(input_path: Option<PathBuf>, threads: Option<usize>) -> BenchmarkResults {
     println!("\n╔════════════════════════════════════════════════╗");
     println!("║     STARTING COMPLETE BENCHMARK PIPELINE       ║");
     println!("╚════════════════════════════════════════════════╝\n");
 
     // Step 1: Setup Prepare Circuit
-    info!("Step 1/9: Setting up Prepare circuit...");
-    let prepare_circuit = PrepareCircuit::new(input_path.clone());
+    info!("Step 1/10: Setting up Prepare circuit...");
+    let prepare_circuit = new_prepare_circuit(input_path.clone());
+    let prepare_params = *prepare_circuit.params();
     let t0 = Instant::now();
     let (prepare_pk, prepare_vk) = setup_circuit_keys_no_save(prepare_circuit);
     let prepare_setup_ms = t0.elapsed().as_millis();
@@ -230,10 +349,15 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         eprintln!("Failed to save Prepare keys: {}", e);
         std::process::exit(1);
     }
+    save_benchmark_key_params(
+        &[PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY],
+        &prepare_params.into(),
+    );
 
     // Step 2: Setup Show Circuit
-    info!("Step 2/9: Setting up Show circuit...");
-    let show_circuit = ShowCircuit::new(input_path.clone());
+    info!("Step 2/10: Setting up Show circuit...");
+    let show_circuit = new_show_circuit(input_path.clone());
+    let show_params = *show_circuit.params();
     let t0 = Instant::now();
     let (show_pk, show_vk) = setup_circuit_keys_no_save(show_circuit);
     let show_setup_ms = t0.elapsed().as_millis();
@@ -244,9 +368,10 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         eprintln!("Failed to save Show keys: {}", e);
         std::process::exit(1);
     }
+    save_benchmark_key_params(&[SHOW_PROVING_KEY, SHOW_VERIFYING_KEY], &show_params.into());
 
     // Step 3: Generate Shared Blinds
-    info!("Step 3/9: Generating shared blinds...");
+    info!("Step 3/10: Generating shared blinds...");
     let t0 = Instant::now();
     generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
     let generate_blinds_ms = t0.elapsed().as_millis();
@@ -255,29 +380,30 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     // Note: We already have prepare_pk and show_pk from setup, no need to reload from files
 
     // Step 4: Prove Prepare Circuit
-    info!("Step 4/9: Proving Prepare circuit...");
+    info!("Step 4/10: Proving Prepare circuit...");
     let t0 = Instant::now();
-    let prepare_circuit = PrepareCircuit::new(input_path.clone());
+    let prepare_circuit = new_prepare_circuit(input_path.clone());
     prove_circuit_with_pk(
-        prepare_circuit,
+        prepare_circuit.clone(),
         &prepare_pk,
         PREPARE_INSTANCE,
-        PREPARE_WITNESS,
+        Some(PREPARE_WITNESS),
         PREPARE_PROOF,
+        None,
     );
     let prove_prepare_ms = t0.elapsed().as_millis();
     println!("✓ Prepare proof generated: {} ms\n", prove_prepare_ms);
 
     // Step 5: Reblind Prepare
-    info!("Step 5/9: Reblinding Prepare proof...");
+    info!("Step 5/10: Reblinding Prepare proof...");
     // Load data before timing (file I/O should not be part of reblind benchmark)
     let prepare_instance = load_instance(PREPARE_INSTANCE).expect("load prepare instance failed");
-    let prepare_witness = load_witness(PREPARE_WITNESS).expect("load prepare witness failed");
+    let prepare_witness = load_witness(PREPARE_WITNESS, None).expect("load prepare witness failed");
     let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).expect("load shared_blinds failed");
 
     let t0 = Instant::now();
     reblind_with_loaded_data(
-        PrepareCircuit::default(),
+        prepare_circuit.clone(),
         &prepare_pk,
         prepare_instance,
         prepare_witness,
@@ -285,47 +411,43 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         PREPARE_INSTANCE,
         PREPARE_WITNESS,
         PREPARE_PROOF,
+        None,
     );
     let reblind_prepare_ms = t0.elapsed().as_millis();
     println!("✓ Prepare proof reblinded: {} ms\n", reblind_prepare_ms);
 
-    // Step 6: Prove Show Circuit
-    info!("Step 6/9: Proving Show circuit...");
+    // Step 6: Prove Show Circuit (in memory, the Show witness is never written to disk)
+    info!("Step 6/10: Proving Show circuit...");
     let t0 = Instant::now();
-    let show_circuit = ShowCircuit::new(input_path.clone());
-    prove_circuit_with_pk(
-        show_circuit,
-        &show_pk,
-        SHOW_INSTANCE,
-        SHOW_WITNESS,
-        SHOW_PROOF,
-    );
+    let show_circuit = new_show_circuit(input_path.clone());
+    let show = generate_proof(show_circuit.clone(), &show_pk).expect("prove show failed");
     let prove_show_ms = t0.elapsed().as_millis();
     println!("✓ Show proof generated: {} ms\n", prove_show_ms);
+    let show_witness_bytes =
+        bincode::serialized_size(&show.witness).expect("show witness size failed");
 
     // Step 7: Reblind Show
-    info!("Step 7/9: Reblinding Show proof...");
-    // Load data before timing (file I/O should not be part of reblind benchmark)
-    let show_instance = load_instance(SHOW_INSTANCE).expect("load show instance failed");
-    let show_witness = load_witness(SHOW_WITNESS).expect("load show witness failed");
+    info!("Step 7/10: Reblinding Show proof...");
     // Reuse shared_blinds from Prepare step (already loaded)
-
     let t0 = Instant::now();
-    reblind_with_loaded_data(
-        ShowCircuit::default(),
+    let show = reblind_proof(
+        show_circuit,
         &show_pk,
-        show_instance,
-        show_witness,
+        show.instance,
+        show.witness,
         &shared_blinds,
-        SHOW_INSTANCE,
-        SHOW_WITNESS,
-        SHOW_PROOF,
-    );
+    )
+    .expect("reblind show failed");
     let reblind_show_ms = t0.elapsed().as_millis();
     println!("✓ Show proof reblinded: {} ms\n", reblind_show_ms);
 
+    if let Err(e) = save_proof(SHOW_PROOF, &show.proof) {
+        eprintln!("Failed to save Show proof: {}", e);
+        std::process::exit(1);
+    }
+
     // Step 8: Verify Prepare
-    info!("Step 8/9: Verifying Prepare proof...");
+    info!("Step 8/10: Verifying Prepare proof...");
     // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
     let prepare_proof = load_proof(PREPARE_PROOF).expect("load prepare proof failed");
     // Reuse prepare_vk from setup step (already in memory)
@@ -336,7 +458,7 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     println!("✓ Prepare proof verified: {} ms\n", verify_prepare_ms);
 
     // Step 9: Verify Show
-    info!("Step 9/9: Verifying Show proof...");
+    info!("Step 9/10: Verifying Show proof...");
     // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
     let show_proof = load_proof(SHOW_PROOF).expect("load show proof failed");
     // Reuse show_vk from setup step (already in memory)
@@ -346,6 +468,33 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     let verify_show_ms = t0.elapsed().as_millis();
     println!("✓ Show proof verified: {} ms\n", verify_show_ms);
 
+    // Both reblinded proofs must commit to the same shared values to form a presentation
+    check_linkage(&prepare_proof, &show_proof).expect("comm_W_shared linkage check failed");
+    println!("✓ Prepare and Show proofs are linked by comm_W_shared\n");
+
+    // Step 10: Presentation (Prepare reblind and Show prove + reblind run concurrently)
+    info!("Step 10/10: Building a presentation...");
+    // Load data before timing (file I/O should not be part of presentation benchmark)
+    let prepare_instance = load_instance(PREPARE_INSTANCE).expect("load prepare instance failed");
+    let prepare_witness = load_witness(PREPARE_WITNESS, None).expect("load prepare witness failed");
+
+    let t0 = Instant::now();
+    let presentation = prove_presentation(
+        &prepare_pk,
+        prepare_circuit,
+        prepare_instance,
+        prepare_witness,
+        &show_pk,
+        new_show_circuit(input_path.clone()),
+        &shared_blinds,
+        threads,
+    )
+    .expect("presentation failed");
+    let presentation_ms = t0.elapsed().as_millis();
+    check_linkage(&presentation.prepare.proof, &presentation.show_proof)
+        .expect("presentation linkage check failed");
+    println!("✓ Presentation built: {} ms\n", presentation_ms);
+
     // Measure file sizes
     info!("Measuring artifact sizes...");
     let prepare_proving_key_bytes = get_file_size(PREPARE_PROVING_KEY);
@@ -355,7 +504,8 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     let prepare_proof_bytes = get_file_size(PREPARE_PROOF);
     let show_proof_bytes = get_file_size(SHOW_PROOF);
     let prepare_witness_bytes = get_file_size(PREPARE_WITNESS);
-    let show_witness_bytes = get_file_size(SHOW_WITNESS);
+    let prepare_proof_sizes = CompressedSizes::measure(&prepare_proof);
+    let show_proof_sizes = CompressedSizes::measure(&show_proof);
 
     BenchmarkResults {
         prepare_setup_ms,
@@ -367,12 +517,16 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         reblind_show_ms,
         verify_prepare_ms,
         verify_show_ms,
+        presentation_ms,
+        presentation_threads: threads,
         prepare_proving_key_bytes,
         prepare_verifying_key_bytes,
         show_proving_key_bytes,
         show_verifying_key_bytes,
         prepare_proof_bytes,
         show_proof_bytes,
+        prepare_proof_sizes,
+        show_proof_sizes,
         prepare_witness_bytes,
         show_witness_bytes,
     }
@@ -385,23 +539,28 @@ fn execute_prepare(action: CircuitAction, options: CommandOptions) {
                 input = ?options.input,
                 "Setting up Spartan-2 keys for the Prepare circuit"
             );
-            let circuit = PrepareCircuit::new(options.input.clone());
+            let circuit = new_prepare_circuit(options.input.clone());
             setup_circuit_keys(circuit, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY);
         }
         CircuitAction::Run => {
-            let circuit = PrepareCircuit::new(options.input.clone());
+            let circuit = new_prepare_circuit(options.input.clone());
             info!("Running Prepare circuit with ZK-Spartan");
             run_circuit(circuit);
         }
+        CircuitAction::Check => {
+            info!("Checking Prepare witness against the R1CS");
+            run_check(new_prepare_circuit(options.input.clone()));
+        }
         CircuitAction::Prove => {
-            let circuit = PrepareCircuit::new(options.input.clone());
+            let circuit = new_prepare_circuit(options.input.clone());
             info!("Proving Prepare circuit with ZK-Spartan");
             prove_circuit(
                 circuit,
                 PREPARE_PROVING_KEY,
                 PREPARE_INSTANCE,
-                PREPARE_WITNESS,
+                Some(PREPARE_WITNESS),
                 PREPARE_PROOF,
+                load_witness_key(&options).as_ref(),
             );
         }
         CircuitAction::Verify => {
@@ -411,22 +570,19 @@ fn execute_prepare(action: CircuitAction, options: CommandOptions) {
         CircuitAction::Reblind => {
             info!("Reblind Spartan sumcheck + Hyrax PCS Prepare");
             reblind(
-                PrepareCircuit::default(),
+                new_prepare_circuit(options.input.clone()),
                 PREPARE_PROVING_KEY,
                 PREPARE_INSTANCE,
                 PREPARE_WITNESS,
                 PREPARE_PROOF,
                 SHARED_BLINDS,
+                load_witness_key(&options).as_ref(),
             );
         }
         CircuitAction::GenerateSharedBlinds => {
             info!("Generating shared blinds for Spartan-2 circuits");
             generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
         }
-        CircuitAction::Benchmark => {
-            let results = run_complete_pipeline(options.input);
-            results.print_summary();
-        }
     }
 }
 
@@ -434,52 +590,467 @@ fn execute_show(action: CircuitAction, options: CommandOptions) {
     match action {
         CircuitAction::Setup => {
             info!(input = ?options.input, "Setting up Spartan-2 keys for the Show circuit");
-            let circuit = ShowCircuit::new(options.input.clone());
+            let circuit = new_show_circuit(options.input.clone());
             setup_circuit_keys(circuit, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY);
         }
         CircuitAction::Run => {
-            let circuit = ShowCircuit::new(options.input.clone());
+            let circuit = new_show_circuit(options.input.clone());
             info!("Running Show circuit with ZK-Spartan");
             run_circuit(circuit);
         }
-        CircuitAction::Prove => {
-            let circuit = ShowCircuit::new(options.input.clone());
-            info!("Proving Show circuit with ZK-Spartan");
+        CircuitAction::Check => {
+            info!("Checking Show witness against the R1CS");
+            run_check(new_show_circuit(options.input.clone()));
+        }
+        CircuitAction::Prove if options.persist_witness => {
+            let circuit = new_show_circuit(options.input.clone());
+            info!("Proving Show circuit with ZK-Spartan, keeping the witness for reblind");
             prove_circuit(
                 circuit,
                 SHOW_PROVING_KEY,
                 SHOW_INSTANCE,
-                SHOW_WITNESS,
+                Some(SHOW_WITNESS),
                 SHOW_PROOF,
+                load_witness_key(&options).as_ref(),
             );
         }
+        CircuitAction::Prove => {
+            let circuit = new_show_circuit(options.input.clone());
+            info!("Proving and reblinding Show circuit in memory with ZK-Spartan");
+            prove_and_reblind_circuit(circuit, SHOW_PROVING_KEY, SHARED_BLINDS, SHOW_PROOF);
+            // A witness left by an earlier `--persist-witness` run belongs to another proof; a
+            // later `show reblind` would replace this proof with it
+            purge_show_witness();
+        }
         CircuitAction::Verify => {
             info!("Verifying Show proof with ZK-Spartan");
             verify_circuit(SHOW_PROOF, SHOW_VERIFYING_KEY);
         }
         CircuitAction::Reblind => {
+            if !Path::new(SHOW_WITNESS).exists() {
+                eprintln!(
+                    "Error: no stored Show witness; `show prove` already reblinds in memory (use `show prove --persist-witness` to reblind separately)"
+                );
+                process::exit(1);
+            }
             info!("Reblind Spartan sumcheck + Hyrax PCS Show");
             reblind(
-                ShowCircuit::default(),
+                new_show_circuit(options.input.clone()),
                 SHOW_PROVING_KEY,
                 SHOW_INSTANCE,
                 SHOW_WITNESS,
                 SHOW_PROOF,
                 SHARED_BLINDS,
+                load_witness_key(&options).as_ref(),
             );
+            // The Show witness is only kept for this one reblind
+            purge_show_witness();
         }
         CircuitAction::GenerateSharedBlinds => {
             eprintln!("Error: generate_shared_blinds is only supported for the Prepare circuit");
             process::exit(1);
         }
-        CircuitAction::Benchmark => {
-            let results = run_complete_pipeline(options.input);
-            results.print_summary();
+    }
+}
+
+fn execute_presentation(action: PresentationAction, options: CommandOptions) {
+    let default_encoded = if options.zstd {
+        PRESENTATION_BUNDLE_ZSTD
+    } else if options.cbor {
+        PRESENTATION_BUNDLE
+    } else {
+        PRESENTATION_ENVELOPE
+    };
+    let result = match action {
+        PresentationAction::Encode => {
+            let output = options
+                .output
+                .unwrap_or_else(|| PathBuf::from(default_encoded));
+            info!(output = ?output, cbor = options.cbor, "Encoding the stored proofs as a presentation");
+            let points = if options.compressed_points {
+                PointEncoding::Compressed
+            } else {
+                PointEncoding::Uncompressed
+            };
+            encode_presentation(&output, options.nonce, points, options.zstd)
+        }
+        PresentationAction::Decode => {
+            let input = options
+                .input
+                .unwrap_or_else(|| PathBuf::from(default_encoded));
+            let output = options
+                .output
+                .unwrap_or_else(|| PathBuf::from(PRESENTATION));
+            info!(input = ?input, output = ?output, "Decoding an encoded presentation");
+            decode_presentation(&input, &output)
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Verify the stored reblinded proofs and write them to `output`, as a CBOR bundle answering
+/// `nonce` when one is given (with `points` and optionally zstd-compressed) and as a JSON
+/// envelope otherwise
+fn encode_presentation(
+    output: &Path,
+    nonce: Option<Vec<u8>>,
+    points: PointEncoding,
+    zstd: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (jwt_params, show_params) = circuit_params();
+    let prepare_limits = jwt_params.deserialize_limits();
+    let show_limits = show_params.deserialize_limits();
+
+    let prepare_proof = load_proof_with_limits(PREPARE_PROOF, &prepare_limits)?;
+    let show_proof = load_proof_with_limits(SHOW_PROOF, &show_limits)?;
+    let prepare_vk_bytes = fs::read(PREPARE_VERIFYING_KEY)?;
+    let show_vk_bytes = fs::read(SHOW_VERIFYING_KEY)?;
+    let prepare_vk = verifying_key_from_bytes(&prepare_vk_bytes, &prepare_limits)?;
+    let show_vk = verifying_key_from_bytes(&show_vk_bytes, &show_limits)?;
+
+    // Only package presentations a relying party would accept
+    let prepare_public_values = verify(&prepare_proof, &prepare_vk)?;
+    let show_public_values = verify(&show_proof, &show_vk)?;
+    check_linkage(&prepare_proof, &show_proof)?;
+    let presentation = Presentation::new(prepare_proof, show_proof);
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if let Some(nonce) = nonce {
+        let bundle = PresentationBundle::new(
+            presentation,
+            &prepare_vk_bytes,
+            &show_vk_bytes,
+            prepare_public_values,
+            show_public_values,
+            nonce,
+        );
+        let encoded = bundle.to_cbor_with(points)?;
+        if zstd {
+            fs::write(output, compression::compress(&encoded)?)?;
+        } else {
+            fs::write(output, &encoded)?;
+        }
+        println!("✓ Presentation bundle written to {}", output.display());
+        let encoded = read_bundle(&fs::read(output)?, &prepare_limits)?;
+        print_bundle_info(&inspect_bundle(&encoded, &prepare_limits)?);
+        return Ok(());
+    }
+
+    let envelope = PresentationEnvelope::new(
+        &presentation,
+        VkIds {
+            prepare: vk_id(&prepare_vk_bytes),
+            show: vk_id(&show_vk_bytes),
+        },
+        &prepare_public_values,
+        &show_public_values,
+    )?;
+    fs::write(output, envelope.to_json()?)?;
+    println!("✓ Presentation envelope written to {}", output.display());
+    print_envelope_sizes(&envelope.sizes()?);
+    Ok(())
+}
+
+/// A JSON envelope starts with `{`; a CBOR bundle with a map header
+fn is_json(encoded: &[u8]) -> bool {
+    encoded.trim_ascii_start().first() == Some(&b'{')
+}
+
+/// The CBOR bundle in `encoded`, decompressed first if it is zstd-compressed
+fn read_bundle(
+    encoded: &[u8],
+    limits: &DeserializeLimits,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if !compression::is_compressed(encoded) {
+        return Ok(encoded.to_vec());
+    }
+    let bundle = compression::decompress(encoded, bundle_limit(limits))?;
+    println!(
+        "Compression:      zstd, {} from {}",
+        BenchmarkResults::format_size(encoded.len() as u64),
+        BenchmarkResults::format_size(bundle.len() as u64)
+    );
+    Ok(bundle)
+}
+
+/// Decode the JSON envelope or CBOR bundle at `input` and save the presentation to `output`
+fn decode_presentation(input: &Path, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let encoded = fs::read(input)?;
+    let limits = circuit_params().0.deserialize_limits();
+    let presentation = if is_json(&encoded) {
+        let envelope = PresentationEnvelope::from_json(std::str::from_utf8(&encoded)?)?;
+        print_envelope_sizes(&envelope.sizes()?);
+        envelope.presentation(&limits)?
+    } else {
+        PresentationBundle::from_cbor(&read_bundle(&encoded, &limits)?, &limits)?.presentation()
+    };
+    presentation.save(output)?;
+    println!("✓ Presentation written to {}", output.display());
+    Ok(())
+}
+
+fn execute_inspect(options: CommandOptions) {
+    let Some(input) = options.input else {
+        eprintln!("Error: inspect needs the --input file to decode");
+        process::exit(1);
+    };
+    info!(input = ?input, "Inspecting an encoded artifact");
+    if let Err(e) = inspect_file(&input, options.json) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Decode `input` with the Prepare limits, the larger of the two circuits, and verify the proofs
+/// it contains against whichever stored verifying keys exist
+fn inspect_file(input: &Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs::read(input)?;
+    let (jwt_params, show_params) = circuit_params();
+    let limits = jwt_params.deserialize_limits();
+    let show_limits = show_params.deserialize_limits();
+
+    let mut stored_keys = Vec::new();
+    for (circuit, path, key_limits) in [
+        ("prepare", PREPARE_VERIFYING_KEY, &limits),
+        ("show", SHOW_VERIFYING_KEY, &show_limits),
+    ] {
+        if !Path::new(path).exists() {
+            continue;
+        }
+        let vk_bytes = fs::read(path)?;
+        let vk = verifying_key_from_bytes(&vk_bytes, key_limits)?;
+        stored_keys.push((circuit, vk, vk_id(&vk_bytes)));
+    }
+    let keys: Vec<KnownKey<'_>> = stored_keys
+        .iter()
+        .map(|(circuit, vk, id)| KnownKey {
+            circuit: *circuit,
+            vk,
+            vk_id: id.clone(),
+        })
+        .collect();
+
+    let report = inspect(&bytes, &limits, &keys)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn print_report(report: &Report) {
+    let version = report
+        .format
+        .version
+        .map(|version| format!(" v{version}"))
+        .unwrap_or_default();
+    let mut details = Vec::new();
+    if report.format.compressed_points {
+        details.push("compressed points");
+    }
+    if report.format.zstd {
+        details.push("zstd");
+    }
+    let details = if details.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", details.join(", "))
+    };
+    println!("Format:           {}{version}{details}", report.format.name);
+    println!(
+        "Size:             {}",
+        BenchmarkResults::format_size(report.bytes as u64)
+    );
+    match &report.artifact {
+        Artifact::Proof(proof) => print_proof_report("Proof", proof),
+        Artifact::Instance(instance) => {
+            println!("Artifact:         instance");
+            println!("Shared rows:      {}", instance.shared_rows);
+            print_points(&instance.comm_w_shared);
+            print_components(&instance.components);
+        }
+        Artifact::Presentation(presentation) => {
+            println!("Artifact:         presentation");
+            if let Some(nonce) = &presentation.nonce {
+                println!("Nonce:            {nonce}");
+            }
+            println!(
+                "Linked:           {}",
+                if presentation.linked { "yes" } else { "no" }
+            );
+            print_proof_report("Prepare proof", &presentation.prepare);
+            print_proof_report("Show proof", &presentation.show);
         }
     }
 }
 
-fn parse_command(args: &[String]) -> Result<ParsedCommand, String> {
+fn print_proof_report(title: &str, proof: &ProofReport) {
+    let unknown = || "unknown".to_string();
+    println!("{title}:");
+    println!("  Circuit:        {}", proof.circuit.unwrap_or("unknown"));
+    println!(
+        "  Vk id:          {}",
+        proof.vk_id.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "  Verification:   {}",
+        proof
+            .verification
+            .clone()
+            .unwrap_or_else(|| "no stored key applies".into())
+    );
+    if let Some(values) = &proof.public_values {
+        println!("  Public values:  {values:?}");
+    }
+    if let Some(values) = &proof.claimed_public_values {
+        println!("  Claimed public: {values:?}");
+    }
+    println!("  Shared rows:    {}", proof.shared_rows);
+    print_points(&proof.comm_w_shared);
+    println!(
+        "  Size:           {}",
+        BenchmarkResults::format_size(proof.bytes as u64)
+    );
+    print_components(&proof.components);
+}
+
+fn print_points(points: &[String]) {
+    println!("  comm_W_shared:");
+    for point in points {
+        println!("    {point}");
+    }
+}
+
+fn print_components(components: &[ComponentSize]) {
+    println!("  Components:");
+    for component in components {
+        println!(
+            "    {:<24} {:>12}",
+            component.name,
+            BenchmarkResults::format_size(component.bytes as u64)
+        );
+    }
+}
+
+fn print_envelope_sizes(sizes: &EnvelopeSizes) {
+    println!(
+        "Sizes:            {} binary, {} base64url, {} JSON",
+        BenchmarkResults::format_size(sizes.proof_bytes as u64),
+        BenchmarkResults::format_size(sizes.proof_base64_bytes as u64),
+        BenchmarkResults::format_size(sizes.envelope_bytes as u64)
+    );
+}
+
+fn print_bundle_info(info: &BundleInfo) {
+    println!("Format:           zkid-spartan2 v{} (CBOR)", info.version);
+    println!("Prepare vk id:    {}", to_hex(&info.prepare_vk_id));
+    println!("Show vk id:       {}", to_hex(&info.show_vk_id));
+    println!("Nonce:            {}", to_hex(&info.nonce));
+    println!(
+        "Prepare public:   {:?}",
+        scalars_to_hex(&info.prepare_public_values)
+    );
+    println!(
+        "Show public:      {:?}",
+        scalars_to_hex(&info.show_public_values)
+    );
+    println!(
+        "Sizes:            {} Prepare proof, {} Show proof, {} bundle",
+        BenchmarkResults::format_size(info.prepare_proof_bytes as u64),
+        BenchmarkResults::format_size(info.show_proof_bytes as u64),
+        BenchmarkResults::format_size(info.bundle_bytes as u64)
+    );
+}
+
+fn save_benchmark_key_params(key_paths: &[&str], params: &CircuitParams) {
+    for key_path in key_paths {
+        if let Err(e) = save_key_params(key_path, params) {
+            eprintln!("Failed to save circuit parameters: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prepare and Show parameters from `circuits.json`, or the built-in defaults when it is absent.
+/// The file is read once per run.
+fn circuit_params() -> (JwtCircuitParams, ShowCircuitParams) {
+    static PARAMS: OnceLock<(JwtCircuitParams, ShowCircuitParams)> = OnceLock::new();
+    *PARAMS.get_or_init(|| {
+        let path = Path::new(CIRCUITS_JSON);
+        if !path.exists() {
+            return Default::default();
+        }
+        let params =
+            load_jwt_params(path, "jwt").and_then(|jwt| Ok((jwt, load_show_params(path, "show")?)));
+        match params {
+            Ok(params) => params,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+    })
+}
+
+fn new_prepare_circuit(input: Option<PathBuf>) -> PrepareCircuit {
+    PrepareCircuit::new(input).with_params(circuit_params().0)
+}
+
+fn new_show_circuit(input: Option<PathBuf>) -> ShowCircuit {
+    ShowCircuit::new(input).with_params(circuit_params().1)
+}
+
+/// Check the witness of `circuit` and report the first failing constraint, exiting on failure
+fn run_check<C: SpartanCircuit<E> + CircomCircuit>(circuit: C) {
+    match check_witness(&circuit) {
+        Ok(num_constraints) => {
+            println!("✓ Witness satisfies all {num_constraints} constraints");
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Read the `--witness-key` file, if any, exiting on failure
+fn load_witness_key(options: &CommandOptions) -> Option<WitnessKey> {
+    let path = options.witness_key.as_ref()?;
+    match WitnessKey::load(&path.to_string_lossy()) {
+        Ok(key) => Some(key),
+        Err(e) => {
+            eprintln!("Failed to load witness key: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn purge_show_witness() {
+    match purge_file(SHOW_WITNESS) {
+        Ok(true) => info!("Purged {}", SHOW_WITNESS),
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("Failed to purge {}: {}", SHOW_WITNESS, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn purge_keys() {
+    info!("Purging stored witnesses and shared blinds");
+    if let Err(e) = purge_secrets() {
+        eprintln!("Failed to purge secrets: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
     if args.is_empty() {
         return Err("No command provided".into());
     }
@@ -491,63 +1062,90 @@ fn parse_command(args: &[String]) -> Result<ParsedCommand, String> {
         }
         "prepare" => parse_circuit_command(CircuitKind::Prepare, &args[1..]),
         "show" => parse_circuit_command(CircuitKind::Show, &args[1..]),
-        "benchmark" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare, // Benchmark runs both circuits, but we need to pick one for the enum
-            action: CircuitAction::Benchmark,
-            options: parse_options(&args[1..])?,
-        }),
-        "setup_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Setup,
-            options: parse_options(&args[1..])?,
-        }),
-        "setup_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Setup,
-            options: parse_options(&args[1..])?,
-        }),
-        "prove_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Prove,
-            options: parse_options(&args[1..])?,
-        }),
-        "prove_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Prove,
-            options: parse_options(&args[1..])?,
-        }),
-        "verify_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Verify,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "verify_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Verify,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "reblind_prepare" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::Reblind,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "reblind_show" => Ok(ParsedCommand {
-            circuit: CircuitKind::Show,
-            action: CircuitAction::Reblind,
-            options: ensure_no_options(&args[1..])?,
-        }),
-        "generate_shared_blinds" => Ok(ParsedCommand {
-            circuit: CircuitKind::Prepare,
-            action: CircuitAction::GenerateSharedBlinds,
-            options: ensure_no_options(&args[1..])?,
-        }),
+        "keys" => match args.get(1).map(String::as_str) {
+            Some("purge") => {
+                ensure_no_options(&args[2..])?;
+                Ok(Command::PurgeKeys)
+            }
+            Some(other) => Err(format!("Unknown keys action '{other}'. Expected purge.")),
+            None => Err("Missing keys action. Expected purge.".into()),
+        },
+        "presentation" => {
+            let action = match args.get(1).map(String::as_str) {
+                Some("encode") => PresentationAction::Encode,
+                Some("decode") => PresentationAction::Decode,
+                Some("inspect") => {
+                    return Ok(Command::Inspect(parse_inspect_options(
+                        &args[2..],
+                        Some(PRESENTATION_ENVELOPE),
+                    )?))
+                }
+                Some(other) => {
+                    return Err(format!(
+                        "Unknown presentation action '{other}'. Expected encode|decode|inspect."
+                    ))
+                }
+                None => {
+                    return Err(
+                        "Missing presentation action. Expected encode|decode|inspect.".into(),
+                    )
+                }
+            };
+            Ok(Command::Presentation(
+                action,
+                parse_presentation_options(action, &args[2..])?,
+            ))
+        }
+        "inspect" => Ok(Command::Inspect(parse_inspect_options(&args[1..], None)?)),
+        "benchmark" => parse_benchmark_command(&args[1..]),
+        "setup_prepare" => parse_legacy_command(CircuitKind::Prepare, "setup", &args[1..]),
+        "setup_show" => parse_legacy_command(CircuitKind::Show, "setup", &args[1..]),
+        "prove_prepare" => parse_legacy_command(CircuitKind::Prepare, "prove", &args[1..]),
+        "prove_show" => parse_legacy_command(CircuitKind::Show, "prove", &args[1..]),
+        "verify_prepare" => parse_legacy_command(CircuitKind::Prepare, "verify", &args[1..]),
+        "verify_show" => parse_legacy_command(CircuitKind::Show, "verify", &args[1..]),
+        "reblind_prepare" => parse_legacy_command(CircuitKind::Prepare, "reblind", &args[1..]),
+        "reblind_show" => parse_legacy_command(CircuitKind::Show, "reblind", &args[1..]),
+        "generate_shared_blinds" => {
+            parse_legacy_command(CircuitKind::Prepare, "generate_shared_blinds", &args[1..])
+        }
         other => Err(format!("Unknown command '{other}'")),
     }
 }
 
-fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<ParsedCommand, String> {
+/// `<action>_<circuit>` aliases, validated exactly like `<circuit> <action>`
+fn parse_legacy_command(
+    circuit: CircuitKind,
+    action: &str,
+    options: &[String],
+) -> Result<Command, String> {
+    let tail: Vec<String> = [action.to_string()]
+        .into_iter()
+        .chain(options.iter().cloned())
+        .collect();
+    parse_circuit_command(circuit, &tail)
+}
+
+/// `benchmark`, also accepted as `prepare benchmark` / `show benchmark`: it runs both circuits
+fn parse_benchmark_command(args: &[String]) -> Result<Command, String> {
+    let options = parse_options(args)?;
+    if options.witness_key.is_some()
+        || options.persist_witness
+        || options.output.is_some()
+        || options.cbor
+        || options.nonce.is_some()
+        || options.compressed_points
+        || options.zstd
+        || options.json
+    {
+        return Err("The benchmark command only supports the --input and --threads options".into());
+    }
+    Ok(Command::Benchmark(options))
+}
+
+fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<Command, String> {
     if tail.is_empty() {
-        return Ok(ParsedCommand {
+        return Ok(Command::Circuit {
             circuit,
             action: CircuitAction::Run,
             options: CommandOptions::default(),
@@ -557,16 +1155,17 @@ fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<Parsed
     let first = &tail[0];
     let (action, option_start) = match first.as_str() {
         "run" => (CircuitAction::Run, 1),
+        "check" => (CircuitAction::Check, 1),
         "setup" => (CircuitAction::Setup, 1),
         "prove" => (CircuitAction::Prove, 1),
         "verify" => (CircuitAction::Verify, 1),
         "reblind" => (CircuitAction::Reblind, 1),
         "generate_shared_blinds" => (CircuitAction::GenerateSharedBlinds, 1),
-        "benchmark" => (CircuitAction::Benchmark, 1),
+        "benchmark" => return parse_benchmark_command(&tail[1..]),
         s if s.starts_with('-') => (CircuitAction::Run, 0),
         other => {
             return Err(format!(
-                "Unknown action '{other}' for {:?}. Expected one of run|setup|prove|verify|reblind|generate_shared_blinds|benchmark.",
+                "Unknown action '{other}' for {:?}. Expected one of run|check|setup|prove|verify|reblind|generate_shared_blinds|benchmark.",
                 circuit
             ))
         }
@@ -581,15 +1180,50 @@ fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<Parsed
     let options_slice = &tail[option_start..];
     let options = match action {
         CircuitAction::Run
+        | CircuitAction::Check
         | CircuitAction::Prove
         | CircuitAction::Setup
-        | CircuitAction::Benchmark => parse_options(options_slice)?,
-        CircuitAction::Verify | CircuitAction::Reblind | CircuitAction::GenerateSharedBlinds => {
+        | CircuitAction::Reblind => parse_options(options_slice)?,
+        CircuitAction::Verify | CircuitAction::GenerateSharedBlinds => {
             ensure_no_options(options_slice)?
         }
     };
+    if options.output.is_some()
+        || options.cbor
+        || options.nonce.is_some()
+        || options.compressed_points
+        || options.zstd
+    {
+        return Err(
+            "The --output, --cbor, --nonce, --compressed-points and --zstd options are only supported for presentation commands"
+                .into(),
+        );
+    }
+    if options.json {
+        return Err("The --json option is only supported for inspect".into());
+    }
+    if options.input.is_some() && action == CircuitAction::Reblind {
+        return Err("The --input option is not supported for reblind".into());
+    }
+    if options.threads.is_some() {
+        return Err("The --threads option is only supported for benchmark".into());
+    }
+    if options.witness_key.is_some()
+        && !matches!(action, CircuitAction::Prove | CircuitAction::Reblind)
+    {
+        return Err("The --witness-key option is only supported for prove and reblind".into());
+    }
+    if options.persist_witness && (circuit, action) != (CircuitKind::Show, CircuitAction::Prove) {
+        return Err("The --persist-witness option is only supported for show prove".into());
+    }
+    if options.witness_key.is_some()
+        && (circuit, action) == (CircuitKind::Show, CircuitAction::Prove)
+        && !options.persist_witness
+    {
+        return Err("The --witness-key option requires --persist-witness for show prove".into());
+    }
 
-    Ok(ParsedCommand {
+    Ok(Command::Circuit {
         circuit,
         action,
         options,
@@ -621,6 +1255,54 @@ fn parse_options(args: &[String]) -> Result<CommandOptions, String> {
                 return Err("Missing value for --input".into());
             }
             options.input = Some(PathBuf::from(value));
+        } else if arg == "--threads" || arg == "-t" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --threads".to_string())?;
+            options.threads = Some(parse_threads(value)?);
+        } else if let Some(value) = arg.strip_prefix("--threads=") {
+            options.threads = Some(parse_threads(value)?);
+        } else if arg == "--witness-key" || arg == "-k" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --witness-key".to_string())?;
+            options.witness_key = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--witness-key=") {
+            if value.is_empty() {
+                return Err("Missing value for --witness-key".into());
+            }
+            options.witness_key = Some(PathBuf::from(value));
+        } else if arg == "--output" || arg == "-o" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --output".to_string())?;
+            options.output = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--output=") {
+            if value.is_empty() {
+                return Err("Missing value for --output".into());
+            }
+            options.output = Some(PathBuf::from(value));
+        } else if arg == "--cbor" {
+            options.cbor = true;
+        } else if arg == "--nonce" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --nonce".to_string())?;
+            options.nonce = Some(parse_hex(value, "--nonce")?);
+        } else if let Some(value) = arg.strip_prefix("--nonce=") {
+            options.nonce = Some(parse_hex(value, "--nonce")?);
+        } else if arg == "--compressed-points" {
+            options.compressed_points = true;
+        } else if arg == "--zstd" {
+            options.zstd = true;
+        } else if arg == "--json" {
+            options.json = true;
+        } else if arg == "--persist-witness" {
+            options.persist_witness = true;
         } else if arg == "--help" || arg == "-h" {
             print_usage();
             process::exit(0);
@@ -633,19 +1315,110 @@ fn parse_options(args: &[String]) -> Result<CommandOptions, String> {
     Ok(options)
 }
 
+fn parse_presentation_options(
+    action: PresentationAction,
+    args: &[String],
+) -> Result<CommandOptions, String> {
+    let options = parse_options(args)?;
+    if options.threads.is_some()
+        || options.witness_key.is_some()
+        || options.persist_witness
+        || options.json
+    {
+        return Err(
+            "Presentation commands only take --input, --output, --cbor, --nonce, --compressed-points and --zstd"
+                .into(),
+        );
+    }
+    if options.cbor != options.nonce.is_some() && action == PresentationAction::Encode {
+        return Err(
+            "presentation encode takes --cbor together with the relying party's --nonce".into(),
+        );
+    }
+    if options.nonce.is_some() && action != PresentationAction::Encode {
+        return Err("The --nonce option is only supported for presentation encode".into());
+    }
+    if (options.compressed_points || options.zstd)
+        && (action != PresentationAction::Encode || !options.cbor)
+    {
+        return Err(
+            "The --compressed-points and --zstd options are only supported for presentation encode --cbor; decode and inspect detect them"
+                .into(),
+        );
+    }
+    if options.input.is_some() && action == PresentationAction::Encode {
+        return Err("presentation encode reads the stored proofs and takes no --input".into());
+    }
+    Ok(options)
+}
+
+/// Options of `inspect`; `presentation inspect` reads `default_input` when no --input is given
+fn parse_inspect_options(
+    args: &[String],
+    default_input: Option<&str>,
+) -> Result<CommandOptions, String> {
+    let mut options = parse_options(args)?;
+    if options.threads.is_some()
+        || options.witness_key.is_some()
+        || options.persist_witness
+        || options.output.is_some()
+        || options.cbor
+        || options.nonce.is_some()
+        || options.compressed_points
+        || options.zstd
+    {
+        return Err("inspect only takes --input and --json".into());
+    }
+    if options.input.is_none() {
+        options.input = default_input.map(PathBuf::from);
+    }
+    if options.input.is_none() {
+        return Err("inspect needs the --input file to decode".into());
+    }
+    Ok(options)
+}
+
+fn parse_hex(value: &str, option: &str) -> Result<Vec<u8>, String> {
+    let invalid = || format!("Invalid hex value '{value}' for {option}");
+    if value.is_empty() || value.len() % 2 != 0 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|_| invalid()))
+        .collect()
+}
+
+fn parse_threads(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("Invalid value '{value}' for --threads")),
+    }
+}
+
 fn print_usage() {
     eprintln!(
         "Usage:
-  ecdsa-spartan2 <prepare|show> [run|setup|prove|verify] [options]
+  ecdsa-spartan2 <prepare|show> [run|check|setup|prove|verify] [options]
   ecdsa-spartan2 benchmark [options]
+  ecdsa-spartan2 keys purge
+  ecdsa-spartan2 presentation <encode|decode|inspect> [options]
+  ecdsa-spartan2 inspect --input <path> [--json]
 
 Commands:
   benchmark            Run complete pipeline with full metrics (setup, prove, reblind, verify)
   prepare <action>     Run action on Prepare circuit
   show <action>        Run action on Show circuit
+  keys purge           Overwrite and delete stored witnesses and shared blinds
+  presentation encode  Verify the stored proofs and write them as a JSON envelope or CBOR bundle
+  presentation decode  Write the presentation of a JSON envelope or CBOR bundle in binary form
+  inspect              Decode a proof, instance or presentation in any encoding, verify it against
+                       the stored keys and print its circuit, public values, comm_W_shared and sizes
+                       (`presentation inspect` reads the stored envelope by default)
 
 Actions:
   run                  Run the complete circuit (setup, prove, verify)
+  check                Check the witness against the R1CS and report the first failing constraint
   setup                Generate proving and verifying keys
   prove                Generate proof
   verify               Verify proof
@@ -653,13 +1426,32 @@ Actions:
   benchmark            Run complete benchmark pipeline
 
 Options:
-  --input, -i <path>   Override the circuit input JSON (run/prove/setup/benchmark)
+  --input, -i <path>   Override the circuit input JSON (run/check/prove/setup/benchmark)
+                       or the envelope read (presentation decode) or the file (inspect)
+  --threads, -t <n>    Cap the threads used to build a presentation (benchmark)
+  --witness-key, -k <path>
+                       Encrypt/decrypt the stored witness with a 32-byte key file (prove/reblind)
+  --persist-witness    Keep the Show witness for a separate `show reblind` (show prove)
+  --output, -o <path>  Override the envelope (encode) or presentation (decode) written
+  --cbor               Encode a deterministic CBOR bundle instead of a JSON envelope (presentation)
+  --nonce <hex>        Relying party challenge carried in the CBOR bundle (presentation encode)
+  --compressed-points  Write the points of the bundled proofs compressed (presentation encode)
+  --zstd               zstd-compress the CBOR bundle, e.g. for QR codes (presentation encode)
+  --json               Print the report as JSON (inspect)
 
 Examples:
   cargo run --release -- benchmark --input ../circom/inputs/jwt/generated.json
+  cargo run --release -- benchmark --threads 4
   cargo run --release -- prepare run --input ../circom/inputs/jwt/generated.json
   cargo run --release -- show prove --input ../circom/inputs/show/generated.json
   cargo run --release -- show verify
+  cargo run --release -- prepare prove --witness-key keys/witness.key
+  cargo run --release -- keys purge
+  cargo run --release -- presentation encode --output presentation.json
+  cargo run --release -- presentation inspect
+  cargo run --release -- presentation encode --cbor --nonce 00112233
+  cargo run --release -- presentation encode --cbor --nonce 00112233 --compressed-points --zstd
+  cargo run --release -- inspect --input keys/show_proof.bin --json
 
 Legacy commands like `prepare`, `show`, `prove_prepare`, etc. are still supported."
     );
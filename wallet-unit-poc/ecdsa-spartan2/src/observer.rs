@@ -0,0 +1,185 @@
+//! Progress reporting and cancellation for long-running setup, prove and reblind calls.
+//!
+//! Spartan2 exposes no hooks inside its proving steps, so phases are reported at the boundaries
+//! between the calls made by the prover, and cancellation is checked at those same boundaries.
+//! The one exception is the PCS opening at the end of `prove_inner`, whose start is taken from the
+//! `pcs_prove` tracing span Spartan2 opens around it (see [`PcsOpenHook`]).
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tracing::{
+    dispatcher,
+    level_filters::LevelFilter,
+    span::{Attributes, Id, Record},
+    subscriber::Interest,
+    Dispatch, Event, Metadata, Subscriber,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofPhase {
+    /// Key generation (`R1CSSNARK::setup`)
+    Setup,
+    /// Circom witness generation (rust-witness) from the circuit inputs
+    WitnessGeneration,
+    /// `prep_prove`: synthesizes and commits to the shared and precommitted values
+    PrepProve,
+    /// Synthesis with the generated witness and commitment to the rest of it
+    WitnessCommit,
+    /// Spartan outer and inner sumchecks (first part of `prove_inner`)
+    Sumcheck,
+    /// Hyrax PCS opening of the witness (end of `prove_inner`)
+    PcsOpen,
+    /// Reblinding of a proven instance and witness with the shared blinds
+    Reblind,
+}
+
+impl ProofPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProofPhase::Setup => "setup",
+            ProofPhase::WitnessGeneration => "witness_generation",
+            ProofPhase::PrepProve => "prep_prove",
+            ProofPhase::WitnessCommit => "witness_commit",
+            ProofPhase::Sumcheck => "sumcheck",
+            ProofPhase::PcsOpen => "pcs_open",
+            ProofPhase::Reblind => "reblind",
+        }
+    }
+}
+
+/// Receives phase notifications and decides whether a running proof should be aborted.
+///
+/// All methods have no-op defaults so observers only implement what they need. The end of
+/// [`ProofPhase::Sumcheck`] and the start of [`ProofPhase::PcsOpen`] are reported from a helper
+/// thread while `prove_inner` runs.
+pub trait ProofObserver: Send + Sync {
+    fn on_phase_start(&self, _phase: ProofPhase) {}
+
+    fn on_phase_end(&self, _phase: ProofPhase, _elapsed: Duration) {}
+
+    /// Checked before and after every phase; returning `true` aborts with `ProverError::Cancelled`.
+    /// It is not checked between the sumchecks and the PCS opening, which run in one Spartan2 call.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Observer used by the non-observed entry points
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl ProofObserver for NoopObserver {}
+
+/// Cloneable flag to cancel a proof from another thread (e.g. a UI "cancel" button)
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl ProofObserver for CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        CancellationToken::is_cancelled(self)
+    }
+}
+
+/// Name of the span Spartan2 opens around the PCS opening, after the sumchecks of `prove_inner`
+const PCS_PROVE_SPAN: &str = "pcs_prove";
+
+/// Dispatcher forwarding everything to the caller's dispatcher, which sends on `opened` the first
+/// time Spartan2 opens its `pcs_prove` span.
+///
+/// Installed with `dispatcher::with_default` around `prove_inner`, so it only sees the spans of the
+/// calling thread; a hook already installed on that thread (a nested proof run by rayon
+/// work-stealing) is bypassed so that each proof only signals its own opening.
+pub(crate) struct PcsOpenHook {
+    inner: Dispatch,
+    opened: Arc<Mutex<Option<Sender<()>>>>,
+}
+
+impl PcsOpenHook {
+    pub(crate) fn new(opened: Arc<Mutex<Option<Sender<()>>>>) -> Self {
+        let inner = dispatcher::get_default(|current| match current.downcast_ref::<Self>() {
+            Some(hook) => hook.inner.clone(),
+            None => current.clone(),
+        });
+        Self { inner, opened }
+    }
+}
+
+fn is_pcs_prove_span(metadata: &Metadata<'_>) -> bool {
+    metadata.is_span() && metadata.name() == PCS_PROVE_SPAN
+}
+
+impl Subscriber for PcsOpenHook {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if is_pcs_prove_span(metadata) {
+            // Ask for `enabled` at every creation, whatever the caller's dispatcher filters out
+            return Interest::sometimes();
+        }
+        self.inner.register_callsite(metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        None
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        if is_pcs_prove_span(metadata) {
+            if let Some(opened) = self.opened.lock().ok().and_then(|mut opened| opened.take()) {
+                let _ = opened.send(());
+            }
+        }
+        self.inner.enabled(metadata)
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.inner.new_span(span)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        self.inner.record(span, values)
+    }
+
+    fn record_follows_from(&self, span: &Id, follows: &Id) {
+        self.inner.record_follows_from(span, follows)
+    }
+
+    fn event(&self, event: &Event<'_>) {
+        self.inner.event(event)
+    }
+
+    fn enter(&self, span: &Id) {
+        self.inner.enter(span)
+    }
+
+    fn exit(&self, span: &Id) {
+        self.inner.exit(span)
+    }
+
+    fn clone_span(&self, id: &Id) -> Id {
+        self.inner.clone_span(id)
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        self.inner.try_close(id)
+    }
+}
@@ -0,0 +1,262 @@
+//! Template parameters of the circom circuits, as instantiated in `circom/circuits.json`.
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use zkid_verifier::DeserializeLimits;
+
+use crate::utils::{
+    calculate_jwt_output_indices, calculate_show_input_indices, JwtOutputLayout, ShowInputLayout,
+};
+
+/// Default location of `circuits.json`, relative to the crate directory
+pub const CIRCUITS_JSON: &str = "../circom/circuits.json";
+
+const KIB: u64 = 1024;
+const MIB: u64 = 1024 * KIB;
+
+/// Parameters of `JWT(maxMessageLength, maxB64PayloadLength, maxMatches, maxSubstringLength,
+/// maxClaimsLength)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JwtCircuitParams {
+    pub max_message_length: usize,
+    pub max_b64_payload_length: usize,
+    pub max_matches: usize,
+    pub max_substring_length: usize,
+    pub max_claims_length: usize,
+}
+
+impl JwtCircuitParams {
+    /// Length of each decoded claim, `(maxClaimsLength * 3) / 4`
+    pub fn decoded_claim_length(&self) -> usize {
+        (self.max_claims_length * 3) / 4
+    }
+
+    /// Position of the circuit outputs in the witness
+    pub fn output_layout(&self) -> JwtOutputLayout {
+        calculate_jwt_output_indices(self)
+    }
+
+    /// Size limits for the Prepare proof, instance and verifying key of this instantiation.
+    ///
+    /// Keys grow linearly with `maxMessageLength` (420 MB at 1920 bytes) and proofs much more
+    /// slowly (109 KB at 1920 bytes); both bounds leave headroom over the measured sizes.
+    pub fn deserialize_limits(&self) -> DeserializeLimits {
+        let message_length = self.max_message_length as u64;
+        let proof = 64 * KIB + 64 * message_length;
+        DeserializeLimits {
+            proof,
+            verifying_key: 256 * KIB * message_length,
+            instance: 2 * proof,
+        }
+    }
+
+    /// The Show instantiation sharing this circuit's claim (`Show(maxClaimsLength)`)
+    pub fn show_params(&self) -> ShowCircuitParams {
+        ShowCircuitParams {
+            max_claims_length: self.max_claims_length,
+        }
+    }
+
+    fn from_params(params: &[usize]) -> Result<Self, ParamsError> {
+        match *params {
+            [max_message_length, max_b64_payload_length, max_matches, max_substring_length, max_claims_length] => {
+                Ok(Self {
+                    max_message_length,
+                    max_b64_payload_length,
+                    max_matches,
+                    max_substring_length,
+                    max_claims_length,
+                })
+            }
+            _ => Err(ParamsError::Arity {
+                template: "JWT",
+                expected: 5,
+                actual: params.len(),
+            }),
+        }
+    }
+}
+
+impl Default for JwtCircuitParams {
+    /// `JWT(1920, 1900, 4, 50, 128)`, the instantiation in `circom/circuits.json`
+    fn default() -> Self {
+        Self {
+            max_message_length: 1920,
+            max_b64_payload_length: 1900,
+            max_matches: 4,
+            max_substring_length: 50,
+            max_claims_length: 128,
+        }
+    }
+}
+
+/// Parameters of `Show(maxClaimsLength)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowCircuitParams {
+    pub max_claims_length: usize,
+}
+
+impl ShowCircuitParams {
+    /// Length of the decoded claim input, `(maxClaimsLength * 3) / 4`
+    pub fn decoded_claim_length(&self) -> usize {
+        (self.max_claims_length * 3) / 4
+    }
+
+    /// Position of the inputs shared with Prepare in the witness
+    pub fn input_layout(&self) -> ShowInputLayout {
+        calculate_show_input_indices(self)
+    }
+
+    /// Size limits for the Show proof, instance and verifying key (40 KB and 3.5 MB measured)
+    pub fn deserialize_limits(&self) -> DeserializeLimits {
+        DeserializeLimits {
+            proof: 256 * KIB,
+            verifying_key: 16 * MIB,
+            instance: 512 * KIB,
+        }
+    }
+
+    fn from_params(params: &[usize]) -> Result<Self, ParamsError> {
+        match *params {
+            [max_claims_length] => Ok(Self { max_claims_length }),
+            _ => Err(ParamsError::Arity {
+                template: "Show",
+                expected: 1,
+                actual: params.len(),
+            }),
+        }
+    }
+}
+
+impl Default for ShowCircuitParams {
+    /// `Show(128)`, the instantiation in `circom/circuits.json`
+    fn default() -> Self {
+        Self {
+            max_claims_length: 128,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// No circuit with this name in `circuits.json`
+    MissingCircuit(String),
+    Template {
+        expected: &'static str,
+        actual: String,
+    },
+    Arity {
+        template: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::Io(e) => write!(f, "failed to read circuits.json: {e}"),
+            ParamsError::Json(e) => write!(f, "invalid circuits.json: {e}"),
+            ParamsError::MissingCircuit(name) => {
+                write!(f, "circuit '{name}' not found in circuits.json")
+            }
+            ParamsError::Template { expected, actual } => {
+                write!(f, "expected a {expected} circuit, found {actual}")
+            }
+            ParamsError::Arity {
+                template,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{template} takes {expected} parameters, circuits.json gives {actual}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// A template instantiation: an entry of `circuits.json`, also stored next to setup keys
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitParams {
+    pub template: String,
+    #[serde(default)]
+    pub params: Vec<usize>,
+}
+
+impl CircuitParams {
+    /// Parameters of circuit `name` in a `circuits.json` file
+    pub fn load(circuits_json: &Path, name: &str) -> Result<Self, ParamsError> {
+        let contents = fs::read_to_string(circuits_json).map_err(ParamsError::Io)?;
+        let mut circuits: HashMap<String, CircuitParams> =
+            serde_json::from_str(&contents).map_err(ParamsError::Json)?;
+        circuits
+            .remove(name)
+            .ok_or_else(|| ParamsError::MissingCircuit(name.to_string()))
+    }
+
+    pub fn jwt(&self) -> Result<JwtCircuitParams, ParamsError> {
+        self.expect_template("JWT")?;
+        JwtCircuitParams::from_params(&self.params)
+    }
+
+    pub fn show(&self) -> Result<ShowCircuitParams, ParamsError> {
+        self.expect_template("Show")?;
+        ShowCircuitParams::from_params(&self.params)
+    }
+
+    fn expect_template(&self, template: &'static str) -> Result<(), ParamsError> {
+        if self.template != template {
+            return Err(ParamsError::Template {
+                expected: template,
+                actual: self.template.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl From<JwtCircuitParams> for CircuitParams {
+    fn from(params: JwtCircuitParams) -> Self {
+        Self {
+            template: "JWT".to_string(),
+            params: vec![
+                params.max_message_length,
+                params.max_b64_payload_length,
+                params.max_matches,
+                params.max_substring_length,
+                params.max_claims_length,
+            ],
+        }
+    }
+}
+
+impl From<ShowCircuitParams> for CircuitParams {
+    fn from(params: ShowCircuitParams) -> Self {
+        Self {
+            template: "Show".to_string(),
+            params: vec![params.max_claims_length],
+        }
+    }
+}
+
+impl fmt::Display for CircuitParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params: Vec<String> = self.params.iter().map(ToString::to_string).collect();
+        write!(f, "{}({})", self.template, params.join(", "))
+    }
+}
+
+/// Load the `JWT` parameters of circuit `name` (usually `"jwt"`) from a `circuits.json` file
+pub fn load_jwt_params(path: &Path, name: &str) -> Result<JwtCircuitParams, ParamsError> {
+    CircuitParams::load(path, name)?.jwt()
+}
+
+/// Load the `Show` parameters of circuit `name` (usually `"show"`) from a `circuits.json` file
+pub fn load_show_params(path: &Path, name: &str) -> Result<ShowCircuitParams, ParamsError> {
+    CircuitParams::load(path, name)?.show()
+}
@@ -0,0 +1,108 @@
+//! Wallet-side presentation flow: reblind the cached Prepare proof and prove a fresh Show proof.
+//!
+//! Once the shared blinds are sampled the two halves are independent, so the Prepare reblind and
+//! the Show prove+reblind run concurrently with `rayon::join`. Spartan2 parallelises internally
+//! with rayon as well, so capping the pool caps the whole presentation.
+
+use std::time::Instant;
+
+use spartan2::{
+    r1cs::{R1CSWitness, SplitR1CSInstance},
+    zk_spartan::R1CSSNARK,
+};
+use tracing::info;
+
+use crate::{
+    observer::{NoopObserver, ProofObserver},
+    prover::{prove_and_reblind_with_observer, reblind_proof_with_observer, ProofArtifacts},
+    PrepareCircuit, ProverError, ProverKey, Scalar, ShowCircuit, E,
+};
+
+/// Reblinded Prepare artifacts and Show proof forming one presentation.
+///
+/// The Show witness is dropped as soon as its proof is reblinded; only the Prepare instance and
+/// witness are returned, for callers that refresh their Prepare cache.
+pub struct PresentationArtifacts {
+    pub prepare: ProofArtifacts,
+    pub show_proof: R1CSSNARK<E>,
+}
+
+/// Build a presentation from a proven Prepare instance/witness and the Show inputs.
+///
+/// `prepare_circuit` is the instantiation the Prepare proof was made with: its public values are
+/// absorbed into the reblind transcript, as they were when proving.
+/// `max_threads` bounds the rayon pool used for the whole presentation (e.g. on mobile); `None`
+/// uses the global pool.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_presentation(
+    prepare_pk: &ProverKey,
+    prepare_circuit: PrepareCircuit,
+    prepare_instance: SplitR1CSInstance<E>,
+    prepare_witness: R1CSWitness<E>,
+    show_pk: &ProverKey,
+    show_circuit: ShowCircuit,
+    shared_blinds: &[Scalar],
+    max_threads: Option<usize>,
+) -> Result<PresentationArtifacts, ProverError> {
+    prove_presentation_with_observer(
+        prepare_pk,
+        prepare_circuit,
+        prepare_instance,
+        prepare_witness,
+        show_pk,
+        show_circuit,
+        shared_blinds,
+        max_threads,
+        &NoopObserver,
+    )
+}
+
+/// [`prove_presentation`] reporting the phases of both halves to `observer`
+#[allow(clippy::too_many_arguments)]
+pub fn prove_presentation_with_observer(
+    prepare_pk: &ProverKey,
+    prepare_circuit: PrepareCircuit,
+    prepare_instance: SplitR1CSInstance<E>,
+    prepare_witness: R1CSWitness<E>,
+    show_pk: &ProverKey,
+    show_circuit: ShowCircuit,
+    shared_blinds: &[Scalar],
+    max_threads: Option<usize>,
+    observer: &dyn ProofObserver,
+) -> Result<PresentationArtifacts, ProverError> {
+    let t0 = Instant::now();
+    let run = || {
+        rayon::join(
+            || {
+                reblind_proof_with_observer(
+                    prepare_circuit,
+                    prepare_pk,
+                    prepare_instance,
+                    prepare_witness,
+                    shared_blinds,
+                    observer,
+                )
+            },
+            || prove_and_reblind_with_observer(show_circuit, show_pk, shared_blinds, observer),
+        )
+    };
+
+    let (prepare, show_proof) = match max_threads {
+        Some(n) => rayon::ThreadPoolBuilder::new()
+            .num_threads(n.max(1))
+            .build()
+            .map_err(ProverError::ThreadPool)?
+            .install(run),
+        None => run(),
+    };
+    let presentation_ms = t0.elapsed().as_millis();
+    info!(
+        elapsed_ms = presentation_ms,
+        "ZK-Spartan presentation: {} ms", presentation_ms
+    );
+
+    Ok(PresentationArtifacts {
+        prepare: prepare?,
+        show_proof: show_proof?,
+    })
+}
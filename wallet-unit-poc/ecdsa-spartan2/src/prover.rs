@@ -1,12 +1,25 @@
-use std::{env::current_dir, fs::File, time::Instant};
+use std::{
+    env::current_dir,
+    fs::File,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Instant,
+};
 
 use crate::{
-    circuits::prepare_circuit::jwt_witness,
+    circuits::{
+        prepare_circuit::{jwt_witness, WitnessGenerator},
+        CircomCircuit,
+    },
+    observer::{NoopObserver, PcsOpenHook, ProofObserver, ProofPhase},
+    params::JwtCircuitParams,
+    secret::{SecretBlinds, WitnessKey},
     setup::{
-        load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
-        load_witness, save_instance, save_proof, save_shared_blinds, save_witness,
+        check_key_params, load_instance, load_proof, load_proving_key, load_shared_blinds,
+        load_verifying_key, load_witness, save_instance, save_proof, save_shared_blinds,
+        save_witness,
     },
-    utils::{convert_bigint_to_scalar, parse_jwt_inputs},
+    utils::{convert_bigint_to_scalar, input_error, parse_jwt_inputs},
     Scalar, E,
 };
 
@@ -17,12 +30,13 @@ use spartan2::{
     bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
     errors::SpartanError,
     provider::traits::DlogGroup,
+    r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{
         circuit::SpartanCircuit, snark::R1CSSNARKTrait, transcript::TranscriptEngineTrait, Engine,
     },
     zk_spartan::R1CSSNARK,
 };
-use tracing::info;
+use tracing::{dispatcher, info, Dispatch};
 
 /// Run circuit using ZK-Spartan (setup, prepare, prove, verify)
 pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
@@ -61,8 +75,136 @@ pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
     info!("comm_W_shared: {:?}", proof.comm_W_shared());
 }
 
+pub type ProverKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey;
+
+#[derive(Debug)]
+pub enum ProverError {
+    Spartan(SpartanError),
+    /// The shared blinds do not match the number of shared rows of the instance
+    InvalidBlinds {
+        expected: usize,
+        actual: usize,
+    },
+    /// A [`ProofObserver`] requested cancellation between two phases
+    Cancelled,
+    /// The capped rayon pool for a presentation could not be created
+    ThreadPool(rayon::ThreadPoolBuildError),
+}
+
+impl std::fmt::Display for ProverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProverError::Spartan(e) => write!(f, "{e}"),
+            ProverError::InvalidBlinds { expected, actual } => write!(
+                f,
+                "expected {expected} shared blinds (one per shared row), got {actual}"
+            ),
+            ProverError::Cancelled => write!(f, "cancelled"),
+            ProverError::ThreadPool(e) => write!(f, "failed to build thread pool: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl From<SpartanError> for ProverError {
+    fn from(e: SpartanError) -> Self {
+        ProverError::Spartan(e)
+    }
+}
+
+/// Run one phase of a setup/prove/reblind call, honouring cancellation and reporting progress
+pub(crate) fn run_phase<T>(
+    observer: &dyn ProofObserver,
+    phase: ProofPhase,
+    f: impl FnOnce() -> Result<T, ProverError>,
+) -> Result<T, ProverError> {
+    check_cancelled(observer, phase)?;
+
+    observer.on_phase_start(phase);
+    let t0 = Instant::now();
+    let res = f()?;
+    observer.on_phase_end(phase, t0.elapsed());
+
+    // A cancellation requested while the phase was running discards its result
+    check_cancelled(observer, phase)?;
+    Ok(res)
+}
+
+fn check_cancelled(observer: &dyn ProofObserver, phase: ProofPhase) -> Result<(), ProverError> {
+    if observer.is_cancelled() {
+        info!("ZK-Spartan {} cancelled", phase.name());
+        return Err(ProverError::Cancelled);
+    }
+    Ok(())
+}
+
+/// `prove_inner` reported as [`ProofPhase::Sumcheck`] then [`ProofPhase::PcsOpen`].
+///
+/// Spartan2 runs both in one call, so the boundary is the creation of its `pcs_prove` span, caught
+/// by a [`PcsOpenHook`] and reported to `observer` from a scoped thread while the proof runs.
+/// Cancellation is checked before the sumchecks and after the opening. If the span is never seen
+/// (e.g. tracing compiled out), the opening is reported as starting when the call returns.
+fn prove_inner_with_observer(
+    observer: &dyn ProofObserver,
+    pk: &ProverKey,
+    instance: &SplitR1CSInstance<E>,
+    witness: &R1CSWitness<E>,
+    transcript: &mut <E as Engine>::TE,
+) -> Result<R1CSSNARK<E>, ProverError> {
+    check_cancelled(observer, ProofPhase::Sumcheck)?;
+
+    observer.on_phase_start(ProofPhase::Sumcheck);
+    let t0 = Instant::now();
+    let (opened_tx, opened_rx) = mpsc::channel();
+    let opened_tx = Arc::new(Mutex::new(Some(opened_tx)));
+    let hook = Dispatch::new(PcsOpenHook::new(Arc::clone(&opened_tx)));
+
+    let (proof, pcs_t) = thread::scope(|scope| {
+        let reporter = scope.spawn(move || {
+            opened_rx.recv().ok().map(|()| {
+                observer.on_phase_end(ProofPhase::Sumcheck, t0.elapsed());
+                observer.on_phase_start(ProofPhase::PcsOpen);
+                Instant::now()
+            })
+        });
+        let proof = dispatcher::with_default(&hook, || {
+            R1CSSNARK::<E>::prove_inner(pk, instance, witness, transcript)
+        });
+
+        // Disconnect the reporter if the span was never opened
+        drop(opened_tx.lock().ok().and_then(|mut opened| opened.take()));
+        let pcs_t = reporter.join().unwrap_or(None);
+        (proof, pcs_t)
+    });
+
+    let proof = proof?;
+    let pcs_t = pcs_t.unwrap_or_else(|| {
+        observer.on_phase_end(ProofPhase::Sumcheck, t0.elapsed());
+        observer.on_phase_start(ProofPhase::PcsOpen);
+        Instant::now()
+    });
+    observer.on_phase_end(ProofPhase::PcsOpen, pcs_t.elapsed());
+
+    check_cancelled(observer, ProofPhase::PcsOpen)?;
+    Ok(proof)
+}
+
+/// Instance, witness and proof produced by a prove or reblind step. The witness is only wiped on
+/// drop under [`crate::WipingAllocator`].
+pub struct ProofArtifacts {
+    pub instance: SplitR1CSInstance<E>,
+    pub witness: R1CSWitness<E>,
+    pub proof: R1CSSNARK<E>,
+}
+
+/// Sample fresh shared blinds, one per shared row
+pub fn sample_shared_blinds<E: Engine>(n: usize) -> SecretBlinds<E::Scalar> {
+    SecretBlinds::new((0..n).map(|_| E::Scalar::random(OsRng)).collect())
+}
+
 pub fn generate_shared_blinds<E: Engine>(shared_blinds_path: &str, n: usize) {
-    let blinds: Vec<_> = (0..n).map(|_| E::Scalar::random(OsRng)).collect();
+    let blinds = sample_shared_blinds::<E>(n);
     if let Err(e) = save_shared_blinds::<E>(shared_blinds_path, &blinds) {
         eprintln!("Failed to save instance: {}", e);
         std::process::exit(1);
@@ -70,34 +212,81 @@ pub fn generate_shared_blinds<E: Engine>(shared_blinds_path: &str, n: usize) {
 }
 
 /// Only run the proving part of the circuit using ZK-Spartan (prep_prove, prove)
-pub fn prove_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+/// The witness is only written when `witness_path` is given (the Prepare cache)
+pub fn prove_circuit<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
     circuit: C,
     pk_path: &str,
     instance_path: &str,
-    witness_path: &str,
+    witness_path: Option<&str>,
     proof_path: &str,
+    witness_key: Option<&WitnessKey>,
 ) {
+    ensure_key_params(&circuit, pk_path);
     let t0 = Instant::now();
     let pk = load_proving_key(pk_path).expect("load proving key failed");
     let load_pk_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan load proving key: {} ms", load_pk_ms);
 
-    prove_circuit_with_pk(circuit, &pk, instance_path, witness_path, proof_path);
+    prove_circuit_with_pk(
+        circuit,
+        &pk,
+        instance_path,
+        witness_path,
+        proof_path,
+        witness_key,
+    );
 }
 
-/// Only run the proving part of the circuit using ZK-Spartan with a pre-loaded proving key
-/// This is useful for benchmarking to exclude file I/O from timing measurements
-pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+/// Prove a circuit in memory with a pre-loaded proving key (prep_prove, prove).
+///
+/// Nothing is written to disk; callers decide what to persist from the returned artifacts.
+pub fn generate_proof<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
     circuit: C,
-    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-    instance_path: &str,
-    witness_path: &str,
-    proof_path: &str,
-) {
+    pk: &ProverKey,
+) -> Result<ProofArtifacts, ProverError> {
+    generate_proof_with_observer(circuit, pk, &NoopObserver)
+}
+
+/// [`generate_proof`] reporting each phase to `observer` and stopping early if it cancels.
+///
+/// The circom witness cached on the circuit is wiped once the proof is made.
+pub fn generate_proof_with_observer<
+    C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug,
+>(
+    circuit: C,
+    pk: &ProverKey,
+    observer: &dyn ProofObserver,
+) -> Result<ProofArtifacts, ProverError> {
+    let proven = prove_with_cached_witness(&circuit, pk, observer);
+    circuit.clear_witness();
+    proven
+}
+
+/// Prove `circuit`, leaving its circom witness cached for the caller to clear
+fn prove_with_cached_witness<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
+    circuit: &C,
+    pk: &ProverKey,
+    observer: &dyn ProofObserver,
+) -> Result<ProofArtifacts, ProverError> {
+    // The witness is cached on the circuit (and its clones) for `prep_prove` and the commitment
     let t0 = Instant::now();
-    let mut prep_snark =
-        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove failed");
+    run_phase(observer, ProofPhase::WitnessGeneration, || {
+        circuit.witness().map_err(|e| {
+            ProverError::Spartan(SpartanError::SynthesisError {
+                reason: format!("Witness generation failed: {e}"),
+            })
+        })
+    })?;
+    info!(
+        "ZK-Spartan witness generation: {} ms",
+        t0.elapsed().as_millis()
+    );
+
+    let t0 = Instant::now();
+    let mut prep_snark = run_phase(observer, ProofPhase::PrepProve, || {
+        Ok(R1CSSNARK::<E>::prep_prove(pk, circuit.clone(), false)?)
+    })?;
     let prep_ms = t0.elapsed().as_millis();
     info!("ZK-Spartan prep_prove: {} ms", prep_ms);
 
@@ -105,27 +294,27 @@ pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
     transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
+    let public_values =
+        SpartanCircuit::<E>::public_values(circuit).map_err(|e| SpartanError::SynthesisError {
             reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
+        })?;
 
     // absorb the public values into the transcript
     transcript.absorb(b"public_values", &public_values.as_slice());
 
-    let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
-        &mut prep_snark.ps,
-        &pk.S,
-        &pk.ck,
-        &circuit,
-        false,
-        &mut transcript,
-    )
-    .unwrap();
+    let (instance, witness) = run_phase(observer, ProofPhase::WitnessCommit, || {
+        Ok(SatisfyingAssignment::r1cs_instance_and_witness(
+            &mut prep_snark.ps,
+            &pk.S,
+            &pk.ck,
+            circuit,
+            false,
+            &mut transcript,
+        )?)
+    })?;
 
     // generate a witness and proof
-    let res = R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript).unwrap();
+    let proof = prove_inner_with_observer(observer, pk, &instance, &witness, &mut transcript)?;
     let prove_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan prove: {} ms", prove_ms);
@@ -137,36 +326,47 @@ pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         prep_ms, prove_ms, total_ms
     );
 
-    // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &instance) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
-    }
-
-    // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
-    }
+    Ok(ProofArtifacts {
+        instance,
+        witness,
+        proof,
+    })
+}
 
-    // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
-        eprintln!("Failed to save proof: {}", e);
-        std::process::exit(1);
-    }
+/// Only run the proving part of the circuit using ZK-Spartan with a pre-loaded proving key
+/// This is useful for benchmarking to exclude file I/O from timing measurements
+/// The witness is only written when `witness_path` is given, encrypted when `witness_key` is given
+pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &ProverKey,
+    instance_path: &str,
+    witness_path: Option<&str>,
+    proof_path: &str,
+    witness_key: Option<&WitnessKey>,
+) {
+    let artifacts = generate_proof(circuit, pk).expect("prove failed");
+    save_artifacts(
+        &artifacts,
+        instance_path,
+        witness_path,
+        proof_path,
+        witness_key,
+    );
 }
 
-pub fn reblind<C: SpartanCircuit<E>>(
+pub fn reblind<C: SpartanCircuit<E> + CircomCircuit>(
     circuit: C,
     pk_path: &str,
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
     shared_blinds_path: &str,
+    witness_key: Option<&WitnessKey>,
 ) {
+    ensure_key_params(&circuit, pk_path);
     let pk = load_proving_key(pk_path).expect("load proving key failed");
     let instance = load_instance(instance_path).expect("load instance failed");
-    let witness = load_witness(witness_path).expect("load witness failed");
+    let witness = load_witness(witness_path, witness_key).expect("load witness failed");
     let randomness =
         load_shared_blinds::<E>(shared_blinds_path).expect("load shared_blinds failed");
 
@@ -179,71 +379,199 @@ pub fn reblind<C: SpartanCircuit<E>>(
         instance_path,
         witness_path,
         proof_path,
+        witness_key,
     );
 }
 
-/// Reblind with pre-loaded data - useful for benchmarking to exclude file I/O
-pub fn reblind_with_loaded_data<C: SpartanCircuit<E>>(
+/// Reblind a proven instance and witness in memory and produce a fresh proof.
+///
+/// The reblinded proof keeps the same `comm_W_shared` for every circuit reblinded with the same
+/// `randomness`, which is what links the Prepare and Show proofs of a presentation.
+pub fn reblind_proof<C: SpartanCircuit<E>>(
     circuit: C,
-    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-    instance: spartan2::r1cs::SplitR1CSInstance<E>,
-    witness: spartan2::r1cs::R1CSWitness<E>,
+    pk: &ProverKey,
+    instance: SplitR1CSInstance<E>,
+    witness: R1CSWitness<E>,
     randomness: &[<E as Engine>::Scalar],
-    instance_path: &str,
-    witness_path: &str,
-    proof_path: &str,
-) {
-    assert_eq!(randomness.len(), instance.num_shared_rows());
+) -> Result<ProofArtifacts, ProverError> {
+    reblind_proof_with_observer(circuit, pk, instance, witness, randomness, &NoopObserver)
+}
+
+/// [`reblind_proof`] reporting each phase to `observer` and stopping early if it cancels
+pub fn reblind_proof_with_observer<C: SpartanCircuit<E>>(
+    circuit: C,
+    pk: &ProverKey,
+    instance: SplitR1CSInstance<E>,
+    witness: R1CSWitness<E>,
+    randomness: &[<E as Engine>::Scalar],
+    observer: &dyn ProofObserver,
+) -> Result<ProofArtifacts, ProverError> {
+    if randomness.len() != instance.num_shared_rows() {
+        return Err(ProverError::InvalidBlinds {
+            expected: instance.num_shared_rows(),
+            actual: randomness.len(),
+        });
+    }
 
     // Reblind instance and witness
     let mut reblind_transcript = <E as Engine>::TE::new(b"R1CSSNARK");
     reblind_transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
+    let public_values =
+        SpartanCircuit::<E>::public_values(&circuit).map_err(|e| SpartanError::SynthesisError {
             reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
+        })?;
 
     // absorb the public values into the reblind_transcript
     reblind_transcript.absorb(b"public_values", &public_values.as_slice());
 
-    let (new_instance, new_witness) = SatisfyingAssignment::reblind_r1cs_instance_and_witness(
-        &randomness,
-        instance,
-        witness,
-        &pk.ck,
+    let (new_instance, new_witness) = run_phase(observer, ProofPhase::Reblind, || {
+        Ok(SatisfyingAssignment::reblind_r1cs_instance_and_witness(
+            randomness,
+            instance,
+            witness,
+            &pk.ck,
+            &mut reblind_transcript,
+        )?)
+    })?;
+
+    // generate a witness and proof
+    let proof = prove_inner_with_observer(
+        observer,
+        pk,
+        &new_instance,
+        &new_witness,
         &mut reblind_transcript,
-    )
-    .unwrap();
+    )?;
+
+    Ok(ProofArtifacts {
+        instance: new_instance,
+        witness: new_witness,
+        proof,
+    })
+}
+
+/// Reblind with pre-loaded data - useful for benchmarking to exclude file I/O
+#[allow(clippy::too_many_arguments)]
+pub fn reblind_with_loaded_data<C: SpartanCircuit<E>>(
+    circuit: C,
+    pk: &ProverKey,
+    instance: SplitR1CSInstance<E>,
+    witness: R1CSWitness<E>,
+    randomness: &[<E as Engine>::Scalar],
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+    witness_key: Option<&WitnessKey>,
+) {
+    let artifacts =
+        reblind_proof(circuit, pk, instance, witness, randomness).expect("reblind failed");
 
     println!(
         "new instance: {:?}",
-        new_instance
+        artifacts
+            .instance
             .clone()
             .comm_W_shared
             .map(|v| v.comm.iter().for_each(|v| println!("v: {:?}", v.affine())))
     );
 
-    // generate a witness and proof
-    let res =
-        R1CSSNARK::<E>::prove_inner(&pk, &new_instance, &new_witness, &mut reblind_transcript)
-            .unwrap();
+    save_artifacts(
+        &artifacts,
+        instance_path,
+        Some(witness_path),
+        proof_path,
+        witness_key,
+    );
+}
 
+/// Prove and immediately reblind a circuit in memory, returning only the reblinded proof.
+///
+/// Meant for the Show circuit, which is reproven for every presentation: its witness never needs
+/// to outlive the call, so it is never written to disk.
+pub fn prove_and_reblind<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &ProverKey,
+    randomness: &[<E as Engine>::Scalar],
+) -> Result<R1CSSNARK<E>, ProverError> {
+    prove_and_reblind_with_observer(circuit, pk, randomness, &NoopObserver)
+}
+
+/// [`prove_and_reblind`] reporting each phase to `observer` and stopping early if it cancels
+pub fn prove_and_reblind_with_observer<
+    C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug,
+>(
+    circuit: C,
+    pk: &ProverKey,
+    randomness: &[<E as Engine>::Scalar],
+    observer: &dyn ProofObserver,
+) -> Result<R1CSSNARK<E>, ProverError> {
+    // The reblind transcript absorbs the same public values, from the witness cached for the
+    // proof, which is wiped once both are done
+    let reblinded = prove_with_cached_witness(&circuit, pk, observer).and_then(|proven| {
+        reblind_proof_with_observer(
+            circuit.clone(),
+            pk,
+            proven.instance,
+            proven.witness,
+            randomness,
+            observer,
+        )
+    });
+    circuit.clear_witness();
+    Ok(reblinded?.proof)
+}
+
+/// Prove and reblind a circuit in memory with the stored shared blinds, saving only the proof
+pub fn prove_and_reblind_circuit<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk_path: &str,
+    shared_blinds_path: &str,
+    proof_path: &str,
+) {
+    ensure_key_params(&circuit, pk_path);
+    let pk = load_proving_key(pk_path).expect("load proving key failed");
+    let randomness =
+        load_shared_blinds::<E>(shared_blinds_path).expect("load shared_blinds failed");
+
+    let proof = prove_and_reblind(circuit, &pk, &randomness).expect("prove and reblind failed");
+    if let Err(e) = save_proof(proof_path, &proof) {
+        eprintln!("Failed to save proof: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Exit before proving if `pk_path` was set up for another instantiation of the circuit
+fn ensure_key_params<C: CircomCircuit>(circuit: &C, pk_path: &str) {
+    if let Err(e) = check_key_params(pk_path, &circuit.circuit_params()) {
+        eprintln!("Proving key does not match the circuit: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn save_artifacts(
+    artifacts: &ProofArtifacts,
+    instance_path: &str,
+    witness_path: Option<&str>,
+    proof_path: &str,
+    witness_key: Option<&WitnessKey>,
+) {
     // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &new_instance) {
+    if let Err(e) = save_instance(instance_path, &artifacts.instance) {
         eprintln!("Failed to save instance: {}", e);
         std::process::exit(1);
     }
 
     // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &new_witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
+    if let Some(witness_path) = witness_path {
+        if let Err(e) = save_witness(witness_path, &artifacts.witness, witness_key) {
+            eprintln!("Failed to save witness: {}", e);
+            std::process::exit(1);
+        }
     }
 
     // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
+    if let Err(e) = save_proof(proof_path, &artifacts.proof) {
         eprintln!("Failed to save proof: {}", e);
         std::process::exit(1);
     }
@@ -263,7 +591,7 @@ pub fn verify_circuit_with_loaded_data(
     vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
 ) {
     let t0 = Instant::now();
-    proof.verify(&vk).expect("verify errored");
+    zkid_verifier::verify(proof, vk).expect("verify errored");
     let verify_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = verify_ms, "ZK-Spartan verify");
 
@@ -274,6 +602,7 @@ pub fn verify_circuit_with_loaded_data(
 /// Returns the full witness vector, the decoded age-claim bytes, and the extracted KeyBindingX/Y values.
 pub fn generate_prepare_witness(
     input_json_path: Option<&std::path::Path>,
+    params: &JwtCircuitParams,
 ) -> Result<Vec<Scalar>, SynthesisError> {
     let root = current_dir().unwrap().join("../circom");
 
@@ -288,13 +617,30 @@ pub fn generate_prepare_witness(
     let json_value: Value =
         serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)?;
 
-    // Parse inputs using declarative field definitions
-    let inputs = parse_jwt_inputs(&json_value)?;
+    generate_prepare_witness_from_json(&json_value, params)
+}
+
+/// Generate witness for the Prepare circuit from already-loaded input JSON.
+pub fn generate_prepare_witness_from_json(
+    json_value: &Value,
+    params: &JwtCircuitParams,
+) -> Result<Vec<Scalar>, SynthesisError> {
+    generate_prepare_witness_with(json_value, params, jwt_witness)
+}
+
+/// [`generate_prepare_witness_from_json`] for another compiled variant of the JWT circuit
+pub fn generate_prepare_witness_with(
+    json_value: &Value,
+    params: &JwtCircuitParams,
+    generator: WitnessGenerator,
+) -> Result<Vec<Scalar>, SynthesisError> {
+    // Parse and validate inputs against the circuit parameters
+    let inputs = parse_jwt_inputs(json_value, params).map_err(input_error)?;
 
     // Generate witness using native Rust (rust-witness)
     info!("Generating witness using native Rust (rust-witness)...");
     let t0 = Instant::now();
-    let witness_bigint = jwt_witness(inputs);
+    let witness_bigint = generator(inputs);
     info!("rust-witness time: {} ms", t0.elapsed().as_millis());
 
     let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
@@ -0,0 +1,448 @@
+//! Evaluation of relying-party queries (DCQL and Presentation Exchange) against the wallet's
+//! SD-JWT credentials.
+//!
+//! The circuits prove one thing about one credential: the Prepare circuit opens the birth date
+//! disclosure selected by `ageClaimIndex` and the Show circuit proves the holder is 18 or older.
+//! A query maps onto them when it asks a single SD-JWT VC credential, optionally restricted to
+//! some `vct` values, for `age_over_18` (or `age_equal_or_over.18`) and nothing else. Anything
+//! that would disclose an attribute, or need several credentials, fails with
+//! [`QueryError::Unsupported`].
+//!
+//! Neither circuit reads the credential type: a `vct` restriction only selects the credential
+//! wallet-side, and [`CircuitRequest::unproven_vct_values`] tells the relying party it is not
+//! proven.
+
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use zkid_verifier::Predicate;
+
+use crate::variants::{SdJwtParts, DEVICE_KEY_MATCHES};
+
+/// Disclosure holding the birth date the Show circuit reads, as `YYYMMDD` in the ROC calendar
+pub const BIRTH_DATE_CLAIM: &str = "roc_birthday";
+
+/// Credential formats of an SD-JWT VC, current and legacy
+const SD_JWT_FORMATS: [&str; 2] = ["dc+sd-jwt", "vc+sd-jwt"];
+
+/// DCQL query of an OpenID4VP authorization request
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcqlQuery {
+    pub credentials: Vec<DcqlCredentialQuery>,
+    pub credential_sets: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcqlCredentialQuery {
+    pub id: String,
+    pub format: String,
+    pub meta: Option<DcqlMeta>,
+    pub claims: Option<Vec<DcqlClaimQuery>>,
+    /// Alternative sets of claim ids, in order of preference
+    pub claim_sets: Option<Vec<Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcqlMeta {
+    pub vct_values: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcqlClaimQuery {
+    pub id: Option<String>,
+    pub path: Vec<Value>,
+    pub values: Option<Vec<Value>>,
+}
+
+/// Presentation Exchange presentation definition
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresentationDefinition {
+    pub id: String,
+    pub input_descriptors: Vec<InputDescriptor>,
+    pub submission_requirements: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InputDescriptor {
+    pub id: String,
+    pub format: Option<Map<String, Value>>,
+    #[serde(default)]
+    pub constraints: Constraints,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Constraints {
+    #[serde(default)]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Field {
+    /// JSONPath alternatives
+    pub path: Vec<String>,
+    pub filter: Option<Value>,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// The circuits to run for a query, and the inputs it selects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitRequest {
+    /// Id of the DCQL credential query or Presentation Exchange input descriptor answered
+    pub query_id: String,
+    /// Predicate the Show circuit proves
+    pub predicate: Predicate,
+    /// Index of the satisfying credential in the wallet's list
+    pub credential: usize,
+    /// `ageClaimIndex` of the Prepare inputs: the birth date disclosure, after the device key
+    /// matches
+    pub age_claim_index: usize,
+    /// `vct` values the query restricts the credential to. The wallet selected a credential of one
+    /// of these types, but the proofs say nothing about its type.
+    pub unproven_vct_values: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum QueryError {
+    Json(serde_json::Error),
+    /// The query parses but is malformed, e.g. a claim set naming an unknown claim
+    Invalid(String),
+    /// The query asks for something the circuits cannot prove
+    Unsupported(String),
+    /// The query is supported, but no credential of the wallet satisfies it
+    NoMatchingCredential {
+        query_id: String,
+    },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Json(e) => write!(f, "invalid query JSON: {e}"),
+            QueryError::Invalid(reason) => write!(f, "invalid query: {reason}"),
+            QueryError::Unsupported(reason) => write!(f, "unsupported by the ZK path: {reason}"),
+            QueryError::NoMatchingCredential { query_id } => {
+                write!(f, "no credential satisfies '{query_id}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl DcqlQuery {
+    pub fn from_json(json: &str) -> Result<Self, QueryError> {
+        serde_json::from_str(json).map_err(QueryError::Json)
+    }
+}
+
+impl PresentationDefinition {
+    pub fn from_json(json: &str) -> Result<Self, QueryError> {
+        serde_json::from_str(json).map_err(QueryError::Json)
+    }
+}
+
+/// What a supported query asks of the credential
+struct Requirement<'q> {
+    query_id: &'q str,
+    predicate: Predicate,
+    /// Accepted `vct` values, any when `None`
+    vct_values: Option<Vec<String>>,
+}
+
+/// Map a DCQL query onto the circuits and pick the first of `credentials` (SD-JWTs) satisfying it
+pub fn evaluate_dcql<S: AsRef<str>>(
+    query: &DcqlQuery,
+    credentials: &[S],
+) -> Result<CircuitRequest, QueryError> {
+    if query.credential_sets.is_some() {
+        return Err(unsupported(
+            "credential_sets, a presentation proves a single credential",
+        ));
+    }
+    let [credential_query] = query.credentials.as_slice() else {
+        return Err(unsupported(format!(
+            "{} credential queries, a presentation proves a single credential",
+            query.credentials.len()
+        )));
+    };
+    select(&dcql_requirement(credential_query)?, credentials)
+}
+
+/// Map a Presentation Exchange definition onto the circuits and pick the first of
+/// `credentials` (SD-JWTs) satisfying it
+pub fn evaluate_presentation_definition<S: AsRef<str>>(
+    definition: &PresentationDefinition,
+    credentials: &[S],
+) -> Result<CircuitRequest, QueryError> {
+    if definition.submission_requirements.is_some() {
+        return Err(unsupported(
+            "submission_requirements, a presentation proves a single credential",
+        ));
+    }
+    let [descriptor] = definition.input_descriptors.as_slice() else {
+        return Err(unsupported(format!(
+            "{} input descriptors, a presentation proves a single credential",
+            definition.input_descriptors.len()
+        )));
+    };
+    select(&descriptor_requirement(descriptor)?, credentials)
+}
+
+fn dcql_requirement(query: &DcqlCredentialQuery) -> Result<Requirement<'_>, QueryError> {
+    check_format(&query.id, [query.format.as_str()].into_iter())?;
+    let claims = match &query.claims {
+        Some(claims) if !claims.is_empty() => claims,
+        _ => {
+            return Err(unsupported(format!(
+                "'{}' requests the whole credential rather than a predicate",
+                query.id
+            )))
+        }
+    };
+
+    // Without claim_sets every claim is requested; otherwise the first option we can prove
+    let predicate = match &query.claim_sets {
+        None => claims_predicate(&query.id, claims.iter())?,
+        Some(claim_sets) => {
+            let claim_sets = claim_sets
+                .iter()
+                .map(|ids| claim_set(&query.id, claims, ids))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut reasons = Vec::with_capacity(claim_sets.len());
+            let mut predicate = None;
+            for claim_set in claim_sets {
+                match claims_predicate(&query.id, claim_set.into_iter()) {
+                    Ok(set_predicate) => {
+                        predicate = Some(set_predicate);
+                        break;
+                    }
+                    Err(QueryError::Unsupported(reason)) => reasons.push(reason),
+                    Err(e) => return Err(e),
+                }
+            }
+            predicate.ok_or_else(|| {
+                unsupported(format!(
+                    "no claim set of '{}' is only age_over_18: {}",
+                    query.id,
+                    reasons.join("; ")
+                ))
+            })?
+        }
+    };
+
+    Ok(Requirement {
+        query_id: &query.id,
+        predicate,
+        vct_values: query.meta.as_ref().and_then(|meta| meta.vct_values.clone()),
+    })
+}
+
+/// The claims a claim set names by id
+fn claim_set<'q>(
+    query_id: &str,
+    claims: &'q [DcqlClaimQuery],
+    ids: &[String],
+) -> Result<Vec<&'q DcqlClaimQuery>, QueryError> {
+    if ids.is_empty() {
+        return Err(QueryError::Invalid(format!(
+            "'{query_id}' has an empty claim set"
+        )));
+    }
+    ids.iter()
+        .map(|id| {
+            claims
+                .iter()
+                .find(|claim| claim.id.as_ref() == Some(id))
+                .ok_or_else(|| {
+                    QueryError::Invalid(format!(
+                        "a claim set of '{query_id}' names the unknown claim '{id}'"
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// The predicate proving all of `claims`
+fn claims_predicate<'q>(
+    query_id: &str,
+    claims: impl Iterator<Item = &'q DcqlClaimQuery>,
+) -> Result<Predicate, QueryError> {
+    let mut predicate = None;
+    for claim in claims {
+        let path: Vec<&str> = claim.path.iter().filter_map(Value::as_str).collect();
+        if path.len() != claim.path.len() || claim_predicate(&path).is_none() {
+            return Err(unsupported(format!(
+                "'{query_id}' requests claim {} which would be disclosed",
+                Value::from(claim.path.clone())
+            )));
+        }
+        if claim
+            .values
+            .as_ref()
+            .is_some_and(|values| values != &[Value::Bool(true)])
+        {
+            return Err(unsupported(format!(
+                "'{query_id}' requests a value other than true for {}",
+                path.join(".")
+            )));
+        }
+        predicate = claim_predicate(&path);
+    }
+    predicate.ok_or_else(|| unsupported(format!("'{query_id}' selects no claims")))
+}
+
+fn descriptor_requirement(descriptor: &InputDescriptor) -> Result<Requirement<'_>, QueryError> {
+    if let Some(format) = &descriptor.format {
+        check_format(&descriptor.id, format.keys().map(String::as_str))?;
+    }
+
+    let mut vct_values = None;
+    let mut predicate = None;
+    for field in &descriptor.constraints.fields {
+        let paths: Vec<&str> = field.path.iter().map(String::as_str).collect();
+        if paths.contains(&"$.vct") {
+            vct_values = vct_filter(field.filter.as_ref())
+                .ok_or_else(|| unsupported("a vct filter other than const or enum"))?;
+            continue;
+        }
+        let field_predicate = paths
+            .iter()
+            .find_map(|path| claim_predicate(&json_path(path)?))
+            .filter(|_| field.filter.as_ref().is_none_or(is_true_filter));
+        match field_predicate {
+            Some(field_predicate) => predicate = Some(field_predicate),
+            // The wallet may leave optional fields out
+            None if field.optional => {}
+            None => {
+                return Err(unsupported(format!(
+                    "'{}' requests {} which would be disclosed",
+                    descriptor.id,
+                    paths.join(" or ")
+                )))
+            }
+        }
+    }
+    let predicate = predicate.ok_or_else(|| {
+        unsupported(format!(
+            "'{}' does not request age_over_18, the only predicate the circuits prove",
+            descriptor.id
+        ))
+    })?;
+    Ok(Requirement {
+        query_id: &descriptor.id,
+        predicate,
+        vct_values,
+    })
+}
+
+/// The predicate a claim path asks for, if the circuits can prove it
+fn claim_predicate(path: &[&str]) -> Option<Predicate> {
+    match path {
+        ["age_over_18"] | ["age_equal_or_over", "18"] => Some(Predicate::AgeOver18),
+        _ => None,
+    }
+}
+
+/// Segments of a JSONPath of the form `$.a.b`
+fn json_path(path: &str) -> Option<Vec<&str>> {
+    Some(path.strip_prefix("$.")?.split('.').collect())
+}
+
+fn is_true_filter(filter: &Value) -> bool {
+    filter.get("const") == Some(&Value::Bool(true))
+}
+
+/// Values a `$.vct` filter accepts: `None` for any, `Some(None)` when it cannot be evaluated
+fn vct_filter(filter: Option<&Value>) -> Option<Option<Vec<String>>> {
+    let Some(filter) = filter else {
+        return Some(None);
+    };
+    let values = match (filter.get("const"), filter.get("enum")) {
+        (Some(value), None) => vec![value.as_str()?.to_string()],
+        (None, Some(Value::Array(values))) => values
+            .iter()
+            .map(|value| value.as_str().map(str::to_string))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(Some(values))
+}
+
+fn check_format<'f>(
+    query_id: &str,
+    mut formats: impl Iterator<Item = &'f str> + Clone,
+) -> Result<(), QueryError> {
+    if formats
+        .clone()
+        .any(|format| SD_JWT_FORMATS.contains(&format))
+    {
+        return Ok(());
+    }
+    Err(unsupported(format!(
+        "'{query_id}' asks for {}, the circuits only read SD-JWT VCs",
+        formats.next().unwrap_or("no format")
+    )))
+}
+
+fn unsupported(reason: impl Into<String>) -> QueryError {
+    QueryError::Unsupported(reason.into())
+}
+
+fn select<S: AsRef<str>>(
+    requirement: &Requirement<'_>,
+    credentials: &[S],
+) -> Result<CircuitRequest, QueryError> {
+    credentials
+        .iter()
+        .enumerate()
+        .find_map(|(credential, sd_jwt)| {
+            let age_claim_index = age_claim_index(sd_jwt.as_ref(), requirement)?;
+            Some(CircuitRequest {
+                query_id: requirement.query_id.to_string(),
+                predicate: requirement.predicate,
+                credential,
+                age_claim_index,
+                unproven_vct_values: requirement.vct_values.clone(),
+            })
+        })
+        .ok_or_else(|| QueryError::NoMatchingCredential {
+            query_id: requirement.query_id.to_string(),
+        })
+}
+
+/// `ageClaimIndex` of the birth date disclosure, if the credential satisfies the requirement;
+/// malformed credentials satisfy nothing
+fn age_claim_index(sd_jwt: &str, requirement: &Requirement<'_>) -> Option<usize> {
+    let parts = SdJwtParts::split(sd_jwt).ok()?;
+    if let Some(vct_values) = &requirement.vct_values {
+        let payload = decode_json(parts.payload)?;
+        let vct = payload.get("vct")?.as_str()?;
+        if !vct_values.iter().any(|value| value == vct) {
+            return None;
+        }
+    }
+
+    let position = parts.disclosures.iter().position(|disclosure| {
+        // A disclosure is `[salt, name, value]`
+        let Some(Value::Array(disclosure)) = decode_json(disclosure) else {
+            return false;
+        };
+        match disclosure.as_slice() {
+            [_, Value::String(name), Value::String(date)] => {
+                name == BIRTH_DATE_CLAIM
+                    && date.len() == 7
+                    && date.bytes().all(|byte| byte.is_ascii_digit())
+            }
+            _ => false,
+        }
+    })?;
+    Some(DEVICE_KEY_MATCHES + position)
+}
+
+fn decode_json(segment: &str) -> Option<Value> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
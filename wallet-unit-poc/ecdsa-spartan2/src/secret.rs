@@ -0,0 +1,424 @@
+//! Handling of the secret artifacts kept by a wallet: witnesses and shared blinds.
+//!
+//! A witness holds the whole credential (claims, device key, signatures) and the shared blinds
+//! are what keep presentations unlinkable, so both are wiped from memory on drop and witnesses
+//! can be encrypted at rest with a caller-supplied key.
+//!
+//! The circom witness ([`CircomWitness`]), the encoded Spartan witness ([`SecretWitness`]) and
+//! the blinds wipe themselves. Spartan2 does not expose the internals of `R1CSWitness`, so the
+//! witnesses it allocates while proving, and those in `ProofArtifacts`, are wiped by
+//! [`WipingAllocator`] instead: the CLI and `zkid-ffi` install it as their global allocator, and
+//! wallets embedding this crate should do the same.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    ops::Deref,
+    path::Path,
+    ptr, slice,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use ff::Field;
+use spartan2::r1cs::R1CSWitness;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{Scalar, E};
+
+/// Header of an encrypted witness file: magic, then a 12-byte nonce, then the AEAD ciphertext
+const ENCRYPTED_WITNESS_MAGIC: &[u8; 4] = b"ZKW1";
+const NONCE_LEN: usize = 12;
+
+/// Bytes of zeros written at a time when purging a file, whatever its size
+const PURGE_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum SecretError {
+    Encoding(bincode::Error),
+    /// The witness file is encrypted but no key was supplied
+    KeyRequired,
+    InvalidKey,
+    /// Wrong key, or the ciphertext was modified
+    Decryption,
+    Encryption,
+}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretError::Encoding(e) => write!(f, "witness encoding failed: {e}"),
+            SecretError::KeyRequired => {
+                write!(f, "witness is encrypted, a witness key is required")
+            }
+            SecretError::InvalidKey => write!(f, "witness key must be 32 bytes"),
+            SecretError::Decryption => write!(f, "witness decryption failed"),
+            SecretError::Encryption => write!(f, "witness encryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+impl From<bincode::Error> for SecretError {
+    fn from(e: bincode::Error) -> Self {
+        SecretError::Encoding(e)
+    }
+}
+
+/// Overwrite field elements with zero in a way the compiler cannot elide
+fn zeroize_scalars<F: Field>(values: &mut [F]) {
+    for value in values.iter_mut() {
+        // SAFETY: `value` is a valid, aligned `&mut F` and field elements are plain data
+        unsafe { std::ptr::write_volatile(value, F::ZERO) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Shared blinding scalars, zeroized on drop
+pub struct SecretBlinds<F: Field>(Vec<F>);
+
+impl<F: Field> SecretBlinds<F> {
+    pub fn new(blinds: Vec<F>) -> Self {
+        Self(blinds)
+    }
+}
+
+impl<F: Field> Deref for SecretBlinds<F> {
+    type Target = [F];
+
+    fn deref(&self) -> &[F] {
+        &self.0
+    }
+}
+
+impl<F: Field> Drop for SecretBlinds<F> {
+    fn drop(&mut self) {
+        zeroize_scalars(&mut self.0);
+    }
+}
+
+impl<F: Field> fmt::Debug for SecretBlinds<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBlinds([REDACTED; {}])", self.0.len())
+    }
+}
+
+/// Full circom witness of a circuit (wire 0 is the constant 1), zeroized on drop
+#[derive(Clone)]
+pub struct CircomWitness(Vec<Scalar>);
+
+impl CircomWitness {
+    pub fn new(witness: Vec<Scalar>) -> Self {
+        Self(witness)
+    }
+}
+
+impl Deref for CircomWitness {
+    type Target = [Scalar];
+
+    fn deref(&self) -> &[Scalar] {
+        &self.0
+    }
+}
+
+impl Drop for CircomWitness {
+    fn drop(&mut self) {
+        zeroize_scalars(&mut self.0);
+    }
+}
+
+impl fmt::Debug for CircomWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CircomWitness([REDACTED; {}])", self.0.len())
+    }
+}
+
+/// Global allocator that zeroes every block before freeing it.
+///
+/// Install it with `#[global_allocator] static ALLOCATOR: WipingAllocator = WipingAllocator;` so
+/// that the `R1CSWitness` values Spartan2 allocates, which cannot be reached from here, are wiped
+/// when dropped. Reallocation always moves, so that a grown or shrunk block leaves no copy behind.
+pub struct WipingAllocator;
+
+// SAFETY: every call is forwarded to `System` with the same layout, after wiping blocks it frees
+unsafe impl GlobalAlloc for WipingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        slice::from_raw_parts_mut(ptr, layout.size()).zeroize();
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = System.alloc(new_layout);
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+            self.dealloc(ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+/// 256-bit key encrypting stored witnesses (ChaCha20-Poly1305), zeroized on drop
+pub struct WitnessKey(Zeroizing<[u8; 32]>);
+
+impl WitnessKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(Zeroizing::new(bytes))
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, SecretError> {
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| SecretError::InvalidKey)?;
+        Ok(Self::new(bytes))
+    }
+
+    /// Read a key stored as 32 raw bytes
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let bytes = Zeroizing::new(fs::read(path)?);
+        Ok(Self::from_slice(&bytes)?)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(self.0.as_ref()))
+    }
+}
+
+impl fmt::Debug for WitnessKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WitnessKey([REDACTED])")
+    }
+}
+
+/// Bincode encoding of a witness, zeroized on drop
+pub struct SecretWitness(Zeroizing<Vec<u8>>);
+
+impl SecretWitness {
+    pub fn from_witness(witness: &R1CSWitness<E>) -> Result<Self, SecretError> {
+        Ok(Self(Zeroizing::new(bincode::serialize(witness)?)))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(Zeroizing::new(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Decode the witness for a prove/reblind call. The decoded copy is only wiped on drop under
+    /// [`WipingAllocator`].
+    pub fn to_witness(&self) -> Result<R1CSWitness<E>, SecretError> {
+        Ok(bincode::deserialize(&self.0)?)
+    }
+
+    pub fn encrypt(&self, key: &WitnessKey) -> Result<Vec<u8>, SecretError> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = key
+            .cipher()
+            .encrypt(&nonce, self.0.as_slice())
+            .map_err(|_| SecretError::Encryption)?;
+
+        let mut out =
+            Vec::with_capacity(ENCRYPTED_WITNESS_MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(ENCRYPTED_WITNESS_MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    pub fn decrypt(bytes: &[u8], key: &WitnessKey) -> Result<Self, SecretError> {
+        let body = bytes
+            .strip_prefix(ENCRYPTED_WITNESS_MAGIC.as_slice())
+            .ok_or(SecretError::Decryption)?;
+        if body.len() < NONCE_LEN {
+            return Err(SecretError::Decryption);
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        let plaintext = key
+            .cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretError::Decryption)?;
+        Ok(Self::from_bytes(plaintext))
+    }
+
+    /// Decode a stored witness, decrypting it when it was written with a key
+    pub fn from_stored(bytes: Vec<u8>, key: Option<&WitnessKey>) -> Result<Self, SecretError> {
+        if is_encrypted(&bytes) {
+            let key = key.ok_or(SecretError::KeyRequired)?;
+            Self::decrypt(&bytes, key)
+        } else {
+            Ok(Self::from_bytes(bytes))
+        }
+    }
+
+    /// Bytes to store: encrypted with `key` when given, plaintext bincode otherwise
+    pub fn to_stored(&self, key: Option<&WitnessKey>) -> Result<Zeroizing<Vec<u8>>, SecretError> {
+        match key {
+            Some(key) => Ok(Zeroizing::new(self.encrypt(key)?)),
+            None => Ok(self.0.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for SecretWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretWitness([REDACTED; {} bytes])", self.0.len())
+    }
+}
+
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(ENCRYPTED_WITNESS_MAGIC)
+}
+
+/// Overwrite a file with zeros, flush it to disk and delete it; missing files are skipped.
+///
+/// Returns whether the file existed. On SSDs and copy-on-write filesystems the old blocks may
+/// survive the overwrite, which is why witnesses should also be encrypted at rest.
+pub fn purge_file(path: &str) -> std::io::Result<bool> {
+    if !Path::new(path).exists() {
+        return Ok(false);
+    }
+
+    let mut remaining = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; PURGE_CHUNK];
+    while remaining > 0 {
+        let chunk = remaining.min(PURGE_CHUNK as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn witness() -> SecretWitness {
+        SecretWitness::from_bytes(b"credential witness".to_vec())
+    }
+
+    #[test]
+    fn encrypted_witness_round_trips() {
+        let key = WitnessKey::new([7; 32]);
+        let encrypted = witness().encrypt(&key).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted
+            .windows(witness().as_bytes().len())
+            .any(|window| window == witness().as_bytes()));
+
+        let decrypted = SecretWitness::decrypt(&encrypted, &key).unwrap();
+        assert_eq!(decrypted.as_bytes(), witness().as_bytes());
+
+        // Stored bytes are only encrypted when a key is given
+        let stored = witness().to_stored(Some(&key)).unwrap();
+        let restored = SecretWitness::from_stored(stored.to_vec(), Some(&key)).unwrap();
+        assert_eq!(restored.as_bytes(), witness().as_bytes());
+        let plain = witness().to_stored(None).unwrap();
+        assert_eq!(plain.as_slice(), witness().as_bytes());
+        let restored = SecretWitness::from_stored(plain.to_vec(), None).unwrap();
+        assert_eq!(restored.as_bytes(), witness().as_bytes());
+    }
+
+    #[test]
+    fn wrong_key_or_altered_ciphertext_fails_to_decrypt() {
+        let encrypted = witness().encrypt(&WitnessKey::new([7; 32])).unwrap();
+        let key = WitnessKey::new([7; 32]);
+
+        let res = SecretWitness::decrypt(&encrypted, &WitnessKey::new([8; 32]));
+        assert!(matches!(res, Err(SecretError::Decryption)));
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        let res = SecretWitness::decrypt(&tampered, &key);
+        assert!(matches!(res, Err(SecretError::Decryption)));
+
+        for truncated in [&encrypted[..4], &encrypted[..10], &[][..]] {
+            let res = SecretWitness::decrypt(truncated, &key);
+            assert!(matches!(res, Err(SecretError::Decryption)));
+        }
+    }
+
+    #[test]
+    fn encrypted_witness_requires_a_key() {
+        let encrypted = witness().encrypt(&WitnessKey::new([7; 32])).unwrap();
+        let res = SecretWitness::from_stored(encrypted, None);
+        assert!(matches!(res, Err(SecretError::KeyRequired)));
+    }
+
+    #[test]
+    fn key_must_be_32_bytes() {
+        assert!(matches!(
+            WitnessKey::from_slice(&[0; 31]),
+            Err(SecretError::InvalidKey)
+        ));
+        assert!(WitnessKey::from_slice(&[0; 32]).is_ok());
+    }
+
+    #[test]
+    fn secrets_are_redacted_in_debug_output() {
+        assert_eq!(
+            format!("{:?}", WitnessKey::new([0xab; 32])),
+            "WitnessKey([REDACTED])"
+        );
+        assert_eq!(
+            format!("{:?}", witness()),
+            "SecretWitness([REDACTED; 18 bytes])"
+        );
+        let blinds = SecretBlinds::new(vec![crate::Scalar::from(5); 3]);
+        assert_eq!(format!("{blinds:?}"), "SecretBlinds([REDACTED; 3])");
+        assert_eq!(blinds.len(), 3);
+        let witness = CircomWitness::new(vec![crate::Scalar::from(5); 2]);
+        assert_eq!(format!("{witness:?}"), "CircomWitness([REDACTED; 2])");
+        assert_eq!(witness[1], crate::Scalar::from(5));
+    }
+
+    #[test]
+    fn wiping_allocator_keeps_contents_across_reallocation() {
+        let allocator = WipingAllocator;
+        let layout = Layout::array::<u8>(4).unwrap();
+        unsafe {
+            let ptr = allocator.alloc(layout);
+            ptr::copy_nonoverlapping([1u8, 2, 3, 4].as_ptr(), ptr, 4);
+            let grown = allocator.realloc(ptr, layout, 64);
+            assert_eq!(slice::from_raw_parts(grown, 4), [1, 2, 3, 4]);
+            let shrunk = allocator.realloc(grown, Layout::array::<u8>(64).unwrap(), 2);
+            assert_eq!(slice::from_raw_parts(shrunk, 2), [1, 2]);
+            allocator.dealloc(shrunk, Layout::array::<u8>(2).unwrap());
+        }
+    }
+
+    #[test]
+    fn purge_removes_the_file_once() {
+        let path = std::env::temp_dir().join(format!("zkid-purge-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, b"shared blinds").unwrap();
+
+        assert!(purge_file(path).unwrap());
+        assert!(!Path::new(path).exists());
+        assert!(!purge_file(path).unwrap());
+
+        // Larger than one chunk, and not a multiple of it
+        fs::write(path, vec![0xff; 2 * PURGE_CHUNK + 7]).unwrap();
+        assert!(purge_file(path).unwrap());
+        assert!(!Path::new(path).exists());
+        assert!(!purge_file(path).unwrap());
+    }
+}
@@ -1,18 +1,33 @@
 use std::{
-    fs::{create_dir_all, File},
+    fs::{self, create_dir_all, rename, File},
     io::{BufReader, Cursor, Write},
     time::Instant,
 };
 
 use spartan2::{
+    errors::SpartanError,
     r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait, Engine},
     zk_spartan::R1CSSNARK,
 };
 use tracing::info;
 
-use crate::E;
+use crate::{
+    circuits::CircomCircuit,
+    observer::{NoopObserver, ProofObserver, ProofPhase},
+    params::CircuitParams,
+    prover::{run_phase, ProverError},
+    secret::{purge_file, SecretBlinds, SecretWitness, WitnessKey},
+    E,
+};
 use memmap2::MmapOptions;
+use zeroize::Zeroizing;
+
+// Verifier-side loaders live in the `zkid-verifier` crate so relying parties can use them alone
+use zkid_verifier::{limits::deserialize_from_with_limit, DeserializeLimits};
+pub use zkid_verifier::{
+    load_proof, load_proof_with_limits, load_verifying_key, load_verifying_key_with_limits,
+};
 
 pub const PREPARE_PROVING_KEY: &str = "keys/prepare_proving.key";
 pub const PREPARE_VERIFYING_KEY: &str = "keys/prepare_verifying.key";
@@ -25,6 +40,34 @@ pub const SHOW_PROOF: &str = "keys/show_proof.bin";
 pub const SHOW_WITNESS: &str = "keys/show_witness.bin";
 pub const SHOW_INSTANCE: &str = "keys/show_instance.bin";
 pub const SHARED_BLINDS: &str = "keys/shared_blinds.bin";
+/// Binary presentation written by `presentation decode`
+pub const PRESENTATION: &str = "keys/presentation.bin";
+/// JSON envelope written by `presentation encode`
+pub const PRESENTATION_ENVELOPE: &str = "keys/presentation.json";
+/// CBOR bundle written by `presentation encode --cbor`
+pub const PRESENTATION_BUNDLE: &str = "keys/presentation.cbor";
+/// zstd-compressed CBOR bundle written by `presentation encode --cbor --zstd`
+pub const PRESENTATION_BUNDLE_ZSTD: &str = "keys/presentation.cbor.zst";
+
+/// Artifacts holding private data, removed by `keys purge`
+pub const SECRET_ARTIFACTS: [&str; 3] = [PREPARE_WITNESS, SHOW_WITNESS, SHARED_BLINDS];
+
+/// Write `bytes` to a temporary sibling file and rename it into place, so that an interrupted or
+/// cancelled run never leaves a truncated artifact at `path`
+pub(crate) fn write_atomically(path: &str, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp_path = format!("{path}.tmp");
+    let res = File::create(&tmp_path)
+        .and_then(|mut tmp_file| {
+            tmp_file.write_all(bytes)?;
+            tmp_file.sync_all()
+        })
+        .and_then(|()| rename(&tmp_path, path));
+    if res.is_err() {
+        // Best effort: the write already failed, a leftover temporary file is only clutter
+        let _ = fs::remove_file(&tmp_path);
+    }
+    res
+}
 
 pub fn save_keys(
     pk_path: &str,
@@ -40,14 +83,12 @@ pub fn save_keys(
     }
 
     let pk_bytes = bincode::serialize(pk)?;
-    let mut pk_file = File::create(pk_path)?;
-    pk_file.write_all(&pk_bytes)?;
+    write_atomically(pk_path, &pk_bytes)?;
 
     info!("Saved ZK-Spartan proving key to: {}", pk_path);
 
     let vk_bytes = bincode::serialize(vk)?;
-    let mut vk_file = File::create(vk_path)?;
-    vk_file.write_all(&vk_bytes)?;
+    write_atomically(vk_path, &vk_bytes)?;
     info!("Saved ZK-Spartan verifying key to: {}", vk_path);
 
     Ok(())
@@ -86,16 +127,6 @@ pub fn load_proving_key(
     Ok(pk)
 }
 
-pub fn load_verifying_key(
-    vk_path: &str,
-) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey, Box<dyn std::error::Error>> {
-    let vk_file = File::open(vk_path)?;
-    let vk_mmap = unsafe { MmapOptions::new().map(&vk_file)? };
-    let vk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey =
-        bincode::deserialize_from(Cursor::new(&vk_mmap[..]))?;
-    Ok(vk)
-}
-
 pub fn save_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
     shared_blinds: &[E::Scalar],
@@ -104,9 +135,8 @@ pub fn save_shared_blinds<E: Engine>(
         create_dir_all(parent)?;
     }
 
-    let shared_blinds_bytes = bincode::serialize(shared_blinds)?;
-    let mut shared_blinds_file = File::create(shared_blinds_path)?;
-    shared_blinds_file.write_all(&shared_blinds_bytes)?;
+    let shared_blinds_bytes = Zeroizing::new(bincode::serialize(shared_blinds)?);
+    write_atomically(shared_blinds_path, &shared_blinds_bytes)?;
     info!("Saved ZK-Spartan shared_blinds to: {}", shared_blinds_path);
 
     Ok(())
@@ -121,8 +151,7 @@ pub fn save_proof(
     }
 
     let proof_bytes = bincode::serialize(proof)?;
-    let mut proof_file = File::create(proof_path)?;
-    proof_file.write_all(&proof_bytes)?;
+    write_atomically(proof_path, &proof_bytes)?;
     info!("Saved ZK-Spartan proof to: {}", proof_path);
 
     Ok(())
@@ -137,35 +166,37 @@ pub fn save_instance(
     }
 
     let instance_bytes = bincode::serialize(instance)?;
-    let mut instance_file = File::create(instance_path)?;
-    instance_file.write_all(&instance_bytes)?;
+    write_atomically(instance_path, &instance_bytes)?;
     info!("Saved ZK-Spartan instance to: {}", instance_path);
 
     Ok(())
 }
 
+/// Save a witness, encrypted with `key` when given
 pub fn save_witness(
     witness_path: &str,
     witness: &R1CSWitness<E>,
+    key: Option<&WitnessKey>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(parent) = std::path::Path::new(witness_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let witness_bytes = bincode::serialize(witness)?;
-    let mut witness_file = File::create(witness_path)?;
-    witness_file.write_all(&witness_bytes)?;
-    info!("Saved ZK-Spartan witness to: {}", witness_path);
+    let witness_bytes = SecretWitness::from_witness(witness)?.to_stored(key)?;
+    write_atomically(witness_path, &witness_bytes)?;
+    info!(
+        encrypted = key.is_some(),
+        "Saved ZK-Spartan witness to: {}", witness_path
+    );
 
     Ok(())
 }
 
 pub fn load_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
-) -> Result<Vec<E::Scalar>, Box<dyn std::error::Error>> {
-    let shared_blinds_file = File::open(shared_blinds_path)?;
-    let shared_blinds: Vec<E::Scalar> =
-        bincode::deserialize_from(&mut BufReader::new(shared_blinds_file))?;
+) -> Result<SecretBlinds<E::Scalar>, Box<dyn std::error::Error>> {
+    let shared_blinds_bytes = Zeroizing::new(fs::read(shared_blinds_path)?);
+    let shared_blinds = SecretBlinds::new(bincode::deserialize(&shared_blinds_bytes)?);
     info!(
         "Loaded ZK-Spartan shared_blinds from: {}",
         shared_blinds_path
@@ -173,37 +204,110 @@ pub fn load_shared_blinds<E: Engine>(
     Ok(shared_blinds)
 }
 
-pub fn load_proof(proof_path: &str) -> Result<R1CSSNARK<E>, Box<dyn std::error::Error>> {
-    let proof_file = File::open(proof_path)?;
-    let proof: R1CSSNARK<E> = bincode::deserialize_from(&mut BufReader::new(proof_file))?;
-    info!("Loaded ZK-Spartan proof from: {}", proof_path);
-    Ok(proof)
+pub fn load_instance(
+    instance_path: &str,
+) -> Result<SplitR1CSInstance<E>, Box<dyn std::error::Error>> {
+    load_instance_with_limits(instance_path, &DeserializeLimits::default())
 }
 
-pub fn load_instance(
+/// Load an instance, failing once more than `limits.instance` bytes have been read
+pub fn load_instance_with_limits(
     instance_path: &str,
+    limits: &DeserializeLimits,
 ) -> Result<SplitR1CSInstance<E>, Box<dyn std::error::Error>> {
     let instance_file = File::open(instance_path)?;
     let instance: SplitR1CSInstance<E> =
-        bincode::deserialize_from(&mut BufReader::new(instance_file))?;
+        deserialize_from_with_limit(BufReader::new(instance_file), "instance", limits.instance)?;
     info!("Loaded ZK-Spartan instance from: {}", instance_path);
     Ok(instance)
 }
 
-pub fn load_witness(witness_path: &str) -> Result<R1CSWitness<E>, Box<dyn std::error::Error>> {
-    let witness_file = File::open(witness_path)?;
-    let witness: R1CSWitness<E> = bincode::deserialize_from(&mut BufReader::new(witness_file))?;
+/// Load a witness; encrypted witnesses require the `key` they were saved with
+pub fn load_witness(
+    witness_path: &str,
+    key: Option<&WitnessKey>,
+) -> Result<R1CSWitness<E>, Box<dyn std::error::Error>> {
+    let witness = SecretWitness::from_stored(fs::read(witness_path)?, key)?.to_witness()?;
     info!("Loaded ZK-Spartan witness from: {}", witness_path);
     Ok(witness)
 }
 
-pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+/// Overwrite and delete every stored witness and the shared blinds
+pub fn purge_secrets() -> Result<(), Box<dyn std::error::Error>> {
+    for path in SECRET_ARTIFACTS {
+        for path in [path.to_string(), format!("{path}.tmp")] {
+            if purge_file(&path)? {
+                info!("Purged {}", path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Circuit parameters recorded next to a key: `<key_path>.params.json`
+pub fn key_params_path(key_path: &str) -> String {
+    format!("{key_path}.params.json")
+}
+
+pub fn save_key_params(
+    key_path: &str,
+    params: &CircuitParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = key_params_path(key_path);
+    write_atomically(&path, &serde_json::to_vec_pretty(params)?)?;
+    info!("Saved circuit parameters {} to: {}", params, path);
+    Ok(())
+}
+
+/// Parameters recorded for a key, `None` for keys set up before they were recorded
+pub fn load_key_params(
+    key_path: &str,
+) -> Result<Option<CircuitParams>, Box<dyn std::error::Error>> {
+    let path = key_params_path(key_path);
+    if !std::path::Path::new(&path).exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&fs::read(&path)?)?))
+}
+
+/// Fail if the key was set up for a different instantiation than `expected`
+pub fn check_key_params(
+    key_path: &str,
+    expected: &CircuitParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match load_key_params(key_path)? {
+        Some(params) if params != *expected => {
+            Err(format!("{key_path} was set up for {params}, the circuit is {expected}").into())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn setup_circuit_keys<C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug>(
     circuit: C,
     pk_path: &str,
     vk_path: &str,
 ) {
+    if let Err(e) = setup_circuit_keys_with_observer(circuit, pk_path, vk_path, &NoopObserver) {
+        eprintln!("Setup failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// [`setup_circuit_keys`] reporting progress to `observer`.
+///
+/// Nothing is written if it cancels, in which case the error is [`ProverError::Cancelled`].
+pub fn setup_circuit_keys_with_observer<
+    C: SpartanCircuit<E> + CircomCircuit + Clone + std::fmt::Debug,
+>(
+    circuit: C,
+    pk_path: &str,
+    vk_path: &str,
+    observer: &dyn ProofObserver,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let params = circuit.circuit_params();
     let t0 = Instant::now();
-    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed");
+    let (pk, vk) = try_setup_circuit_keys_with_observer(circuit, observer)?;
     let setup_ms = t0.elapsed().as_millis();
     info!(
         elapsed_ms = setup_ms,
@@ -211,14 +315,15 @@ pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         setup_ms as f64 / 1000.0
     );
 
-    if let Err(e) = save_keys(pk_path, vk_path, &pk, &vk) {
-        eprintln!("Failed to save keys: {}", e);
-        std::process::exit(1);
+    save_keys(pk_path, vk_path, &pk, &vk)?;
+    for key_path in [pk_path, vk_path] {
+        save_key_params(key_path, &params)?;
     }
 
     info!("Keys generated and saved successfully!");
     info!("Proving key: {}", pk_path);
     info!("Verifying key: {}", vk_path);
+    Ok(())
 }
 
 /// Setup circuit keys without saving to file - useful for benchmarking
@@ -229,5 +334,56 @@ pub fn setup_circuit_keys_no_save<C: SpartanCircuit<E> + Clone + std::fmt::Debug
     <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
     <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
 ) {
-    R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed")
+    try_setup_circuit_keys(circuit).expect("setup failed")
+}
+
+/// Setup circuit keys without saving to file, reporting failures to the caller instead of panicking
+pub fn try_setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+) -> Result<
+    (
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    ),
+    SpartanError,
+> {
+    R1CSSNARK::<E>::setup(circuit)
+}
+
+/// [`try_setup_circuit_keys`] reporting progress to `observer` and honouring its cancellation
+pub fn try_setup_circuit_keys_with_observer<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    observer: &dyn ProofObserver,
+) -> Result<
+    (
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    ),
+    ProverError,
+> {
+    run_phase(observer, ProofPhase::Setup, || {
+        Ok(try_setup_circuit_keys(circuit)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_atomic_write_leaves_no_temporary_file() {
+        let dir = std::env::temp_dir().join(format!("zkid-write-{}", std::process::id()));
+        // Renaming a file over a non-empty directory fails after the temporary file is written
+        let target = dir.join("artifact.bin");
+        create_dir_all(target.join("occupied")).unwrap();
+        let target = target.to_str().unwrap();
+
+        assert!(write_atomically(target, b"bytes").is_err());
+        assert!(!std::path::Path::new(&format!("{target}.tmp")).exists());
+
+        let file = dir.join("file.bin");
+        write_atomically(file.to_str().unwrap(), b"bytes").unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"bytes");
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
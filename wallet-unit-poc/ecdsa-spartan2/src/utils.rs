@@ -3,97 +3,38 @@ use base64::Engine;
 use bellpepper_core::SynthesisError;
 use rust_witness::BigInt;
 use serde_json::Value;
-use std::{collections::HashMap, ops::Range, str::FromStr};
+use std::{collections::HashMap, ops::Range};
 
-use crate::Scalar;
+use tracing::error;
 
-#[derive(Clone, Copy)]
-pub enum FieldParser {
-    BigIntScalar,
-    U64Scalar,
-    BigIntArray,
-    U64Array,
-    BigInt2DArray,
-}
-
-/// Generic function to parse input fields from JSON based on field definitions
-pub fn parse_inputs(
-    json_value: &Value,
-    field_defs: &[(&str, FieldParser)],
-) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
-    let mut inputs = HashMap::new();
-
-    for (field_name, parser) in field_defs {
-        let value = match parser {
-            FieldParser::BigIntScalar => {
-                vec![parse_bigint_scalar(json_value, field_name)
-                    .map_err(|_| SynthesisError::AssignmentMissing)?]
-            }
-            FieldParser::U64Scalar => {
-                vec![parse_u64_scalar(json_value, field_name)
-                    .map_err(|_| SynthesisError::AssignmentMissing)?]
-            }
-            FieldParser::BigIntArray => parse_bigint_string_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
-            FieldParser::U64Array => parse_u64_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
-            FieldParser::BigInt2DArray => parse_2d_bigint_array(json_value, field_name)
-                .map_err(|_| SynthesisError::AssignmentMissing)?,
-        };
-        inputs.insert(field_name.to_string(), value);
-    }
-
-    Ok(inputs)
-}
+use crate::{
+    inputs::{InputError, JwtInputs, ShowInputs},
+    params::{JwtCircuitParams, ShowCircuitParams},
+    Scalar,
+};
 
 // Circuit-specific input parsers
-/// Parse JWT circuit inputs from JSON
+/// Parse JWT circuit inputs from JSON, validated against the circuit parameters
 pub fn parse_jwt_inputs(
     json_value: &Value,
-) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
-    let field_defs: &[(&str, FieldParser)] = &[
-        // BigInt scalar fields (wrapped in vec)
-        ("sig_r", FieldParser::BigIntScalar),
-        ("sig_s_inverse", FieldParser::BigIntScalar),
-        ("pubKeyX", FieldParser::BigIntScalar),
-        ("pubKeyY", FieldParser::BigIntScalar),
-        // U64 scalar fields (wrapped in vec)
-        ("messageLength", FieldParser::U64Scalar),
-        ("periodIndex", FieldParser::U64Scalar),
-        ("matchesCount", FieldParser::U64Scalar),
-        // Array fields
-        ("message", FieldParser::BigIntArray),
-        ("matchIndex", FieldParser::U64Array),
-        ("matchLength", FieldParser::U64Array),
-        ("claimLengths", FieldParser::BigIntArray),
-        ("decodeFlags", FieldParser::U64Array),
-        // 2D array fields (flattened)
-        ("matchSubstring", FieldParser::BigInt2DArray),
-        ("claims", FieldParser::BigInt2DArray),
-        ("ageClaimIndex", FieldParser::U64Scalar),
-    ];
-
-    parse_inputs(json_value, field_defs)
+    params: &JwtCircuitParams,
+) -> Result<HashMap<String, Vec<BigInt>>, InputError> {
+    JwtInputs::from_json(json_value, params).map(|inputs| inputs.to_witness_inputs())
 }
 
-/// Parse Show circuit inputs from JSON
+/// Parse Show circuit inputs from JSON, validated against the circuit parameters
 pub fn parse_show_inputs(
     json_value: &Value,
-) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
-    let field_defs: &[(&str, FieldParser)] = &[
-        // BigInt scalar fields (wrapped in vec)
-        ("deviceKeyX", FieldParser::BigIntScalar),
-        ("deviceKeyY", FieldParser::BigIntScalar),
-        ("sig_r", FieldParser::BigIntScalar),
-        ("sig_s_inverse", FieldParser::BigIntScalar),
-        ("messageHash", FieldParser::BigIntScalar),
-        ("claim", FieldParser::BigIntArray),
-        ("currentYear", FieldParser::BigIntScalar),
-        ("currentMonth", FieldParser::BigIntScalar),
-        ("currentDay", FieldParser::BigIntScalar),
-    ];
-
-    parse_inputs(json_value, field_defs)
+    params: &ShowCircuitParams,
+) -> Result<HashMap<String, Vec<BigInt>>, InputError> {
+    ShowInputs::from_json(json_value, params).map(|inputs| inputs.to_witness_inputs())
+}
+
+/// Converts an input error where it crosses into the `SpartanCircuit` / `CircomCircuit` methods:
+/// `SynthesisError` has no room for a message, so the field and reason are logged here
+pub(crate) fn input_error(e: InputError) -> SynthesisError {
+    error!("{e}");
+    SynthesisError::AssignmentMissing
 }
 
 /// Convert a single BigInt to Scalar
@@ -119,7 +60,7 @@ pub fn convert_bigint_to_scalar(
     bigint_witness.into_iter().map(bigint_to_scalar).collect()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PrepareSharedScalars {
     pub keybinding_x: Scalar,
     pub keybinding_y: Scalar,
@@ -128,6 +69,7 @@ pub struct PrepareSharedScalars {
 
 pub fn compute_prepare_shared_scalars(
     root_json: &Value,
+    params: &JwtCircuitParams,
 ) -> Result<PrepareSharedScalars, SynthesisError> {
     let message_length = root_json
         .get("messageLength")
@@ -163,12 +105,13 @@ pub fn compute_prepare_shared_scalars(
     let payload_json: Value =
         serde_json::from_slice(&payload_bytes).map_err(|_| SynthesisError::AssignmentMissing)?;
 
-    extract_prepare_shared_data(&payload_json, root_json)
+    extract_prepare_shared_data(&payload_json, root_json, params)
 }
 
 pub fn extract_prepare_shared_data(
     payload_json: &Value,
     root_json: &Value,
+    params: &JwtCircuitParams,
 ) -> Result<PrepareSharedScalars, SynthesisError> {
     let jwk = payload_json
         .get("cnf")
@@ -208,8 +151,7 @@ pub fn extract_prepare_shared_data(
         .map(parse_byte)
         .collect::<Result<Vec<_>, _>>()?;
 
-    let max_claim_length = claim_values.len();
-    if max_claim_length == 0 {
+    if claim_values.len() != params.max_claims_length {
         return Err(SynthesisError::AssignmentMissing);
     }
 
@@ -241,7 +183,7 @@ pub fn extract_prepare_shared_data(
         .map_err(|_| SynthesisError::AssignmentMissing)?;
 
     let decoded_claim_bytes = decode_base64(&encoded_claim)?;
-    let decoded_len = (max_claim_length * 3) / 4;
+    let decoded_len = params.decoded_claim_length();
 
     if decoded_claim_bytes.len() > decoded_len {
         return Err(SynthesisError::AssignmentMissing);
@@ -315,86 +257,6 @@ pub fn decode_base64(encoded: &str) -> Result<Vec<u8>, SynthesisError> {
     Err(SynthesisError::AssignmentMissing)
 }
 
-// JSON Parsing Helpers
-/// Parse a single BigInt from a string field
-fn parse_bigint_scalar(json: &Value, key: &str) -> Result<BigInt, String> {
-    let s = json
-        .get(key)
-        .and_then(|v| v.as_str())
-        .ok_or("Field must be a string")?;
-    BigInt::from_str(s).map_err(|_| "Failed to parse as BigInt".to_string())
-}
-
-/// Parse a single u64 from a number field and convert to BigInt
-fn parse_u64_scalar(json: &Value, key: &str) -> Result<BigInt, String> {
-    json.get(key)
-        .and_then(|v| v.as_u64())
-        .map(BigInt::from)
-        .ok_or("Field must be a number".to_string())
-}
-
-/// Parse an array of BigInt strings
-fn parse_bigint_string_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
-    let array = json
-        .get(key)
-        .and_then(|v| v.as_array())
-        .ok_or("Field must be an array")?;
-
-    array
-        .iter()
-        .map(|v| {
-            let s = v.as_str().ok_or("Array element must be a string")?;
-            BigInt::from_str(s).map_err(|_| "Failed to parse array element as BigInt".to_string())
-        })
-        .collect()
-}
-
-/// Parse an array of u64 numbers and convert to BigInt
-fn parse_u64_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
-    json.get(key)
-        .and_then(|v| v.as_array())
-        .ok_or("Field must be an array")?
-        .iter()
-        .map(|v| {
-            v.as_u64()
-                .map(BigInt::from)
-                .ok_or("Array element must be a number".to_string())
-        })
-        .collect()
-}
-
-/// Parse a 2D array of BigInt strings and flatten into 1D vector
-fn parse_2d_bigint_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String> {
-    let outer_array = json
-        .get(key)
-        .and_then(|v| v.as_array())
-        .ok_or("Field must be an array")?;
-
-    // Pre-calculate total capacity
-    let total_capacity: usize = outer_array
-        .iter()
-        .filter_map(|v| v.as_array())
-        .map(|arr| arr.len())
-        .sum();
-
-    let mut result = Vec::with_capacity(total_capacity);
-
-    for inner_value in outer_array.iter() {
-        let inner_array = inner_value
-            .as_array()
-            .ok_or("Outer array element must be an array")?;
-
-        for v in inner_array.iter() {
-            let s = v.as_str().ok_or("Inner array element must be a string")?;
-            let bigint =
-                BigInt::from_str(s).map_err(|_| "Failed to parse inner array element as BigInt")?;
-            result.push(bigint);
-        }
-    }
-
-    Ok(result)
-}
-
 fn bytes_to_bigint(bytes: &[u8]) -> BigInt {
     let mut acc = BigInt::from(0u8);
     for &byte in bytes {
@@ -424,13 +286,8 @@ impl JwtOutputLayout {
 /// 1. `ageClaim[decodedLen]` where `decodedLen = (maxClaimsLength * 3) / 4`
 /// 2. `KeyBindingX`
 /// 3. `KeyBindingY`
-///
-/// Parameters: `[maxMessageLength, maxB64PayloadLength, maxMatches, maxSubstringLength, maxClaimsLength]`
-pub fn calculate_jwt_output_indices(
-    _max_matches: usize,
-    max_claims_length: usize,
-) -> JwtOutputLayout {
-    let decoded_len = (max_claims_length * 3) / 4;
+pub fn calculate_jwt_output_indices(params: &JwtCircuitParams) -> JwtOutputLayout {
+    let decoded_len = params.decoded_claim_length();
     let age_claim_start = 1; // Index 0 is reserved for the constant signal in Circom witness
     let keybinding_x_index = age_claim_start + decoded_len;
     let keybinding_y_index = keybinding_x_index + 1;
@@ -442,3 +299,212 @@ pub fn calculate_jwt_output_indices(
         keybinding_y_index,
     }
 }
+
+/// Position of the Show circuit inputs shared with Prepare, and of its public values, within the
+/// witness vector.
+#[derive(Debug, Clone, Copy)]
+pub struct ShowInputLayout {
+    pub age_above_18_index: usize,
+    pub device_key_x_index: usize,
+    pub device_key_y_index: usize,
+    pub message_hash_index: usize,
+    /// `currentYear`, then `currentMonth` and `currentDay`
+    pub current_date_start: usize,
+    pub claim_start: usize,
+    pub claim_len: usize,
+}
+
+impl ShowInputLayout {
+    pub fn current_date_range(&self) -> Range<usize> {
+        self.current_date_start..self.current_date_start + 3
+    }
+
+    pub fn claim_range(&self) -> Range<usize> {
+        self.claim_start..self.claim_start + self.claim_len
+    }
+}
+
+/// Calculate the witness indices of the Show circuit inputs shared with Prepare and of its public
+/// values.
+///
+/// circom numbers wires as the constant, outputs, public inputs, then private inputs, each in
+/// declaration order. For
+/// `component main {public[deviceKeyX, deviceKeyY, messageHash, currentYear, currentMonth,
+/// currentDay]} = Show(maxClaimsLength)`:
+/// 1. `ageAbove18` (output)
+/// 2. `deviceKeyX`, `deviceKeyY`, `messageHash`, `currentYear`, `currentMonth`, `currentDay`
+///    (public)
+/// 3. `sig_r`, `sig_s_inverse`, then `claim[decodedLen]` (private)
+pub fn calculate_show_input_indices(params: &ShowCircuitParams) -> ShowInputLayout {
+    let age_above_18_index = 1; // first output, after the constant
+    let device_key_x_index = age_above_18_index + 1;
+    let device_key_y_index = device_key_x_index + 1;
+    let message_hash_index = device_key_y_index + 1;
+    let current_date_start = message_hash_index + 1;
+    let claim_start = current_date_start + 5; // past the date, sig_r and sig_s_inverse
+
+    ShowInputLayout {
+        age_above_18_index,
+        device_key_x_index,
+        device_key_y_index,
+        message_hash_index,
+        current_date_start,
+        claim_start,
+        claim_len: params.decoded_claim_length(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::{Field, PrimeField};
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+
+    const URL_SAFE_ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    fn modulus() -> BigInt {
+        let hex = Scalar::MODULUS.trim_start_matches("0x");
+        BigInt::parse_bytes(hex.as_bytes(), 16).unwrap()
+    }
+
+    fn unsigned(bytes: &[u8]) -> BigInt {
+        let mut be = vec![0];
+        be.extend_from_slice(bytes);
+        BigInt::from_signed_bytes_be(&be)
+    }
+
+    /// The claim array of `generateShowInputs` in `circom/src/show.ts`: the base64url claim is
+    /// decoded, read as UTF-8 and stored as UTF-16 char codes, zero-padded to `decodedLen`
+    fn ts_show_claim(encoded: &str, decoded_len: usize) -> Vec<Scalar> {
+        let decoded = URL_SAFE_NO_PAD.decode(encoded).unwrap();
+        let mut claim: Vec<Scalar> = String::from_utf8(decoded)
+            .unwrap()
+            .encode_utf16()
+            .map(|code| Scalar::from(u64::from(code)))
+            .collect();
+        claim.resize(decoded_len, Scalar::ZERO);
+        claim
+    }
+
+    /// Prepare inputs carrying only what `compute_prepare_shared_scalars` reads
+    fn prepare_json(x: &[u8], y: &[u8], disclosure: &str, params: &JwtCircuitParams) -> Value {
+        let payload = json!({ "cnf": { "jwk": { "kty": "EC", "crv": "P-256",
+            "x": URL_SAFE_NO_PAD.encode(x), "y": URL_SAFE_NO_PAD.encode(y) } } });
+        let message = format!(
+            "eyJhbGciOiJFUzI1NiJ9.{}",
+            URL_SAFE_NO_PAD.encode(payload.to_string())
+        );
+        let mut claim: Vec<u8> = disclosure.bytes().collect();
+        claim.resize(params.max_claims_length, 0);
+
+        json!({
+            "message": message.bytes().collect::<Vec<u8>>(),
+            "messageLength": message.len(),
+            "claims": [vec![0u8; params.max_claims_length], claim],
+            "claimLengths": ["0", disclosure.len().to_string()],
+            "ageClaimIndex": 1,
+        })
+    }
+
+    fn disclosure() -> impl Strategy<Value = String> {
+        ("[A-Za-z0-9_-]{16,22}", "[0-9]{7}").prop_map(|(salt, birthday)| {
+            let json = format!(r#"["{salt}","roc_birthday","{birthday}"]"#);
+            URL_SAFE_NO_PAD.encode(json)
+        })
+    }
+
+    /// A P-256 coordinate: 32 bytes, below the field modulus
+    fn coordinate() -> impl Strategy<Value = Vec<u8>> {
+        (0u8..0xff, prop::collection::vec(any::<u8>(), 31)).prop_map(|(first, rest)| {
+            let mut bytes = vec![first];
+            bytes.extend(rest);
+            bytes
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn base64url_round_trips(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+            prop_assert_eq!(decode_base64(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap(), bytes.clone());
+            prop_assert_eq!(decode_base64(&URL_SAFE.encode(&bytes)).unwrap(), bytes);
+        }
+
+        #[test]
+        fn non_canonical_trailing_bits_are_rejected(
+            bytes in prop::collection::vec(any::<u8>(), 1..256)
+                .prop_filter("needs a partial final group", |b| b.len() % 3 != 0),
+            bit in 0usize..2,
+        ) {
+            // The last character of a partial group carries 2 or 4 unused bits, which must be zero
+            let mut encoded = URL_SAFE_NO_PAD.encode(&bytes).into_bytes();
+            let last = encoded.last_mut().unwrap();
+            let value = URL_SAFE_ALPHABET.iter().position(|c| c == last).unwrap();
+            *last = URL_SAFE_ALPHABET[value ^ (1 << bit)];
+            let encoded = String::from_utf8(encoded).unwrap();
+
+            prop_assert!(decode_base64(&encoded).is_err(), "accepted {}", encoded);
+        }
+
+        #[test]
+        fn impossible_lengths_are_rejected(encoded in "([A-Za-z0-9_-]{4}){0,16}[A-Za-z0-9_-]") {
+            // One character past a full group encodes fewer than 8 bits
+            prop_assert!(decode_base64(&encoded).is_err());
+        }
+
+        #[test]
+        fn parse_byte_accepts_strings_and_numbers(value in any::<u16>()) {
+            let expected = u8::try_from(value).ok();
+            prop_assert_eq!(parse_byte(&json!(value)).ok(), expected);
+            prop_assert_eq!(parse_byte(&json!(value.to_string())).ok(), expected);
+        }
+
+        #[test]
+        fn bytes_to_bigint_is_big_endian(bytes in prop::collection::vec(any::<u8>(), 0..48)) {
+            prop_assert_eq!(bytes_to_bigint(&bytes), unsigned(&bytes));
+        }
+
+        #[test]
+        fn bigint_to_scalar_matches_field_arithmetic(a in any::<u64>(), b in any::<u128>()) {
+            let a_big = BigInt::from(a);
+            let b_big = BigInt::from(b);
+            let sum = bigint_to_scalar(a_big.clone() + b_big.clone() * b_big.clone()).unwrap();
+            let b_scalar = Scalar::from_u128(b);
+            prop_assert_eq!(bigint_to_scalar(a_big).unwrap(), Scalar::from(a));
+            prop_assert_eq!(sum, Scalar::from(a) + b_scalar * b_scalar);
+        }
+
+        #[test]
+        fn bigint_to_scalar_rejects_values_from_the_modulus(offset in any::<u64>()) {
+            let modulus = modulus();
+            let below = modulus.clone() - BigInt::from(1u8) - BigInt::from(offset);
+            prop_assert_eq!(
+                bigint_to_scalar(below).unwrap(),
+                -Scalar::ONE - Scalar::from(offset)
+            );
+            prop_assert!(bigint_to_scalar(modulus.clone() + BigInt::from(offset)).is_err());
+            prop_assert!(bigint_to_scalar(modulus << (8 + offset % 64)).is_err());
+        }
+
+        #[test]
+        fn prepare_shared_claim_matches_ts_show_encoding(
+            x in coordinate(),
+            y in coordinate(),
+            disclosure in disclosure(),
+        ) {
+            let params = JwtCircuitParams::default();
+            let shared =
+                compute_prepare_shared_scalars(&prepare_json(&x, &y, &disclosure, &params), &params)
+                    .unwrap();
+
+            prop_assert_eq!(
+                shared.claim_scalars,
+                ts_show_claim(&disclosure, params.decoded_claim_length())
+            );
+            prop_assert_eq!(shared.keybinding_x, bigint_to_scalar(unsigned(&x)).unwrap());
+            prop_assert_eq!(shared.keybinding_y, bigint_to_scalar(unsigned(&y)).unwrap());
+        }
+    }
+}
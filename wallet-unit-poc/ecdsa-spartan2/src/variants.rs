@@ -0,0 +1,401 @@
+//! Registry of compiled Prepare variants and selection of the smallest one fitting a credential.
+//!
+//! Key size and proving time grow with `maxMessageLength` (about 420 MB of keys for 1920 bytes
+//! against 1.5 GB for 8 KB), so a wallet holding several variants should prove each credential
+//! with the smallest `JWT(...)` instantiation its SD-JWT fits in.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    circuits::prepare_circuit::{jwt_witness, WitnessGenerator},
+    params::{load_jwt_params, CircuitParams, JwtCircuitParams, ParamsError, CIRCUITS_JSON},
+    setup::{PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY},
+    PrepareCircuit,
+};
+
+/// Base64url SHA-256 digest of a disclosure, as matched in the payload
+const DISCLOSURE_DIGEST_LENGTH: usize = 43;
+
+/// The device key `"x":"` and `"y":"` matches come before the disclosures
+pub(crate) const DEVICE_KEY_MATCHES: usize = 2;
+
+/// A compiled Prepare circuit with its setup keys
+#[derive(Debug, Clone)]
+pub struct PrepareVariant {
+    pub name: String,
+    pub params: JwtCircuitParams,
+    pub r1cs_path: PathBuf,
+    pub proving_key_path: PathBuf,
+    pub verifying_key_path: PathBuf,
+    pub witness_generator: WitnessGenerator,
+}
+
+impl PrepareVariant {
+    /// Circuit for this variant over the given inputs
+    pub fn circuit(&self, input_path: Option<PathBuf>) -> PrepareCircuit {
+        PrepareCircuit::new(input_path)
+            .with_params(self.params)
+            .with_r1cs_path(self.r1cs_path.clone())
+            .with_witness_generator(self.witness_generator)
+    }
+}
+
+/// Sizes of an SD-JWT, in the units of the `JWT(...)` template parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CredentialSize {
+    /// SHA-256 padded length of the signed `header.payload`
+    pub message_length: usize,
+    pub b64_payload_length: usize,
+    /// Disclosures plus the two device key matches
+    pub matches: usize,
+    pub substring_length: usize,
+    /// SHA-256 padded length of the longest disclosure
+    pub claim_length: usize,
+}
+
+impl CredentialSize {
+    /// Measure an SD-JWT `<issuer-jwt>~<disclosure>~...~[<kb-jwt>]`
+    pub fn measure(sd_jwt: &str) -> Result<Self, VariantError> {
+        let SdJwtParts {
+            header,
+            payload,
+            disclosures,
+        } = SdJwtParts::split(sd_jwt)?;
+
+        let signed_length = header.len() + 1 + payload.len();
+        Ok(Self {
+            message_length: sha256_padded_length(signed_length),
+            b64_payload_length: payload.len(),
+            matches: DEVICE_KEY_MATCHES + disclosures.len(),
+            substring_length: if disclosures.is_empty() {
+                0
+            } else {
+                DISCLOSURE_DIGEST_LENGTH
+            },
+            claim_length: disclosures
+                .iter()
+                .map(|disclosure| sha256_padded_length(disclosure.len()))
+                .max()
+                .unwrap_or(0),
+        })
+    }
+
+    pub fn fits(&self, params: &JwtCircuitParams) -> bool {
+        self.message_length <= params.max_message_length
+            && self.b64_payload_length <= params.max_b64_payload_length
+            && self.matches <= params.max_matches
+            && self.substring_length <= params.max_substring_length
+            && self.claim_length <= params.max_claims_length
+    }
+}
+
+impl fmt::Display for CredentialSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "message {} B, payload {} B, {} matches, claims {} B",
+            self.message_length, self.b64_payload_length, self.matches, self.claim_length
+        )
+    }
+}
+
+/// Base64url segments of an SD-JWT, in the order the Prepare circuit sees them
+pub(crate) struct SdJwtParts<'a> {
+    pub header: &'a str,
+    pub payload: &'a str,
+    /// Disclosures in presentation order; the claim at `ageClaimIndex` is the disclosure at
+    /// `ageClaimIndex - DEVICE_KEY_MATCHES`
+    pub disclosures: Vec<&'a str>,
+}
+
+impl<'a> SdJwtParts<'a> {
+    /// Split `<issuer-jwt>~<disclosure>~...~[<kb-jwt>]`
+    pub fn split(sd_jwt: &'a str) -> Result<Self, VariantError> {
+        let mut parts = sd_jwt.trim().split('~');
+        let issuer_jwt = parts.next().unwrap_or_default();
+        let mut segments = issuer_jwt.split('.');
+        let (Some(header), Some(payload), Some(_signature), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err(VariantError::Malformed(
+                "issuer JWT must have three '.'-separated segments",
+            ));
+        };
+
+        // The trailing part is empty, or the key binding JWT which the circuit does not see
+        let disclosures = parts
+            .filter(|part| !part.is_empty() && !part.contains('.'))
+            .collect();
+        Ok(Self {
+            header,
+            payload,
+            disclosures,
+        })
+    }
+}
+
+/// Message length after SHA-256 padding (`0x80`, zeros, 64-bit length), as in `sha256Pad`
+fn sha256_padded_length(len: usize) -> usize {
+    (len + 9).div_ceil(64) * 64
+}
+
+#[derive(Debug)]
+pub enum VariantError {
+    Malformed(&'static str),
+    /// No registered variant is large enough for the credential
+    NoFittingVariant {
+        size: CredentialSize,
+        largest: Option<JwtCircuitParams>,
+    },
+}
+
+impl fmt::Display for VariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariantError::Malformed(reason) => write!(f, "malformed SD-JWT: {reason}"),
+            VariantError::NoFittingVariant {
+                size,
+                largest: Some(largest),
+            } => write!(
+                f,
+                "no Prepare variant fits the credential ({size}); the largest is {}",
+                CircuitParams::from(*largest)
+            ),
+            VariantError::NoFittingVariant {
+                size,
+                largest: None,
+            } => write!(
+                f,
+                "no Prepare variant registered for the credential ({size})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VariantError {}
+
+/// Compiled Prepare variants, kept ordered from smallest to largest
+#[derive(Debug, Clone, Default)]
+pub struct VariantRegistry {
+    variants: Vec<PrepareVariant>,
+}
+
+impl VariantRegistry {
+    /// Registry holding the `jwt` circuit built with this crate and its keys under `keys/`, with
+    /// the instantiation read from `circuits.json`
+    pub fn builtin() -> Result<Self, ParamsError> {
+        let mut registry = Self::default();
+        registry.register(PrepareVariant {
+            name: "jwt".to_string(),
+            params: load_jwt_params(Path::new(CIRCUITS_JSON), "jwt")?,
+            r1cs_path: PathBuf::from("../circom/build/jwt/jwt_js/jwt.r1cs"),
+            proving_key_path: PathBuf::from(PREPARE_PROVING_KEY),
+            verifying_key_path: PathBuf::from(PREPARE_VERIFYING_KEY),
+            witness_generator: jwt_witness,
+        });
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, variant: PrepareVariant) {
+        let position = self
+            .variants
+            .partition_point(|v| size_key(&v.params) <= size_key(&variant.params));
+        self.variants.insert(position, variant);
+    }
+
+    pub fn variants(&self) -> &[PrepareVariant] {
+        &self.variants
+    }
+
+    /// Smallest registered variant the SD-JWT fits in
+    pub fn select_variant(&self, sd_jwt: &str) -> Result<&PrepareVariant, VariantError> {
+        let size = CredentialSize::measure(sd_jwt)?;
+        self.variants
+            .iter()
+            .find(|variant| size.fits(&variant.params))
+            .ok_or_else(|| VariantError::NoFittingVariant {
+                size,
+                largest: self.variants.last().map(|variant| variant.params),
+            })
+    }
+}
+
+/// Ordering by circuit size: the message length dominates the constraint count
+fn size_key(params: &JwtCircuitParams) -> (usize, usize, usize, usize, usize) {
+    (
+        params.max_message_length,
+        params.max_b64_payload_length,
+        params.max_claims_length,
+        params.max_matches,
+        params.max_substring_length,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An SD-JWT whose segments have the given lengths; only lengths matter to the measurement
+    fn sd_jwt(header: usize, payload: usize, disclosures: &[usize]) -> String {
+        let mut sd_jwt = format!("{}.{}.sig", "h".repeat(header), "p".repeat(payload));
+        for &length in disclosures {
+            sd_jwt.push('~');
+            sd_jwt.push_str(&"d".repeat(length));
+        }
+        sd_jwt
+    }
+
+    fn params(message: usize, claims: usize) -> JwtCircuitParams {
+        JwtCircuitParams {
+            max_message_length: message,
+            max_b64_payload_length: message,
+            max_matches: 4,
+            max_substring_length: 50,
+            max_claims_length: claims,
+        }
+    }
+
+    fn variant(name: &str, params: JwtCircuitParams) -> PrepareVariant {
+        PrepareVariant {
+            name: name.to_string(),
+            params,
+            r1cs_path: PathBuf::from(format!("{name}.r1cs")),
+            proving_key_path: PathBuf::from(format!("{name}_proving.key")),
+            verifying_key_path: PathBuf::from(format!("{name}_verifying.key")),
+            witness_generator: jwt_witness,
+        }
+    }
+
+    fn registry() -> VariantRegistry {
+        let mut registry = VariantRegistry::default();
+        registry.register(variant("large", params(8192, 256)));
+        registry.register(variant("small", params(1024, 128)));
+        registry.register(variant("medium", params(1920, 128)));
+        registry
+    }
+
+    fn names(registry: &VariantRegistry) -> Vec<&str> {
+        registry
+            .variants()
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn measure_pads_as_the_circuit_does() {
+        // A trailing key binding JWT and the empty segment after the last `~` are not disclosures
+        let credential = format!("{}~kb.jwt.sig", sd_jwt(20, 100, &[30, 60]));
+        assert_eq!(
+            CredentialSize::measure(&credential).unwrap(),
+            CredentialSize {
+                // 20 + 1 + 100 bytes plus 9 bytes of padding round up to three blocks
+                message_length: 192,
+                b64_payload_length: 100,
+                matches: DEVICE_KEY_MATCHES + 2,
+                substring_length: DISCLOSURE_DIGEST_LENGTH,
+                claim_length: 128,
+            }
+        );
+
+        let bare = CredentialSize::measure(&format!("{}~", sd_jwt(20, 34, &[]))).unwrap();
+        assert_eq!(bare.message_length, 64);
+        assert_eq!(bare.matches, DEVICE_KEY_MATCHES);
+        assert_eq!((bare.substring_length, bare.claim_length), (0, 0));
+
+        assert!(matches!(
+            CredentialSize::measure("header.payload~disclosure"),
+            Err(VariantError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn fits_checks_every_parameter_inclusively() {
+        let size = CredentialSize::measure(&sd_jwt(20, 100, &[30, 60])).unwrap();
+        let exact = JwtCircuitParams {
+            max_message_length: 192,
+            max_b64_payload_length: 100,
+            max_matches: 4,
+            max_substring_length: DISCLOSURE_DIGEST_LENGTH,
+            max_claims_length: 128,
+        };
+        assert!(size.fits(&exact));
+
+        for too_small in [
+            JwtCircuitParams {
+                max_message_length: 191,
+                ..exact
+            },
+            JwtCircuitParams {
+                max_b64_payload_length: 99,
+                ..exact
+            },
+            JwtCircuitParams {
+                max_matches: 3,
+                ..exact
+            },
+            JwtCircuitParams {
+                max_substring_length: 42,
+                ..exact
+            },
+            JwtCircuitParams {
+                max_claims_length: 127,
+                ..exact
+            },
+        ] {
+            assert!(!size.fits(&too_small), "{too_small:?}");
+        }
+    }
+
+    #[test]
+    fn register_keeps_variants_ordered_by_size() {
+        assert_eq!(names(&registry()), ["small", "medium", "large"]);
+    }
+
+    #[test]
+    fn select_variant_picks_the_smallest_that_fits() {
+        let registry = registry();
+        let select = |payload, claim| {
+            registry
+                .select_variant(&sd_jwt(20, payload, &[claim]))
+                .map(|v| v.name.as_str())
+        };
+
+        assert_eq!(select(500, 60).unwrap(), "small");
+        assert_eq!(select(1500, 60).unwrap(), "medium");
+        // Fits the message length of "medium" but not its claims
+        assert_eq!(select(1500, 150).unwrap(), "large");
+    }
+
+    #[test]
+    fn select_variant_reports_when_nothing_fits() {
+        let credential = sd_jwt(20, 9000, &[60]);
+        match registry().select_variant(&credential) {
+            Err(VariantError::NoFittingVariant { size, largest }) => {
+                assert_eq!(size.b64_payload_length, 9000);
+                assert_eq!(largest, Some(params(8192, 256)));
+            }
+            other => panic!("expected NoFittingVariant, got {other:?}"),
+        }
+
+        assert!(matches!(
+            VariantRegistry::default().select_variant(&credential),
+            Err(VariantError::NoFittingVariant { largest: None, .. })
+        ));
+    }
+
+    #[test]
+    fn builtin_uses_the_circuits_json_instantiation() {
+        let registry = VariantRegistry::builtin().unwrap();
+        assert_eq!(
+            registry.variants()[0].params,
+            load_jwt_params(Path::new(CIRCUITS_JSON), "jwt").unwrap()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! `AsyncProver` over the stand-in circuit: concurrent proofs, the concurrency bound and
+//! cancellation. Run with `cargo test --features async`.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ecdsa_spartan2::{
+    sample_shared_blinds, try_setup_circuit_keys, AsyncProver, CancellationToken, ProofObserver,
+    ProofPhase, ProverError, E,
+};
+use tokio::runtime::Runtime;
+use zkid_verifier::{
+    test_utils::{ToyCircuit, SHARED},
+    verify,
+};
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("tokio runtime")
+}
+
+/// Tracks how many proofs are between their first and last phase at once
+#[derive(Default)]
+struct Concurrency {
+    running: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl ProofObserver for Concurrency {
+    fn on_phase_start(&self, phase: ProofPhase) {
+        if phase == ProofPhase::WitnessGeneration {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(running, Ordering::SeqCst);
+            // Keep the proof running long enough for the others to be scheduled
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn on_phase_end(&self, phase: ProofPhase, _: Duration) {
+        if phase == ProofPhase::PcsOpen {
+            self.running.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn concurrent_proofs_verify_and_reblind() {
+    let (pk, vk) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+    let prover = AsyncProver::new(Arc::new(pk), 2);
+
+    runtime().block_on(async {
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let prover = prover.clone();
+                tokio::spawn(async move { prover.prove(ToyCircuit::prepare(&SHARED)).await })
+            })
+            .collect();
+
+        let mut proofs = Vec::new();
+        for task in tasks {
+            let artifacts = task.await.unwrap().expect("async prove");
+            verify(&artifacts.proof, &vk).expect("async proof verifies");
+            proofs.push(artifacts);
+        }
+
+        let proven = proofs.pop().unwrap();
+        let blinds = sample_shared_blinds::<E>(proven.instance.num_shared_rows());
+        let reblinded = prover
+            .reblind(
+                ToyCircuit::prepare(&SHARED),
+                proven.instance,
+                proven.witness,
+                blinds,
+            )
+            .await
+            .expect("async reblind");
+        verify(&reblinded.proof, &vk).expect("reblinded proof verifies");
+    });
+}
+
+#[test]
+fn proofs_beyond_the_bound_wait_for_a_permit() {
+    let (pk, _) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+    let prover = AsyncProver::new(Arc::new(pk), 1);
+    let concurrency = Arc::new(Concurrency::default());
+
+    runtime().block_on(async {
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let prover = prover.clone();
+                let observer: Arc<dyn ProofObserver> = concurrency.clone();
+                tokio::spawn(async move {
+                    prover
+                        .prove_with_observer(ToyCircuit::prepare(&SHARED), observer)
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().expect("async prove");
+        }
+    });
+
+    assert_eq!(concurrency.max.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn cancelled_proof_returns_cancelled() {
+    let (pk, _) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+    let prover = AsyncProver::new(Arc::new(pk), 1);
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let res = runtime()
+        .block_on(prover.prove_with_observer(ToyCircuit::prepare(&SHARED), Arc::new(token)));
+    assert!(matches!(res, Err(ProverError::Cancelled)));
+}
@@ -0,0 +1,154 @@
+//! Keys and one honest presentation over the compiled circuits under `../circom/build` with the
+//! default inputs, set up once per test binary. Only the `e2e` tests use it.
+
+use std::{fs, sync::OnceLock};
+
+use ecdsa_spartan2::{
+    challenge_message_hash, generate_proof, prove_and_reblind, reblind_proof, sample_shared_blinds,
+    try_setup_circuit_keys, PrepareCircuit, Presentation, ProofArtifacts, ProverKey, Scalar,
+    SecretBlinds, ShowCircuit, E,
+};
+use ff::{Field, PrimeField};
+use p256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+use rust_witness::BigInt;
+use serde_json::Value;
+use zkid_verifier::{Date, Predicate, Proof, VerificationPolicy, VerifierKey};
+
+/// Challenge the device signature of the default Show inputs is over (`DEFAULT_CHALLENGE` of
+/// `circom/src/save_inputs.ts`)
+pub const CHALLENGE: &[u8] = b"zkid-default-challenge";
+
+/// Date the default Show inputs are presented on (`save_inputs.ts`)
+pub const DATE: Date = Date {
+    year: 2025,
+    month: 1,
+    day: 1,
+};
+
+/// The default credential's holder is born on 1986-06-05 and turns 18 on this date
+pub const EIGHTEENTH_BIRTHDAY: Date = Date {
+    year: 2004,
+    month: 6,
+    day: 5,
+};
+
+/// Device key the default credential is bound to (`MOCK_DEVICE_PRIVATE_KEY` of
+/// `circom/src/mock-vc-generator.ts`)
+const DEVICE_KEY: &str = "9a8b7c6d5e4f30211203f4e5d6c7b8a99a8b7c6d5e4f30211203f4e5d6c7b8a9";
+
+pub struct Fixture {
+    pub prepare_pk: ProverKey,
+    pub prepare_vk: VerifierKey,
+    pub show_pk: ProverKey,
+    pub show_vk: VerifierKey,
+    pub blinds: SecretBlinds<Scalar>,
+    /// Reblinded Prepare proof
+    pub prepare: ProofArtifacts,
+    pub show_proof: Proof,
+}
+
+impl Fixture {
+    /// The honest presentation
+    pub fn presentation(&self) -> Presentation {
+        presentation(&self.show_proof)
+    }
+
+    /// Show proof answering `challenge`, signed by the credential's device key and linked to the
+    /// fixture's Prepare proof
+    pub fn show_proof_for(&self, challenge: &[u8]) -> Proof {
+        self.show_proof_on(challenge, DATE)
+    }
+
+    /// Show proof answering `challenge` with `ageAbove18` computed on `date`
+    pub fn show_proof_on(&self, challenge: &[u8], date: Date) -> Proof {
+        let mut inputs = signed_show_inputs(&device_key(), challenge);
+        inputs["currentYear"] = date.year.to_string().into();
+        inputs["currentMonth"] = date.month.to_string().into();
+        inputs["currentDay"] = date.day.to_string().into();
+        prove_and_reblind(ShowCircuit::from_json(inputs), &self.show_pk, &self.blinds)
+            .expect("show prove")
+    }
+}
+
+pub fn fixture() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| {
+        let (prepare_pk, prepare_vk) =
+            try_setup_circuit_keys(PrepareCircuit::default()).expect("prepare setup");
+        let (show_pk, show_vk) =
+            try_setup_circuit_keys(ShowCircuit::default()).expect("show setup");
+
+        let proven = generate_proof(PrepareCircuit::default(), &prepare_pk).expect("prepare prove");
+        let blinds = sample_shared_blinds::<E>(proven.instance.num_shared_rows());
+        let prepare = reblind_proof(
+            PrepareCircuit::default(),
+            &prepare_pk,
+            proven.instance,
+            proven.witness,
+            &blinds,
+        )
+        .expect("prepare reblind");
+        let show_proof =
+            prove_and_reblind(ShowCircuit::default(), &show_pk, &blinds).expect("show prove");
+
+        Fixture {
+            prepare_pk,
+            prepare_vk,
+            show_pk,
+            show_vk,
+            blinds,
+            prepare,
+            show_proof,
+        }
+    })
+}
+
+/// The fixture's Prepare proof presented with `show_proof`
+pub fn presentation(show_proof: &Proof) -> Presentation {
+    Presentation::new(fixture().prepare.proof.clone(), show_proof.clone())
+}
+
+/// Policy the honest presentation satisfies: age over 18 on [`DATE`], answering [`CHALLENGE`]
+pub fn policy() -> VerificationPolicy {
+    Predicate::AgeOver18.policy(CHALLENGE, DATE)
+}
+
+/// The default Show inputs
+pub fn show_inputs() -> Value {
+    let contents = fs::read_to_string("../circom/inputs/show/default.json").expect("show inputs");
+    serde_json::from_str(&contents).expect("show inputs JSON")
+}
+
+/// The device key the default credential is bound to
+pub fn device_key() -> SigningKey {
+    let bytes: Vec<u8> = (0..DEVICE_KEY.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&DEVICE_KEY[i..i + 2], 16).unwrap())
+        .collect();
+    SigningKey::from_slice(&bytes).expect("device key")
+}
+
+/// The default Show inputs with `device_key` and its signature over `challenge`
+pub fn signed_show_inputs(device_key: &SigningKey, challenge: &[u8]) -> Value {
+    let mut inputs = show_inputs();
+    // The hash is below the P-256 order, so its scalar encoding is the prehash, little-endian
+    let mut prehash = [0u8; 32];
+    prehash.copy_from_slice(challenge_message_hash(challenge).to_repr().as_ref());
+    prehash.reverse();
+    let signature: Signature = device_key.sign_prehash(&prehash).unwrap();
+    let (r, s) = signature.split_scalars();
+    let s_inverse = Option::<p256::Scalar>::from((*s).invert()).unwrap();
+    let point = device_key.verifying_key().to_encoded_point(false);
+
+    inputs["deviceKeyX"] = decimal(point.x().unwrap()).into();
+    inputs["deviceKeyY"] = decimal(point.y().unwrap()).into();
+    inputs["messageHash"] = decimal(&prehash).into();
+    inputs["sig_r"] = decimal(&r.to_repr()).into();
+    inputs["sig_s_inverse"] = decimal(&s_inverse.to_repr()).into();
+    inputs
+}
+
+fn decimal(bytes: &[u8]) -> String {
+    let unsigned: Vec<u8> = [0].iter().chain(bytes).copied().collect();
+    BigInt::from_signed_bytes_be(&unsigned).to_string()
+}
@@ -0,0 +1,108 @@
+//! The OpenID4VP relying party of `zkid_verifier::http` for the keys of the circom fixture,
+//! served on a free localhost port once per test binary, and the wallet side of the exchange.
+
+use std::{net::TcpListener, sync::Arc, sync::OnceLock, thread};
+
+use zkid_verifier::{
+    http, verify, vp_token, AuthorizationRequest, DeserializeLimits, ErrorResponse, Presentation,
+    PresentationBundle, Proof, RelyingParty, VerificationResponse,
+};
+
+use super::circom::{fixture, DATE};
+
+pub struct RelyingPartyFixture {
+    pub prepare_vk_bytes: Vec<u8>,
+    pub show_vk_bytes: Vec<u8>,
+    /// Base URL of the relying party
+    pub url: String,
+}
+
+pub fn relying_party() -> &'static RelyingPartyFixture {
+    static RELYING_PARTY: OnceLock<RelyingPartyFixture> = OnceLock::new();
+    RELYING_PARTY.get_or_init(|| {
+        let fixture = fixture();
+        let prepare_vk_bytes = bincode::serialize(&fixture.prepare_vk).unwrap();
+        let show_vk_bytes = bincode::serialize(&fixture.show_vk).unwrap();
+        let url = serve(&prepare_vk_bytes, &show_vk_bytes);
+        RelyingPartyFixture {
+            prepare_vk_bytes,
+            show_vk_bytes,
+            url,
+        }
+    })
+}
+
+/// Start the relying party on a free localhost port in the background
+fn serve(prepare_vk_bytes: &[u8], show_vk_bytes: &[u8]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind localhost");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    listener.set_nonblocking(true).unwrap();
+    let relying_party = RelyingParty::new(
+        url.clone(),
+        format!("{url}{}", http::RESPONSE_PATH),
+        prepare_vk_bytes,
+        show_vk_bytes,
+        DeserializeLimits::default(),
+    )
+    .expect("relying party")
+    // The Show proofs of the fixture are dated like the default inputs
+    .with_date(DATE);
+
+    thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(async {
+                let listener = tokio::net::TcpListener::from_std(listener)?;
+                http::serve(listener, Arc::new(relying_party)).await
+            })
+            .expect("relying party server");
+    });
+    url
+}
+
+/// Fetch an authorization request for the age-over-18 predicate
+pub fn authorize(relying_party: &RelyingPartyFixture) -> AuthorizationRequest {
+    ureq::get(&format!(
+        "{}/authorize?predicate=age_over_18",
+        relying_party.url
+    ))
+    .call()
+    .expect("authorization request")
+    .into_json()
+    .unwrap()
+}
+
+/// `vp_token` of a bundle carrying `nonce` and the two proofs, with the public values they return
+pub fn answer(
+    relying_party: &RelyingPartyFixture,
+    nonce: &str,
+    prepare_proof: &Proof,
+    show_proof: &Proof,
+) -> String {
+    let fixture = fixture();
+    let bundle = PresentationBundle::new(
+        Presentation::new(prepare_proof.clone(), show_proof.clone()),
+        &relying_party.prepare_vk_bytes,
+        &relying_party.show_vk_bytes,
+        verify(prepare_proof, &fixture.prepare_vk).unwrap_or_default(),
+        verify(show_proof, &fixture.show_vk).unwrap_or_default(),
+        nonce.as_bytes().to_vec(),
+    );
+    vp_token(&bundle.to_cbor().unwrap())
+}
+
+/// Post `vp_token` to the relying party: its verification response, or its error body
+pub fn post(
+    response_uri: &str,
+    vp_token: &str,
+    state: &str,
+) -> Result<VerificationResponse, ErrorResponse> {
+    match ureq::post(response_uri).send_form(&[("vp_token", vp_token), ("state", state)]) {
+        Ok(response) => Ok(response.into_json().unwrap()),
+        Err(ureq::Error::Status(400, response)) => Err(response.into_json().unwrap()),
+        Err(e) => panic!("relying party failed: {e}"),
+    }
+}
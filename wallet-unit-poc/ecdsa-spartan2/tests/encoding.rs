@@ -0,0 +1,30 @@
+//! Canonical proof encoding of real proofs, with uncompressed and compressed points: decoded
+//! proofs must verify and re-encode to the same bytes.
+
+mod common;
+
+use common::circom::fixture;
+use zkid_verifier::{decode_proof, encode_proof, encode_proof_with, verify, PointEncoding};
+
+#[test]
+fn canonical_encoding_round_trips() {
+    let fixture = fixture();
+    let bytes = encode_proof(&fixture.show_proof).unwrap();
+    let decoded = decode_proof(&bytes).expect("canonical proof");
+    verify(&decoded, &fixture.show_vk).expect("decoded proof");
+    assert_eq!(encode_proof(&decoded).unwrap(), bytes);
+}
+
+#[test]
+fn compressed_points_round_trip() {
+    let fixture = fixture();
+    let bytes = encode_proof_with(&fixture.show_proof, PointEncoding::Compressed).unwrap();
+    assert!(bytes.len() < encode_proof(&fixture.show_proof).unwrap().len());
+
+    let decoded = decode_proof(&bytes).expect("proof with compressed points");
+    verify(&decoded, &fixture.show_vk).expect("decoded proof");
+    assert_eq!(
+        encode_proof_with(&decoded, PointEncoding::Compressed).unwrap(),
+        bytes
+    );
+}
@@ -0,0 +1,50 @@
+//! Text and binary transports of a real presentation: the JSON envelope and the CBOR bundle
+//! must decode to a presentation that still verifies.
+
+mod common;
+
+use common::circom::{fixture, policy};
+use zkid_verifier::{
+    verify_presentation, DeserializeLimits, PresentationBundle, PresentationEnvelope, VkIds,
+};
+
+#[test]
+fn envelope_round_trips() {
+    let fixture = fixture();
+    let vk_id = VkIds {
+        prepare: "prepare".into(),
+        show: "show".into(),
+    };
+    let envelope = PresentationEnvelope::new(&fixture.presentation(), vk_id, &[], &[]).unwrap();
+    let decoded = PresentationEnvelope::from_json(&envelope.to_json().unwrap())
+        .unwrap()
+        .presentation(&DeserializeLimits::default())
+        .expect("envelope presentation");
+    verify_presentation(&decoded, &fixture.prepare_vk, &fixture.show_vk, &policy())
+        .expect("decoded presentation");
+}
+
+#[test]
+fn cbor_bundle_round_trips() {
+    let fixture = fixture();
+    let bundle = PresentationBundle::new(
+        fixture.presentation(),
+        b"prepare vk",
+        b"show vk",
+        vec![],
+        vec![],
+        b"nonce".to_vec(),
+    );
+    let bytes = bundle.to_cbor().unwrap();
+    let decoded =
+        PresentationBundle::from_cbor(&bytes, &DeserializeLimits::default()).expect("CBOR bundle");
+    assert_eq!(decoded.nonce, b"nonce");
+    assert_eq!(decoded.to_cbor().unwrap(), bytes);
+    verify_presentation(
+        &decoded.presentation(),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(),
+    )
+    .expect("decoded presentation");
+}
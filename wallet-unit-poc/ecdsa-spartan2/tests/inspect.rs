@@ -0,0 +1,54 @@
+//! Inspection reports of a real proof and presentation.
+
+mod common;
+
+use common::circom::fixture;
+use zkid_verifier::{
+    canonical::MAGIC, encode_proof, inspect, inspect::Artifact, DeserializeLimits, KnownKey,
+};
+
+#[test]
+fn inspect_reports_presentation() {
+    let fixture = fixture();
+    let keys = [
+        KnownKey {
+            circuit: "prepare",
+            vk: &fixture.prepare_vk,
+            vk_id: "prepare".into(),
+        },
+        KnownKey {
+            circuit: "show",
+            vk: &fixture.show_vk,
+            vk_id: "show".into(),
+        },
+    ];
+    let limits = DeserializeLimits::default();
+
+    let bytes = bincode::serialize(&fixture.show_proof).unwrap();
+    let report = inspect(&bytes, &limits, &keys).expect("proof report");
+    assert_eq!(report.format.name, "bincode");
+    let Artifact::Proof(proof) = report.artifact else {
+        panic!("not reported as a proof: {:?}", report.artifact);
+    };
+    assert_eq!(proof.circuit, Some("show"));
+    assert_eq!(proof.verification.as_deref(), Some("ok"));
+    assert!(proof.shared_rows > 0);
+    assert_eq!(proof.comm_w_shared.len(), proof.shared_rows);
+    assert_eq!(
+        proof.components.iter().map(|c| c.bytes).sum::<usize>(),
+        encode_proof(&fixture.show_proof).unwrap().len() - MAGIC.len() - 2
+    );
+
+    let bytes = bincode::serialize(&fixture.presentation()).unwrap();
+    let report = inspect(&bytes, &limits, &keys).expect("presentation report");
+    let Artifact::Presentation(presentation) = report.artifact else {
+        panic!("not reported as a presentation: {:?}", report.artifact);
+    };
+    assert!(presentation.linked);
+    assert_eq!(presentation.prepare.verification.as_deref(), Some("ok"));
+    assert_eq!(presentation.show.verification.as_deref(), Some("ok"));
+    assert_eq!(
+        presentation.prepare.comm_w_shared,
+        presentation.show.comm_w_shared
+    );
+}
@@ -0,0 +1,158 @@
+//! Phase reporting and cancellation of setup, prove and reblind, over a small stand-in circuit so
+//! that no compiled circom circuit is needed.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use ecdsa_spartan2::{
+    generate_proof_with_observer, reblind_proof_with_observer, sample_shared_blinds,
+    setup_circuit_keys_with_observer, try_setup_circuit_keys, try_setup_circuit_keys_with_observer,
+    CancellationToken, ProofObserver, ProofPhase, ProverError, E,
+};
+use zkid_verifier::test_utils::{ToyCircuit, SHARED};
+
+/// Records phase starts and ends, and cancels once `cancel_at` starts
+#[derive(Default)]
+struct Recorder {
+    starts: Mutex<Vec<ProofPhase>>,
+    ends: Mutex<Vec<ProofPhase>>,
+    cancel_at: Option<ProofPhase>,
+    cancelled: AtomicBool,
+}
+
+impl Recorder {
+    fn cancelling_at(phase: ProofPhase) -> Self {
+        Self {
+            cancel_at: Some(phase),
+            ..Default::default()
+        }
+    }
+
+    fn starts(&self) -> Vec<ProofPhase> {
+        self.starts.lock().unwrap().clone()
+    }
+
+    fn ends(&self) -> Vec<ProofPhase> {
+        self.ends.lock().unwrap().clone()
+    }
+}
+
+impl ProofObserver for Recorder {
+    fn on_phase_start(&self, phase: ProofPhase) {
+        self.starts.lock().unwrap().push(phase);
+        if self.cancel_at == Some(phase) {
+            self.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn on_phase_end(&self, phase: ProofPhase, _: Duration) {
+        self.ends.lock().unwrap().push(phase);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn prove_and_reblind_report_every_phase_in_order() {
+    let (pk, _) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+
+    let recorder = Recorder::default();
+    let proven =
+        generate_proof_with_observer(ToyCircuit::prepare(&SHARED), &pk, &recorder).unwrap();
+    let prove_phases = vec![
+        ProofPhase::WitnessGeneration,
+        ProofPhase::PrepProve,
+        ProofPhase::WitnessCommit,
+        ProofPhase::Sumcheck,
+        ProofPhase::PcsOpen,
+    ];
+    assert_eq!(recorder.starts(), prove_phases);
+    assert_eq!(recorder.ends(), prove_phases);
+
+    let recorder = Recorder::default();
+    let blinds = sample_shared_blinds::<E>(proven.instance.num_shared_rows());
+    reblind_proof_with_observer(
+        ToyCircuit::prepare(&SHARED),
+        &pk,
+        proven.instance,
+        proven.witness,
+        &blinds,
+        &recorder,
+    )
+    .unwrap();
+    let reblind_phases = vec![
+        ProofPhase::Reblind,
+        ProofPhase::Sumcheck,
+        ProofPhase::PcsOpen,
+    ];
+    assert_eq!(recorder.starts(), reblind_phases);
+    assert_eq!(recorder.ends(), reblind_phases);
+}
+
+#[test]
+fn cancelled_token_stops_before_the_first_phase() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let res = try_setup_circuit_keys_with_observer(ToyCircuit::prepare(&SHARED), &token);
+    assert!(matches!(res, Err(ProverError::Cancelled)));
+
+    let (pk, _) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+    let res = generate_proof_with_observer(ToyCircuit::prepare(&SHARED), &pk, &token);
+    assert!(matches!(res, Err(ProverError::Cancelled)));
+}
+
+#[test]
+fn cancelling_during_a_phase_discards_its_result() {
+    let (pk, _) = try_setup_circuit_keys(ToyCircuit::prepare(&SHARED)).unwrap();
+
+    for phase in [
+        ProofPhase::WitnessGeneration,
+        ProofPhase::PrepProve,
+        ProofPhase::Sumcheck,
+    ] {
+        let recorder = Recorder::cancelling_at(phase);
+        let res = generate_proof_with_observer(ToyCircuit::prepare(&SHARED), &pk, &recorder);
+        assert!(matches!(res, Err(ProverError::Cancelled)), "{phase:?}");
+
+        // Nothing starts after the cancelled phase, except the opening that runs in the same
+        // Spartan2 call as the sumchecks
+        let starts = recorder.starts();
+        let last = if phase == ProofPhase::Sumcheck {
+            ProofPhase::PcsOpen
+        } else {
+            phase
+        };
+        assert_eq!(starts.last(), Some(&last), "{phase:?}");
+    }
+}
+
+#[test]
+fn cancelled_setup_returns_the_error_and_writes_no_keys() {
+    let dir = std::env::temp_dir().join(format!("zkid-observer-{}", std::process::id()));
+    let pk_path = dir.join("toy_proving.key");
+    let vk_path = dir.join("toy_verifying.key");
+
+    let recorder = Recorder::cancelling_at(ProofPhase::Setup);
+    let err = setup_circuit_keys_with_observer(
+        ToyCircuit::prepare(&SHARED),
+        pk_path.to_str().unwrap(),
+        vk_path.to_str().unwrap(),
+        &recorder,
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<ProverError>(),
+        Some(ProverError::Cancelled)
+    ));
+    assert_eq!(recorder.starts(), vec![ProofPhase::Setup]);
+    assert!(!pk_path.exists() && !vk_path.exists());
+}
@@ -0,0 +1,104 @@
+//! End-to-end OpenID4VP flow against the local relying party of `zkid_verifier::http`: the wallet
+//! fetches an authorization request over HTTP, answers it with a CBOR bundle carrying the request
+//! nonce and a Show proof signed over it, and the relying party verifies it.
+//!
+//! Like `soundness.rs`, this runs the compiled circuits under `../circom/build` with the default
+//! inputs; setup and the server are shared by every test (`common::circom`, `common::openid4vp`).
+
+mod common;
+
+use common::{
+    circom::fixture,
+    openid4vp::{answer, authorize, post, relying_party},
+};
+use zkid_verifier::Predicate;
+
+#[test]
+fn honest_presentation_is_accepted_once() {
+    let fixture = fixture();
+    let relying_party = relying_party();
+    let request = authorize(relying_party);
+    assert_eq!(request.response_type, "vp_token");
+    assert_eq!(request.response_mode, "direct_post");
+    assert_eq!(request.predicate, Predicate::AgeOver18);
+    assert_eq!(
+        request.vk_id.show,
+        zkid_verifier::vk_id(&relying_party.show_vk_bytes)
+    );
+
+    let vp_token = answer(
+        relying_party,
+        &request.nonce,
+        &fixture.prepare.proof,
+        &fixture.show_proof_for(request.nonce.as_bytes()),
+    );
+    let verified = post(&request.response_uri, &vp_token, &request.state).expect("accepted");
+    assert!(verified.verified);
+    assert_eq!(verified.state, request.state);
+    assert_eq!(verified.predicate, Predicate::AgeOver18);
+
+    // The request is consumed, so the same response cannot be replayed
+    let replayed = post(&request.response_uri, &vp_token, &request.state).unwrap_err();
+    assert_eq!(replayed.error, "invalid_request");
+}
+
+#[test]
+fn presentation_for_another_nonce_is_rejected() {
+    let fixture = fixture();
+    let relying_party = relying_party();
+    let first = authorize(relying_party);
+    let second = authorize(relying_party);
+    assert_ne!(first.nonce, second.nonce);
+
+    let vp_token = answer(
+        relying_party,
+        &first.nonce,
+        &fixture.prepare.proof,
+        &fixture.show_proof_for(first.nonce.as_bytes()),
+    );
+    let rejected = post(&second.response_uri, &vp_token, &second.state).unwrap_err();
+    assert_eq!(rejected.error, "invalid_request");
+    assert!(rejected.error_description.contains("nonce"));
+}
+
+#[test]
+fn presentation_replayed_under_another_nonce_is_denied() {
+    let fixture = fixture();
+    let relying_party = relying_party();
+    let first = authorize(relying_party);
+    let second = authorize(relying_party);
+
+    // The bundle is rewritten to carry the second nonce, but the Show proof answers the first
+    let vp_token = answer(
+        relying_party,
+        &second.nonce,
+        &fixture.prepare.proof,
+        &fixture.show_proof_for(first.nonce.as_bytes()),
+    );
+    let denied = post(&second.response_uri, &vp_token, &second.state).unwrap_err();
+    assert_eq!(denied.error, "access_denied");
+    assert!(denied.error_description.contains("Show public values"));
+}
+
+#[test]
+fn invalid_proofs_are_denied() {
+    let fixture = fixture();
+    let relying_party = relying_party();
+    let request = authorize(relying_party);
+    // The Prepare proof in place of the Show proof does not verify against the Show key
+    let vp_token = answer(
+        relying_party,
+        &request.nonce,
+        &fixture.prepare.proof,
+        &fixture.prepare.proof,
+    );
+    let denied = post(&request.response_uri, &vp_token, &request.state).unwrap_err();
+    assert_eq!(denied.error, "access_denied");
+}
+
+#[test]
+fn malformed_vp_token_is_rejected() {
+    let request = authorize(relying_party());
+    let rejected = post(&request.response_uri, "not base64url!", &request.state).unwrap_err();
+    assert_eq!(rejected.error, "invalid_request");
+}
@@ -0,0 +1,246 @@
+//! Mapping of DCQL and Presentation Exchange queries onto the Prepare and Show circuits.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ecdsa_spartan2::{
+    evaluate_dcql, evaluate_presentation_definition, query::BIRTH_DATE_CLAIM, CircuitRequest,
+    DcqlQuery, PresentationDefinition, QueryError,
+};
+use serde_json::{json, Value};
+use zkid_verifier::Predicate;
+
+const PID: &str = "urn:eudi:pid:1";
+
+fn encode(value: &Value) -> String {
+    URL_SAFE_NO_PAD.encode(value.to_string())
+}
+
+/// SD-JWT of type `vct` with one disclosure per `(name, value)`, and a key binding JWT
+fn sd_jwt(vct: &str, claims: &[(&str, &str)]) -> String {
+    let header = encode(&json!({"alg": "ES256", "typ": "dc+sd-jwt"}));
+    let payload = encode(&json!({"vct": vct, "_sd": []}));
+    let disclosures: Vec<String> = claims
+        .iter()
+        .map(|(name, value)| encode(&json!(["salt", name, value])))
+        .collect();
+    format!(
+        "{header}.{payload}.sig~{}~kb.jwt.sig",
+        disclosures.join("~")
+    )
+}
+
+fn wallet() -> Vec<String> {
+    vec![
+        sd_jwt("urn:example:diploma", &[(BIRTH_DATE_CLAIM, "0750101")]),
+        sd_jwt(
+            PID,
+            &[("given_name", "Alice"), (BIRTH_DATE_CLAIM, "0750101")],
+        ),
+    ]
+}
+
+fn dcql(claims: Value) -> DcqlQuery {
+    DcqlQuery::from_json(
+        &json!({
+            "credentials": [{
+                "id": "pid",
+                "format": "dc+sd-jwt",
+                "meta": {"vct_values": [PID]},
+                "claims": claims
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap()
+}
+
+fn assert_unsupported(result: Result<CircuitRequest, QueryError>, reason: &str) {
+    match result {
+        Err(e @ QueryError::Unsupported(_)) => {
+            let message = e.to_string();
+            assert!(message.starts_with("unsupported by the ZK path"));
+            assert!(message.contains(reason), "{message}");
+        }
+        other => panic!("query was not rejected as unsupported: {other:?}"),
+    }
+}
+
+#[test]
+fn dcql_age_over_18_selects_credential_and_disclosure() {
+    let expected = CircuitRequest {
+        query_id: "pid".into(),
+        predicate: Predicate::AgeOver18,
+        credential: 1,
+        // After the two device key matches and the given_name disclosure
+        age_claim_index: 3,
+        unproven_vct_values: Some(vec![PID.into()]),
+    };
+    let query = dcql(json!([{"path": ["age_over_18"], "values": [true]}]));
+    assert_eq!(evaluate_dcql(&query, &wallet()).unwrap(), expected);
+
+    let query = dcql(json!([{"path": ["age_equal_or_over", "18"]}]));
+    assert_eq!(evaluate_dcql(&query, &wallet()).unwrap(), expected);
+}
+
+#[test]
+fn dcql_claim_sets_pick_the_provable_option() {
+    let query = dcql(json!([
+        {"id": "name", "path": ["given_name"]},
+        {"id": "age", "path": ["age_over_18"]}
+    ]));
+    assert_unsupported(evaluate_dcql(&query, &wallet()), "given_name");
+
+    let mut query = query;
+    query.credentials[0].claim_sets = Some(vec![vec!["name".into()], vec!["age".into()]]);
+    let request = evaluate_dcql(&query, &wallet()).unwrap();
+    assert_eq!(request.age_claim_index, 3);
+
+    // When no option can be proven, each one's reason is reported
+    query.credentials[0].claim_sets =
+        Some(vec![vec!["name".into()], vec!["name".into(), "age".into()]]);
+    assert_unsupported(
+        evaluate_dcql(&query, &wallet()),
+        "no claim set of 'pid' is only age_over_18",
+    );
+}
+
+#[test]
+fn dcql_malformed_claim_sets_are_invalid() {
+    let mut query = dcql(json!([
+        {"id": "age", "path": ["age_over_18"]},
+        {"path": ["given_name"]}
+    ]));
+    // A later malformed option is reported even though the first one can be proven
+    for (claim_sets, reason) in [
+        (
+            vec![vec!["age".into()], vec!["name".into()]],
+            "unknown claim 'name'",
+        ),
+        (vec![vec!["age".into()], vec![]], "empty claim set"),
+    ] {
+        query.credentials[0].claim_sets = Some(claim_sets);
+        match evaluate_dcql(&query, &wallet()) {
+            Err(e @ QueryError::Invalid(_)) => {
+                assert!(e.to_string().contains(reason), "{e}")
+            }
+            other => panic!("malformed claim sets were not rejected: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn vct_restriction_is_reported_as_unproven() {
+    let request = evaluate_dcql(&dcql(json!([{"path": ["age_over_18"]}])), &wallet()).unwrap();
+    assert_eq!(request.unproven_vct_values, Some(vec![PID.to_string()]));
+
+    let mut query = dcql(json!([{"path": ["age_over_18"]}]));
+    query.credentials[0].meta = None;
+    let request = evaluate_dcql(&query, &wallet()).unwrap();
+    assert_eq!(request.unproven_vct_values, None);
+}
+
+#[test]
+fn dcql_outside_the_circuits_is_unsupported() {
+    assert_unsupported(
+        evaluate_dcql(&dcql(json!([{"path": ["birthdate"]}])), &wallet()),
+        "would be disclosed",
+    );
+    assert_unsupported(
+        evaluate_dcql(
+            &dcql(json!([{"path": ["age_over_18"], "values": [false]}])),
+            &wallet(),
+        ),
+        "other than true",
+    );
+
+    let mut query = dcql(json!([{"path": ["age_over_18"]}]));
+    query.credentials[0].claims = None;
+    assert_unsupported(evaluate_dcql(&query, &wallet()), "whole credential");
+
+    let mut query = dcql(json!([{"path": ["age_over_18"]}]));
+    query.credentials[0].format = "mso_mdoc".into();
+    assert_unsupported(evaluate_dcql(&query, &wallet()), "mso_mdoc");
+
+    let mut query = dcql(json!([{"path": ["age_over_18"]}]));
+    query.credentials.push(query.credentials[0].clone());
+    assert_unsupported(evaluate_dcql(&query, &wallet()), "single credential");
+}
+
+#[test]
+fn dcql_without_matching_credential() {
+    let query = dcql(json!([{"path": ["age_over_18"]}]));
+    // The PID has no birth date disclosure, the diploma is of another type
+    let wallet = [
+        sd_jwt("urn:example:diploma", &[(BIRTH_DATE_CLAIM, "0750101")]),
+        sd_jwt(PID, &[("given_name", "Alice")]),
+        "not an SD-JWT".to_string(),
+    ];
+    assert!(matches!(
+        evaluate_dcql(&query, &wallet),
+        Err(QueryError::NoMatchingCredential { query_id }) if query_id == "pid"
+    ));
+}
+
+fn definition(fields: Value) -> PresentationDefinition {
+    PresentationDefinition::from_json(
+        &json!({
+            "id": "age-check",
+            "input_descriptors": [{
+                "id": "pid",
+                "format": {"vc+sd-jwt": {"sd-jwt_alg_values": ["ES256"]}},
+                "constraints": {"limit_disclosure": "required", "fields": fields}
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn presentation_exchange_maps_onto_the_circuits() {
+    let pid = definition(json!([
+        {"path": ["$.vct"], "filter": {"type": "string", "const": PID}},
+        {"path": ["$.age_over_18"], "filter": {"type": "boolean", "const": true}},
+        {"path": ["$.given_name"], "optional": true}
+    ]));
+    assert_eq!(
+        evaluate_presentation_definition(&pid, &wallet()).unwrap(),
+        CircuitRequest {
+            query_id: "pid".into(),
+            predicate: Predicate::AgeOver18,
+            credential: 1,
+            age_claim_index: 3,
+            unproven_vct_values: Some(vec![PID.into()]),
+        }
+    );
+
+    // Any vct: the first credential with a birth date satisfies it
+    let any_vct = definition(json!([{"path": ["$.age_equal_or_over.18"]}]));
+    let request = evaluate_presentation_definition(&any_vct, &wallet()).unwrap();
+    assert_eq!((request.credential, request.age_claim_index), (0, 2));
+    assert_eq!(request.unproven_vct_values, None);
+}
+
+#[test]
+fn presentation_exchange_outside_the_circuits_is_unsupported() {
+    assert_unsupported(
+        evaluate_presentation_definition(
+            &definition(json!([{"path": ["$.given_name"]}, {"path": ["$.age_over_18"]}])),
+            &wallet(),
+        ),
+        "$.given_name",
+    );
+    assert_unsupported(
+        evaluate_presentation_definition(
+            &definition(json!([{"path": ["$.vct"], "filter": {"pattern": "urn:.*"}}])),
+            &wallet(),
+        ),
+        "vct filter",
+    );
+    assert_unsupported(
+        evaluate_presentation_definition(
+            &definition(json!([{"path": ["$.vct"], "filter": {"const": PID}}])),
+            &wallet(),
+        ),
+        "does not request age_over_18",
+    );
+}
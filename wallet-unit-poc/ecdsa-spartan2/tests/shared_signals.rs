@@ -0,0 +1,65 @@
+//! The shared commitment must be tied to the circom signals: changing one shared value without
+//! changing the circuit inputs leaves the constraint system unsatisfied.
+//!
+//! These run the compiled circuits under `../circom/build` with the default inputs.
+
+use bellpepper::util_cs::test_cs::TestConstraintSystem;
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem};
+use ecdsa_spartan2::{PrepareCircuit, Scalar, ShowCircuit, E};
+use ff::Field;
+use spartan2::traits::circuit::SpartanCircuit;
+
+/// Synthesize `circuit`, replacing shared value `tamper` (if any) by a different value
+fn synthesize_with_shared<C: SpartanCircuit<E>>(
+    circuit: &C,
+    tamper: Option<usize>,
+) -> TestConstraintSystem<Scalar> {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let mut shared = circuit
+        .shared(&mut cs.namespace(|| "shared"))
+        .expect("shared values");
+    if let Some(index) = tamper {
+        let value = shared[index].get_value().unwrap() + Scalar::ONE;
+        shared[index] = AllocatedNum::alloc(cs.namespace(|| "tampered"), || Ok(value)).unwrap();
+    }
+    circuit
+        .synthesize(&mut cs, &shared, &[], None)
+        .expect("synthesize");
+    cs
+}
+
+#[test]
+fn prepare_shared_values_match_circuit_outputs() {
+    let cs = synthesize_with_shared(&PrepareCircuit::default(), None);
+    assert!(cs.is_satisfied(), "{:?}", cs.which_is_unsatisfied());
+}
+
+#[test]
+fn prepare_rejects_mismatched_keybinding() {
+    let cs = synthesize_with_shared(&PrepareCircuit::default(), Some(0));
+    assert_eq!(cs.which_is_unsatisfied(), Some("shared_0_equals_signal"));
+}
+
+#[test]
+fn prepare_rejects_mismatched_claim() {
+    let cs = synthesize_with_shared(&PrepareCircuit::default(), Some(2));
+    assert_eq!(cs.which_is_unsatisfied(), Some("shared_2_equals_signal"));
+}
+
+#[test]
+fn show_shared_values_match_circuit_inputs() {
+    let cs = synthesize_with_shared(&ShowCircuit::default(), None);
+    assert!(cs.is_satisfied(), "{:?}", cs.which_is_unsatisfied());
+}
+
+#[test]
+fn show_rejects_mismatched_device_key() {
+    let cs = synthesize_with_shared(&ShowCircuit::default(), Some(1));
+    assert_eq!(cs.which_is_unsatisfied(), Some("shared_1_equals_signal"));
+}
+
+#[test]
+fn show_rejects_mismatched_claim() {
+    let cs = synthesize_with_shared(&ShowCircuit::default(), Some(5));
+    assert_eq!(cs.which_is_unsatisfied(), Some("shared_5_equals_signal"));
+}
@@ -0,0 +1,284 @@
+//! Negative tests for the prover and verifier: altered proofs, wrong keys, unlinked presentations
+//! and malformed prover inputs must all be rejected.
+//!
+//! These run the compiled circuits under `../circom/build` with the default inputs. Setup and the
+//! honest presentation are computed once and shared by every test (`common::circom`).
+
+mod common;
+
+use std::{fs, path::PathBuf, process};
+
+use common::circom::{
+    fixture, policy, presentation, signed_show_inputs, CHALLENGE, DATE, EIGHTEENTH_BIRTHDAY,
+};
+use ecdsa_spartan2::{
+    check_linkage, generate_proof, load_proving_key, load_verifying_key, prove_and_reblind,
+    prove_presentation, reblind_proof, sample_shared_blinds, PrepareCircuit, Presentation,
+    ProverError, ShowCircuit, VerifyError, E,
+};
+use ff::{derive::rand_core::OsRng, Field};
+use p256::ecdsa::SigningKey;
+use zkid_verifier::{verify, verify_presentation, Date, Predicate, Proof, Scalar};
+
+/// Path in the temp directory, unique to this test process
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("zkid-soundness-{}-{name}", process::id()))
+}
+
+#[test]
+fn honest_presentation_verifies() {
+    let fixture = fixture();
+    verify_presentation(
+        &presentation(&fixture.show_proof),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(),
+    )
+    .expect("honest presentation");
+}
+
+#[test]
+fn presentation_on_capped_threads_verifies_and_links() {
+    let fixture = fixture();
+    let proven =
+        generate_proof(PrepareCircuit::default(), &fixture.prepare_pk).expect("prepare prove");
+    let artifacts = prove_presentation(
+        &fixture.prepare_pk,
+        PrepareCircuit::default(),
+        proven.instance,
+        proven.witness,
+        &fixture.show_pk,
+        ShowCircuit::default(),
+        &fixture.blinds,
+        Some(1),
+    )
+    .expect("presentation on one thread");
+
+    let verified = verify_presentation(
+        &Presentation::new(artifacts.prepare.proof, artifacts.show_proof),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(),
+    )
+    .expect("concurrent presentation");
+
+    // Same credential and blinds as the sequentially built fixture, so the same shared commitment
+    let sequential =
+        check_linkage(&fixture.prepare.proof, &fixture.show_proof).expect("fixture linkage");
+    assert_eq!(verified.shared_commitment, sequential);
+}
+
+#[test]
+fn show_proof_is_bound_to_its_challenge() {
+    let fixture = fixture();
+    let show_proof = fixture.show_proof_for(b"another challenge");
+
+    // Linked to the same credential, but its public `messageHash` answers the other challenge
+    verify_presentation(
+        &presentation(&show_proof),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &Predicate::AgeOver18.policy(b"another challenge", DATE),
+    )
+    .expect("presentation for its own challenge");
+    assert!(matches!(
+        verify_presentation(
+            &presentation(&show_proof),
+            &fixture.prepare_vk,
+            &fixture.show_vk,
+            &policy(),
+        ),
+        Err(VerifyError::PublicValues { circuit: "Show" })
+    ));
+}
+
+#[test]
+fn underage_presentation_is_denied() {
+    let fixture = fixture();
+    let day_before = Date {
+        day: EIGHTEENTH_BIRTHDAY.day - 1,
+        ..EIGHTEENTH_BIRTHDAY
+    };
+    let show_proof = fixture.show_proof_on(CHALLENGE, day_before);
+
+    // The circuit proves the holder is not yet 18, dated as the relying party expects
+    let show_values = verify(&show_proof, &fixture.show_vk).expect("underage show proof");
+    assert_eq!(show_values[0], Scalar::ZERO);
+    assert!(matches!(
+        verify_presentation(
+            &presentation(&show_proof),
+            &fixture.prepare_vk,
+            &fixture.show_vk,
+            &Predicate::AgeOver18.policy(CHALLENGE, day_before),
+        ),
+        Err(VerifyError::PublicValues { circuit: "Show" })
+    ));
+
+    // On their birthday the same credential proves age over 18
+    let show_proof = fixture.show_proof_on(CHALLENGE, EIGHTEENTH_BIRTHDAY);
+    verify_presentation(
+        &presentation(&show_proof),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &Predicate::AgeOver18.policy(CHALLENGE, EIGHTEENTH_BIRTHDAY),
+    )
+    .expect("presentation on the 18th birthday");
+}
+
+#[test]
+fn presentation_dated_away_from_the_relying_party_is_denied() {
+    let fixture = fixture();
+
+    // An underage holder cannot date the proof in the future, when they are over 18
+    let show_proof = fixture.show_proof_on(CHALLENGE, EIGHTEENTH_BIRTHDAY);
+    for date in [
+        Date::new(2004, 6, 3).unwrap(),
+        Date::new(2004, 6, 4).unwrap(),
+        Date::new(2004, 6, 6).unwrap(),
+        Date::new(2004, 6, 7).unwrap(),
+    ] {
+        let result = verify_presentation(
+            &presentation(&show_proof),
+            &fixture.prepare_vk,
+            &fixture.show_vk,
+            &Predicate::AgeOver18.policy(CHALLENGE, date),
+        );
+        // Within a day of the relying party's date for wallets in other time zones
+        let within_a_day = date.days_since(EIGHTEENTH_BIRTHDAY).abs() <= 1;
+        assert_eq!(result.is_ok(), within_a_day, "verified on {date}");
+    }
+
+    // Nor present an old proof long after the date it was made on
+    let later = Date::new(2026, 1, 1).unwrap();
+    assert!(matches!(
+        verify_presentation(
+            &presentation(&fixture.show_proof),
+            &fixture.prepare_vk,
+            &fixture.show_vk,
+            &Predicate::AgeOver18.policy(CHALLENGE, later),
+        ),
+        Err(VerifyError::PublicValues { circuit: "Show" })
+    ));
+}
+
+#[test]
+fn tampered_proof_fails_verify() {
+    let fixture = fixture();
+    let bytes = bincode::serialize(&fixture.prepare.proof).unwrap();
+
+    // Flipping the low bit of a byte inside a field element keeps the proof decodable; bytes of
+    // points or lengths may not, so only the offsets that still decode are checked
+    let tampered: Vec<(usize, Proof)> = (0..bytes.len())
+        .rev()
+        .step_by(61)
+        .filter_map(|offset| {
+            let mut tampered = bytes.clone();
+            tampered[offset] ^= 1;
+            bincode::deserialize::<Proof>(&tampered)
+                .ok()
+                .map(|proof| (offset, proof))
+        })
+        .collect();
+    assert!(
+        tampered.len() >= 4,
+        "only {} tampered proofs decoded",
+        tampered.len()
+    );
+
+    for (offset, proof) in tampered {
+        assert!(
+            verify(&proof, &fixture.prepare_vk).is_err(),
+            "proof tampered at byte {offset} verified"
+        );
+    }
+}
+
+#[test]
+fn proof_fails_against_wrong_verifying_key() {
+    let fixture = fixture();
+    assert!(verify(&fixture.prepare.proof, &fixture.show_vk).is_err());
+    assert!(verify(&fixture.show_proof, &fixture.prepare_vk).is_err());
+
+    let swapped = verify_presentation(
+        &presentation(&fixture.show_proof),
+        &fixture.show_vk,
+        &fixture.prepare_vk,
+        &policy(),
+    );
+    assert!(matches!(swapped, Err(VerifyError::PrepareProof(_))));
+}
+
+#[test]
+fn show_proof_from_other_device_key_fails_linkage() {
+    let fixture = fixture();
+
+    // Sign the same challenge with a device key the credential was not issued to
+    let inputs = signed_show_inputs(&SigningKey::random(&mut OsRng), CHALLENGE);
+    let show_proof = prove_and_reblind(
+        ShowCircuit::from_json(inputs),
+        &fixture.show_pk,
+        &fixture.blinds,
+    )
+    .expect("show prove");
+
+    // The Show proof is valid on its own, but commits to another device key than Prepare
+    verify(&show_proof, &fixture.show_vk).expect("show proof");
+    assert!(matches!(
+        check_linkage(&fixture.prepare.proof, &show_proof),
+        Err(VerifyError::LinkageMismatch)
+    ));
+    assert!(matches!(
+        verify_presentation(
+            &presentation(&show_proof),
+            &fixture.prepare_vk,
+            &fixture.show_vk,
+            &policy(),
+        ),
+        Err(VerifyError::LinkageMismatch)
+    ));
+}
+
+#[test]
+fn reblind_rejects_wrong_number_of_blinds() {
+    let fixture = fixture();
+    let expected = fixture.blinds.len();
+
+    for actual in [0, expected + 1] {
+        let blinds = sample_shared_blinds::<E>(actual);
+        let proven =
+            generate_proof(PrepareCircuit::default(), &fixture.prepare_pk).expect("prepare prove");
+        let result = reblind_proof(
+            PrepareCircuit::default(),
+            &fixture.prepare_pk,
+            proven.instance,
+            proven.witness,
+            &blinds,
+        );
+        assert!(matches!(
+            result,
+            Err(ProverError::InvalidBlinds { expected: e, actual: a }) if e == expected && a == actual
+        ));
+
+        let result = prove_and_reblind(ShowCircuit::default(), &fixture.show_pk, &blinds);
+        assert!(matches!(result, Err(ProverError::InvalidBlinds { .. })));
+    }
+}
+
+#[test]
+fn truncated_key_files_fail_to_load() {
+    let fixture = fixture();
+
+    let pk_bytes = bincode::serialize(&fixture.show_pk).unwrap();
+    let pk_path = temp_path("show_proving.key");
+    fs::write(&pk_path, &pk_bytes[..pk_bytes.len() / 2]).unwrap();
+    let pk = load_proving_key(pk_path.to_str().unwrap());
+    fs::remove_file(&pk_path).unwrap();
+    assert!(pk.is_err(), "truncated proving key loaded");
+
+    let vk_bytes = bincode::serialize(&fixture.show_vk).unwrap();
+    let vk_path = temp_path("show_verifying.key");
+    fs::write(&vk_path, &vk_bytes[..vk_bytes.len() / 2]).unwrap();
+    let vk = load_verifying_key(vk_path.to_str().unwrap());
+    fs::remove_file(&vk_path).unwrap();
+    assert!(vk.is_err(), "truncated verifying key loaded");
+}
@@ -0,0 +1,435 @@
+//! UniFFI bindings for mobile wallets
+//!
+//! Exposes the wallet flow as a small, stable set of functions over byte buffers:
+//! - `setup_keys` — generate Prepare and Show proving/verifying keys
+//! - `prove_prepare` — prove the Prepare circuit once per credential, kept as an opaque
+//!   `PreparedCredential` that the app stores encrypted with `seal`
+//! - `present` — reblind the cached Prepare proof and prove Show for a verifier challenge
+//! - `verify` — verify a presentation for a challenge, as a relying party would
+//!
+//! Inputs are the circom input JSON documents (as UTF-8 bytes), keys and proofs are their bincode
+//! encodings. The `.r1cs` files are passed by path since apps ship them as bundled resources.
+//! Long-running calls accept an optional `ProgressListener` implemented by the app, which is told
+//! about each proving stage and can cancel the call between stages.
+//! Kotlin and Swift bindings are generated with the bundled `uniffi-bindgen` binary.
+
+use std::{fmt, sync::Arc};
+
+use ecdsa_spartan2::{
+    bigint_to_scalar, challenge_message_hash, generate_proof_with_observer,
+    prove_presentation_with_observer, sample_shared_blinds, try_setup_circuit_keys_with_observer,
+    CircuitParams, JwtCircuitParams, NoopObserver, PrepareCircuit, ProofObserver, ProofPhase,
+    ProverError, ProverKey, SecretWitness, ShowCircuit, ShowInputs, WipingAllocator, WitnessKey, E,
+};
+use serde_json::Value;
+use spartan2::r1cs::SplitR1CSInstance;
+use zeroize::Zeroizing;
+use zkid_verifier::{
+    limits::deserialize_with_limit, verify_presentation, verifying_key_from_bytes, Date,
+    DeserializeLimits, Predicate, Presentation,
+};
+
+uniffi::setup_scaffolding!();
+
+/// Wipes freed memory, so that the credential witnesses decoded for `present` and the copies
+/// Spartan2 makes while proving do not linger in the app's heap
+#[global_allocator]
+static ALLOCATOR: WipingAllocator = WipingAllocator;
+
+#[derive(Debug, uniffi::Error)]
+pub enum ZkidError {
+    /// A key, input document, credential or presentation could not be decoded
+    InvalidInput { reason: String },
+    /// Setup, proving or reblinding failed
+    Proving { reason: String },
+    /// The presentation was decoded but does not verify
+    Verification { reason: String },
+    /// The progress listener cancelled the operation
+    Cancelled,
+}
+
+impl fmt::Display for ZkidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkidError::InvalidInput { reason } => write!(f, "invalid input: {reason}"),
+            ZkidError::Proving { reason } => write!(f, "proving failed: {reason}"),
+            ZkidError::Verification { reason } => write!(f, "verification failed: {reason}"),
+            ZkidError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ZkidError {}
+
+fn invalid_input(reason: impl ToString) -> ZkidError {
+    ZkidError::InvalidInput {
+        reason: reason.to_string(),
+    }
+}
+
+fn proving(reason: impl ToString) -> ZkidError {
+    ZkidError::Proving {
+        reason: reason.to_string(),
+    }
+}
+
+fn prover_error(e: ProverError) -> ZkidError {
+    match e {
+        ProverError::Cancelled => ZkidError::Cancelled,
+        e => proving(e),
+    }
+}
+
+/// Mirror of [`ProofPhase`] for the foreign bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ProofStage {
+    Setup,
+    WitnessGeneration,
+    PrepProve,
+    WitnessCommit,
+    Sumcheck,
+    PcsOpen,
+    Reblind,
+}
+
+impl From<ProofPhase> for ProofStage {
+    fn from(phase: ProofPhase) -> Self {
+        match phase {
+            ProofPhase::Setup => ProofStage::Setup,
+            ProofPhase::WitnessGeneration => ProofStage::WitnessGeneration,
+            ProofPhase::PrepProve => ProofStage::PrepProve,
+            ProofPhase::WitnessCommit => ProofStage::WitnessCommit,
+            ProofPhase::Sumcheck => ProofStage::Sumcheck,
+            ProofPhase::PcsOpen => ProofStage::PcsOpen,
+            ProofPhase::Reblind => ProofStage::Reblind,
+        }
+    }
+}
+
+/// Implemented by the app to show progress and to cancel long-running calls
+#[uniffi::export(with_foreign)]
+pub trait ProgressListener: Send + Sync {
+    fn on_stage(&self, stage: ProofStage);
+
+    fn is_cancelled(&self) -> bool;
+}
+
+struct ListenerObserver(Arc<dyn ProgressListener>);
+
+impl ProofObserver for ListenerObserver {
+    fn on_phase_start(&self, phase: ProofPhase) {
+        self.0.on_stage(phase.into());
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled()
+    }
+}
+
+fn observer(listener: Option<Arc<dyn ProgressListener>>) -> Box<dyn ProofObserver> {
+    match listener {
+        Some(listener) => Box::new(ListenerObserver(listener)),
+        None => Box::new(NoopObserver),
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct CircuitKeys {
+    pub proving_key: Vec<u8>,
+    pub verifying_key: Vec<u8>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct WalletKeys {
+    pub prepare: CircuitKeys,
+    pub show: CircuitKeys,
+}
+
+/// Instantiation `JWT(maxMessageLength, maxB64PayloadLength, maxMatches, maxSubstringLength,
+/// maxClaimsLength)` the Prepare circuit was compiled with, as in `circuits.json`. The Show
+/// circuit is `Show(maxClaimsLength)`.
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct PrepareParams {
+    pub max_message_length: u32,
+    pub max_b64_payload_length: u32,
+    pub max_matches: u32,
+    pub max_substring_length: u32,
+    pub max_claims_length: u32,
+}
+
+impl From<PrepareParams> for JwtCircuitParams {
+    fn from(params: PrepareParams) -> Self {
+        Self {
+            max_message_length: params.max_message_length as usize,
+            max_b64_payload_length: params.max_b64_payload_length as usize,
+            max_matches: params.max_matches as usize,
+            max_substring_length: params.max_substring_length as usize,
+            max_claims_length: params.max_claims_length as usize,
+        }
+    }
+}
+
+/// The given instantiation, `JWT(1920, 1900, 4, 50, 128)` when `None`
+fn prepare_params(params: Option<PrepareParams>) -> JwtCircuitParams {
+    params.map(JwtCircuitParams::from).unwrap_or_default()
+}
+
+/// Prepare proof state cached by the wallet between presentations, with the instantiation it was
+/// proven with.
+///
+/// The witness holds the whole credential, so it never crosses the FFI boundary in plaintext: the
+/// app stores the credential with `seal` and restores it with `unseal`, under a 32-byte key kept
+/// in the platform keystore.
+#[derive(Debug, uniffi::Object)]
+pub struct PreparedCredential {
+    instance: Vec<u8>,
+    witness: SecretWitness,
+    params: JwtCircuitParams,
+}
+
+fn witness_key(key: Vec<u8>) -> Result<WitnessKey, ZkidError> {
+    WitnessKey::from_slice(&Zeroizing::new(key)).map_err(invalid_input)
+}
+
+#[uniffi::export]
+impl PreparedCredential {
+    /// Restore a credential sealed with `seal` under the same key
+    #[uniffi::constructor]
+    pub fn unseal(sealed: Vec<u8>, key: Vec<u8>) -> Result<Arc<Self>, ZkidError> {
+        let key = witness_key(key)?;
+        let (instance, witness, params): (Vec<u8>, Vec<u8>, CircuitParams) =
+            deserialize_with_limit(&sealed, "sealed credential", sealed.len() as u64)
+                .map_err(invalid_input)?;
+        let params = params.jwt().map_err(invalid_input)?;
+        let witness = SecretWitness::decrypt(&witness, &key).map_err(invalid_input)?;
+        Ok(Arc::new(Self {
+            instance,
+            witness,
+            params,
+        }))
+    }
+
+    /// Encrypt the credential for storage (ChaCha20-Poly1305 over the witness)
+    pub fn seal(&self, key: Vec<u8>) -> Result<Vec<u8>, ZkidError> {
+        let key = witness_key(key)?;
+        let witness = self.witness.encrypt(&key).map_err(proving)?;
+        let params = CircuitParams::from(self.params);
+        bincode::serialize(&(&self.instance, witness, params)).map_err(proving)
+    }
+}
+
+/// Largest encodings `verify` decodes, in bytes.
+///
+/// Relying parties set them from the instantiation their verifying keys were set up for (see
+/// `JwtCircuitParams::deserialize_limits`); without them `verify` accepts every measured Prepare
+/// variant.
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct DecodeLimits {
+    /// Per proof; a presentation may be twice as large
+    pub proof: u64,
+    /// Per verifying key
+    pub verifying_key: u64,
+}
+
+impl From<DecodeLimits> for DeserializeLimits {
+    fn from(limits: DecodeLimits) -> Self {
+        Self {
+            proof: limits.proof,
+            verifying_key: limits.verifying_key,
+            ..Default::default()
+        }
+    }
+}
+
+/// A day of the Gregorian calendar
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct CalendarDate {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct VerificationOutcome {
+    /// Serialized `comm_W_shared` common to the Prepare and Show proofs
+    pub shared_commitment: Vec<u8>,
+}
+
+fn parse_json(bytes: &[u8]) -> Result<Value, ZkidError> {
+    serde_json::from_slice(bytes).map_err(invalid_input)
+}
+
+fn proving_key_from_bytes(bytes: &[u8]) -> Result<ProverKey, ZkidError> {
+    bincode::deserialize(bytes).map_err(invalid_input)
+}
+
+/// Generate proving and verifying keys for both circuits, compiled with `params`
+/// (`JWT(1920, 1900, 4, 50, 128)` and `Show(128)` when `None`)
+#[uniffi::export]
+pub fn setup_keys(
+    prepare_r1cs_path: String,
+    show_r1cs_path: String,
+    prepare_inputs: Vec<u8>,
+    show_inputs: Vec<u8>,
+    params: Option<PrepareParams>,
+    listener: Option<Arc<dyn ProgressListener>>,
+) -> Result<WalletKeys, ZkidError> {
+    let observer = observer(listener);
+    let params = prepare_params(params);
+    let prepare_circuit = PrepareCircuit::from_json(parse_json(&prepare_inputs)?)
+        .with_params(params)
+        .with_r1cs_path(prepare_r1cs_path);
+    let show_circuit = ShowCircuit::from_json(parse_json(&show_inputs)?)
+        .with_params(params.show_params())
+        .with_r1cs_path(show_r1cs_path);
+
+    let (prepare_pk, prepare_vk) =
+        try_setup_circuit_keys_with_observer(prepare_circuit, observer.as_ref())
+            .map_err(prover_error)?;
+    let (show_pk, show_vk) = try_setup_circuit_keys_with_observer(show_circuit, observer.as_ref())
+        .map_err(prover_error)?;
+
+    Ok(WalletKeys {
+        prepare: CircuitKeys {
+            proving_key: bincode::serialize(&prepare_pk).map_err(proving)?,
+            verifying_key: bincode::serialize(&prepare_vk).map_err(proving)?,
+        },
+        show: CircuitKeys {
+            proving_key: bincode::serialize(&show_pk).map_err(proving)?,
+            verifying_key: bincode::serialize(&show_vk).map_err(proving)?,
+        },
+    })
+}
+
+/// Prove the Prepare circuit for a credential; the result is reused by every `present` call.
+///
+/// `params` is the instantiation `prepare_r1cs_path` was compiled with (`JWT(1920, 1900, 4, 50,
+/// 128)` when `None`); the credential remembers it for `present`.
+#[uniffi::export]
+pub fn prove_prepare(
+    prepare_proving_key: Vec<u8>,
+    prepare_r1cs_path: String,
+    prepare_inputs: Vec<u8>,
+    params: Option<PrepareParams>,
+    listener: Option<Arc<dyn ProgressListener>>,
+) -> Result<Arc<PreparedCredential>, ZkidError> {
+    let observer = observer(listener);
+    let params = prepare_params(params);
+    let pk = proving_key_from_bytes(&prepare_proving_key)?;
+    let circuit = PrepareCircuit::from_json(parse_json(&prepare_inputs)?)
+        .with_params(params)
+        .with_r1cs_path(prepare_r1cs_path);
+
+    let artifacts =
+        generate_proof_with_observer(circuit, &pk, observer.as_ref()).map_err(prover_error)?;
+
+    Ok(Arc::new(PreparedCredential {
+        instance: bincode::serialize(&artifacts.instance).map_err(proving)?,
+        witness: SecretWitness::from_witness(&artifacts.witness).map_err(proving)?,
+        params,
+    }))
+}
+
+/// Build a presentation answering a verifier `challenge`.
+///
+/// `show_inputs` must carry the device signature over `challenge`; its `messageHash` is checked
+/// against `sha256(challenge)` so a presentation cannot be produced for another challenge.
+/// Fresh shared blinds are sampled for every call, so two presentations are unlinkable.
+/// Both circuits use the instantiation the credential was proven with.
+/// The Prepare reblind and the Show proof run concurrently on at most `max_threads` threads
+/// (all cores when `None`).
+#[uniffi::export]
+pub fn present(
+    prepare_proving_key: Vec<u8>,
+    show_proving_key: Vec<u8>,
+    show_r1cs_path: String,
+    credential: Arc<PreparedCredential>,
+    show_inputs: Vec<u8>,
+    challenge: Vec<u8>,
+    max_threads: Option<u32>,
+    listener: Option<Arc<dyn ProgressListener>>,
+) -> Result<Vec<u8>, ZkidError> {
+    let observer = observer(listener);
+    let show_json = parse_json(&show_inputs)?;
+    let show_params = credential.params.show_params();
+    let show_fields = ShowInputs::from_json(&show_json, &show_params).map_err(invalid_input)?;
+    let message_hash = bigint_to_scalar(show_fields.message_hash).ok();
+    if message_hash != Some(challenge_message_hash(&challenge)) {
+        return Err(invalid_input(
+            "Show inputs were not signed over the given challenge",
+        ));
+    }
+
+    let prepare_pk = proving_key_from_bytes(&prepare_proving_key)?;
+    let show_pk = proving_key_from_bytes(&show_proving_key)?;
+    let instance: SplitR1CSInstance<E> = deserialize_with_limit(
+        &credential.instance,
+        "instance",
+        credential.params.deserialize_limits().instance,
+    )
+    .map_err(invalid_input)?;
+    let witness = credential.witness.to_witness().map_err(invalid_input)?;
+
+    let shared_blinds = sample_shared_blinds::<E>(instance.num_shared_rows());
+
+    let show_circuit = ShowCircuit::from_json(show_json)
+        .with_params(show_params)
+        .with_r1cs_path(show_r1cs_path);
+    let presentation = prove_presentation_with_observer(
+        &prepare_pk,
+        PrepareCircuit::default().with_params(credential.params),
+        instance,
+        witness,
+        &show_pk,
+        show_circuit,
+        &shared_blinds,
+        max_threads.map(|n| n as usize),
+        observer.as_ref(),
+    )
+    .map_err(prover_error)?;
+
+    Presentation::new(presentation.prepare.proof, presentation.show_proof)
+        .to_bytes()
+        .map_err(proving)
+}
+
+/// Verify a presentation answering `challenge` against the Prepare and Show verifying keys: the
+/// Show proof must prove age over 18 on `date`, the relying party's date, within
+/// `zkid_verifier::DATE_TOLERANCE_DAYS`, and be signed over `challenge`
+#[uniffi::export]
+pub fn verify(
+    presentation: Vec<u8>,
+    prepare_verifying_key: Vec<u8>,
+    show_verifying_key: Vec<u8>,
+    challenge: Vec<u8>,
+    date: CalendarDate,
+    limits: Option<DecodeLimits>,
+) -> Result<VerificationOutcome, ZkidError> {
+    let date = Date::new(date.year, date.month, date.day)
+        .ok_or_else(|| invalid_input(format!("{date:?} is not a date")))?;
+    let limits = limits.map(DeserializeLimits::from).unwrap_or_default();
+    let presentation = Presentation::from_bytes(&presentation, &limits).map_err(invalid_input)?;
+    let prepare_vk =
+        verifying_key_from_bytes(&prepare_verifying_key, &limits).map_err(invalid_input)?;
+    let show_vk = verifying_key_from_bytes(&show_verifying_key, &limits).map_err(invalid_input)?;
+
+    let verified = verify_presentation(
+        &presentation,
+        &prepare_vk,
+        &show_vk,
+        &Predicate::AgeOver18.policy(&challenge, date),
+    )
+    .map_err(|e| ZkidError::Verification {
+        reason: e.to_string(),
+    })?;
+
+    Ok(VerificationOutcome {
+        shared_commitment: verified.shared_commitment,
+    })
+}
+
+/// Identifier of a verifying key (hex SHA-256 of its bytes)
+#[uniffi::export]
+pub fn vk_id(verifying_key: Vec<u8>) -> String {
+    zkid_verifier::vk_id(&verifying_key)
+}
@@ -0,0 +1,106 @@
+import java.io.File
+import uniffi.zkid_ffi.*
+
+// Date of the default Show inputs, on which the relying party verifies
+val presentedOn = CalendarDate(2025u, 1u, 1u)
+
+// vkId is the hex SHA-256 of the verifying key bytes
+check(vkId(byteArrayOf()) == "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+
+// Garbage presentations are rejected with a typed error rather than a crash
+try {
+    verify(byteArrayOf(1, 2, 3), byteArrayOf(), byteArrayOf(), byteArrayOf(), presentedOn, null)
+    throw RuntimeException("garbage presentation should not verify")
+} catch (e: ZkidException.InvalidInput) {
+    // expected
+}
+
+// A sealed credential that cannot be decoded is rejected before any proving work
+try {
+    PreparedCredential.unseal(byteArrayOf(1, 2, 3), ByteArray(32))
+    throw RuntimeException("garbage credential should not unseal")
+} catch (e: ZkidException.InvalidInput) {
+    // expected
+}
+
+// Full wallet flow over the compiled circuits and the default inputs, whose Show inputs are
+// signed over "zkid-default-challenge"
+val prepareR1cs = "../circom/build/jwt/jwt_js/jwt.r1cs"
+val showR1cs = "../circom/build/show/show_js/show.r1cs"
+val prepareInputs = File("../circom/inputs/jwt/default.json").readBytes()
+val showInputs = File("../circom/inputs/show/default.json").readBytes()
+val challenge = "zkid-default-challenge".toByteArray()
+
+class RecordingListener : ProgressListener {
+    val stages = mutableListOf<ProofStage>()
+
+    override fun onStage(stage: ProofStage) {
+        stages.add(stage)
+    }
+
+    override fun isCancelled() = false
+}
+
+val keys = setupKeys(prepareR1cs, showR1cs, prepareInputs, showInputs, null, null)
+val listener = RecordingListener()
+val credential = provePrepare(keys.prepare.provingKey, prepareR1cs, prepareInputs, null, listener)
+check(listener.stages.first() == ProofStage.WITNESS_GENERATION)
+check(listener.stages.contains(ProofStage.WITNESS_COMMIT))
+check(listener.stages.last() == ProofStage.PCS_OPEN)
+
+// The credential is stored encrypted and only opens under its key
+val storageKey = ByteArray(32) { it.toByte() }
+val sealed = credential.seal(storageKey)
+try {
+    PreparedCredential.unseal(sealed, ByteArray(32))
+    throw RuntimeException("credential unsealed under the wrong key")
+} catch (e: ZkidException.InvalidInput) {
+    // expected
+}
+val restored = PreparedCredential.unseal(sealed, storageKey)
+
+val presentation = present(
+    keys.prepare.provingKey,
+    keys.show.provingKey,
+    showR1cs,
+    restored,
+    showInputs,
+    challenge,
+    null,
+    null,
+)
+val outcome = verify(presentation, keys.prepare.verifyingKey, keys.show.verifyingKey, challenge, presentedOn, null)
+check(outcome.sharedCommitment.isNotEmpty())
+
+// The presentation proves age over 18 on its own date, not on a relying party's much later one
+try {
+    verify(presentation, keys.prepare.verifyingKey, keys.show.verifyingKey, challenge, CalendarDate(2027u, 1u, 1u), null)
+    throw RuntimeException("presentation verified on another date")
+} catch (e: ZkidException.Verification) {
+    // expected
+}
+
+// The presentation is bound to its challenge and does not verify for another one
+try {
+    verify(presentation, keys.prepare.verifyingKey, keys.show.verifyingKey, "another challenge".toByteArray(), presentedOn, null)
+    throw RuntimeException("presentation verified for another challenge")
+} catch (e: ZkidException.Verification) {
+    // expected
+}
+
+// Show inputs signed over another challenge are refused
+try {
+    present(
+        keys.prepare.provingKey,
+        keys.show.provingKey,
+        showR1cs,
+        restored,
+        showInputs,
+        "another challenge".toByteArray(),
+        null,
+        null,
+    )
+    throw RuntimeException("presented for a challenge the device did not sign")
+} catch (e: ZkidException.InvalidInput) {
+    // expected
+}
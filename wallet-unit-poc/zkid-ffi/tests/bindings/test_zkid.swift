@@ -0,0 +1,142 @@
+import Foundation
+import zkid_ffi
+
+// Date of the default Show inputs, on which the relying party verifies
+let presentedOn = CalendarDate(year: 2025, month: 1, day: 1)
+
+// vkId is the hex SHA-256 of the verifying key bytes
+precondition(vkId(verifyingKey: Data()) == "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+
+// Garbage presentations are rejected with a typed error rather than a crash
+do {
+    _ = try verify(presentation: Data([1, 2, 3]), prepareVerifyingKey: Data(), showVerifyingKey: Data(), challenge: Data(), date: presentedOn, limits: nil)
+    fatalError("garbage presentation should not verify")
+} catch ZkidError.InvalidInput {
+    // expected
+}
+
+// A sealed credential that cannot be decoded is rejected before any proving work
+do {
+    _ = try PreparedCredential.unseal(sealed: Data([1, 2, 3]), key: Data(count: 32))
+    fatalError("garbage credential should not unseal")
+} catch ZkidError.InvalidInput {
+    // expected
+}
+
+// Full wallet flow over the compiled circuits and the default inputs, whose Show inputs are
+// signed over "zkid-default-challenge"
+let prepareR1cs = "../circom/build/jwt/jwt_js/jwt.r1cs"
+let showR1cs = "../circom/build/show/show_js/show.r1cs"
+let prepareInputs = try Data(contentsOf: URL(fileURLWithPath: "../circom/inputs/jwt/default.json"))
+let showInputs = try Data(contentsOf: URL(fileURLWithPath: "../circom/inputs/show/default.json"))
+let challenge = "zkid-default-challenge".data(using: .utf8)!
+
+final class RecordingListener: ProgressListener {
+    var stages: [ProofStage] = []
+
+    func onStage(stage: ProofStage) {
+        stages.append(stage)
+    }
+
+    func isCancelled() -> Bool {
+        false
+    }
+}
+
+let keys = try setupKeys(
+    prepareR1csPath: prepareR1cs,
+    showR1csPath: showR1cs,
+    prepareInputs: prepareInputs,
+    showInputs: showInputs,
+    params: nil,
+    listener: nil
+)
+let listener = RecordingListener()
+let credential = try provePrepare(
+    prepareProvingKey: keys.prepare.provingKey,
+    prepareR1csPath: prepareR1cs,
+    prepareInputs: prepareInputs,
+    params: nil,
+    listener: listener
+)
+precondition(listener.stages.first == .witnessGeneration)
+precondition(listener.stages.contains(.witnessCommit))
+precondition(listener.stages.last == .pcsOpen)
+
+// The credential is stored encrypted and only opens under its key
+let storageKey = Data((0..<32).map { UInt8($0) })
+let sealed = try credential.seal(key: storageKey)
+do {
+    _ = try PreparedCredential.unseal(sealed: sealed, key: Data(count: 32))
+    fatalError("credential unsealed under the wrong key")
+} catch ZkidError.InvalidInput {
+    // expected
+}
+let restored = try PreparedCredential.unseal(sealed: sealed, key: storageKey)
+
+let presentation = try present(
+    prepareProvingKey: keys.prepare.provingKey,
+    showProvingKey: keys.show.provingKey,
+    showR1csPath: showR1cs,
+    credential: restored,
+    showInputs: showInputs,
+    challenge: challenge,
+    maxThreads: nil,
+    listener: nil
+)
+let outcome = try verify(
+    presentation: presentation,
+    prepareVerifyingKey: keys.prepare.verifyingKey,
+    showVerifyingKey: keys.show.verifyingKey,
+    challenge: challenge,
+    date: presentedOn,
+    limits: nil
+)
+precondition(!outcome.sharedCommitment.isEmpty)
+
+// The presentation proves age over 18 on its own date, not on a relying party's much later one
+do {
+    _ = try verify(
+        presentation: presentation,
+        prepareVerifyingKey: keys.prepare.verifyingKey,
+        showVerifyingKey: keys.show.verifyingKey,
+        challenge: challenge,
+        date: CalendarDate(year: 2027, month: 1, day: 1),
+        limits: nil
+    )
+    fatalError("presentation verified on another date")
+} catch ZkidError.Verification {
+    // expected
+}
+
+// The presentation is bound to its challenge and does not verify for another one
+do {
+    _ = try verify(
+        presentation: presentation,
+        prepareVerifyingKey: keys.prepare.verifyingKey,
+        showVerifyingKey: keys.show.verifyingKey,
+        challenge: "another challenge".data(using: .utf8)!,
+        date: presentedOn,
+        limits: nil
+    )
+    fatalError("presentation verified for another challenge")
+} catch ZkidError.Verification {
+    // expected
+}
+
+// Show inputs signed over another challenge are refused
+do {
+    _ = try present(
+        prepareProvingKey: keys.prepare.provingKey,
+        showProvingKey: keys.show.provingKey,
+        showR1csPath: showR1cs,
+        credential: restored,
+        showInputs: showInputs,
+        challenge: "another challenge".data(using: .utf8)!,
+        maxThreads: nil,
+        listener: nil
+    )
+    fatalError("presented for a challenge the device did not sign")
+} catch ZkidError.InvalidInput {
+    // expected
+}
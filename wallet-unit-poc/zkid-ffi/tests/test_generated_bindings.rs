@@ -0,0 +1,7 @@
+// Generates the Kotlin and Swift bindings for the host target and runs the scripts against them.
+// Requires `kotlinc` (with JNA on the classpath) and `swiftc` to be installed, and the circuits
+// compiled under `../circom/build`: the scripts run the full wallet flow on the default inputs.
+uniffi::build_foreign_language_testcases!(
+    "tests/bindings/test_zkid.kts",
+    "tests/bindings/test_zkid.swift",
+);
@@ -0,0 +1,40 @@
+//! Local OpenID4VP relying party for trying presentations end to end.
+//!
+//!   cargo run --example relying_party --features http -- \
+//!     ../ecdsa-spartan2/keys/prepare_verifying.key ../ecdsa-spartan2/keys/show_verifying.key
+//!
+//! Serves `GET /authorize` and `POST /response` on 127.0.0.1:8080, or on the address given as
+//! third argument.
+
+use std::{env, error::Error, fs, sync::Arc};
+
+use tokio::net::TcpListener;
+use zkid_verifier::{http, DeserializeLimits, RelyingParty};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [prepare_vk, show_vk, rest @ ..] = args.as_slice() else {
+        return Err("usage: relying_party <prepare vk> <show vk> [address]".into());
+    };
+    let address = rest.first().map_or(DEFAULT_ADDRESS, String::as_str);
+
+    let relying_party = RelyingParty::new(
+        format!("http://{address}"),
+        format!("http://{address}{}", http::RESPONSE_PATH),
+        &fs::read(prepare_vk)?,
+        &fs::read(show_vk)?,
+        DeserializeLimits::default(),
+    )?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()?;
+    runtime.block_on(async {
+        let listener = TcpListener::bind(address).await?;
+        println!("Relying party listening on http://{address}");
+        http::serve(listener, Arc::new(relying_party)).await
+    })?;
+    Ok(())
+}
@@ -0,0 +1,1245 @@
+//! Canonical, documented byte encoding of proofs and instances (see `ENCODING.md`).
+//!
+//! bincode output depends on its configuration (integer encoding, length width, trailing bytes).
+//! This encoding fixes every choice so that a verifier in another language can parse proofs from
+//! the spec alone: fixed-width little-endian integers, `u32` element counts, tagged options and
+//! enums, no field names, and a header naming the artifact and the format version. Decoding is
+//! strict: every byte string has exactly one valid decoding, and trailing bytes are rejected.
+//!
+//! Points sit at the positions [`PROOF_LAYOUT`] and [`INSTANCE_LAYOUT`] give, and are written
+//! in the form the artifact header names rather than in the serde form of the curve library:
+//! uncompressed affine coordinates by default, or compressed ([`PointEncoding::Compressed`]),
+//! half the size at the cost of a square root per point when decoding. Encoding and decoding
+//! check every struct against its layout, so a Spartan2 update that moves a field fails loudly.
+
+use std::fmt;
+
+use bincode::Options;
+use group::{prime::PrimeCurveAffine, Curve, GroupEncoding, UncompressedEncoding};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor},
+    ser::{self, Serialize},
+};
+use spartan2::{r1cs::SplitR1CSInstance, traits::Engine};
+
+use crate::{Proof, E};
+
+type Point = <E as Engine>::GE;
+type Affine = <Point as Curve>::AffineRepr;
+
+/// First bytes of every canonically encoded artifact
+pub const MAGIC: [u8; 4] = *b"ZKID";
+
+/// Version of the encoding rules in `ENCODING.md`
+pub const VERSION: u8 = 1;
+
+/// Artifact encoded after the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ArtifactKind {
+    Proof = 1,
+    Instance = 2,
+}
+
+/// How group elements are written in the body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PointEncoding {
+    /// `UncompressedEncoding::to_uncompressed`: the affine x and y coordinates
+    #[default]
+    Uncompressed,
+    /// `GroupEncoding::to_bytes`: a flag byte and the x coordinate
+    Compressed,
+}
+
+impl PointEncoding {
+    /// Encoded size of a point
+    fn len(self) -> usize {
+        match self {
+            PointEncoding::Uncompressed => {
+                <Affine as UncompressedEncoding>::Uncompressed::default()
+                    .as_ref()
+                    .len()
+            }
+            PointEncoding::Compressed => <Point as GroupEncoding>::Repr::default().as_ref().len(),
+        }
+    }
+
+    fn write(self, point: &Point, out: &mut Vec<u8>) {
+        match self {
+            PointEncoding::Uncompressed => {
+                out.extend_from_slice(point.to_affine().to_uncompressed().as_ref())
+            }
+            PointEncoding::Compressed => out.extend_from_slice(point.to_bytes().as_ref()),
+        }
+    }
+
+    /// Decode a point, rejecting bytes that are not its canonical encoding
+    fn read(self, bytes: &[u8]) -> Result<Point, CanonicalError> {
+        let point: Option<Point> = match self {
+            PointEncoding::Uncompressed => {
+                let mut repr = <Affine as UncompressedEncoding>::Uncompressed::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Affine::from_uncompressed(&repr)
+                    .map(|affine| affine.to_curve())
+                    .into()
+            }
+            PointEncoding::Compressed => {
+                let mut repr = <Point as GroupEncoding>::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                Point::from_bytes(&repr).into()
+            }
+        };
+        let point = point.ok_or(CanonicalError::InvalidPoint)?;
+        let mut encoded = Vec::with_capacity(bytes.len());
+        self.write(&point, &mut encoded);
+        if encoded != bytes {
+            return Err(CanonicalError::InvalidPoint);
+        }
+        Ok(point)
+    }
+}
+
+/// Bit of the artifact kind byte set when points are compressed
+pub(crate) const COMPRESSED_POINTS: u8 = 0x80;
+
+/// Where the points of a value are: the field-by-field tables of `ENCODING.md`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// A value without points, encoded with the rules alone: integers, scalars and containers of
+    /// them
+    Plain,
+    /// A T-256 point, as a struct field or a sequence element
+    Point,
+    /// A struct named as Spartan2 names it, with its fields in declaration order
+    Struct(&'static str, &'static [(&'static str, Layout)]),
+    /// A `Vec` of elements laid out alike
+    Seq(&'static Layout),
+    Option(&'static Layout),
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layout::Plain => write!(f, "a value without points"),
+            Layout::Point => write!(f, "a point"),
+            Layout::Struct(name, fields) => write!(f, "struct {name} of {} fields", fields.len()),
+            Layout::Seq(_) => write!(f, "a sequence"),
+            Layout::Option(_) => write!(f, "an option"),
+        }
+    }
+}
+
+/// Hyrax commitment: one point per row
+const COMMITMENT: Layout =
+    Layout::Struct("HyraxCommitment", &[("comm", Layout::Seq(&Layout::Point))]);
+
+/// Layout of `SplitR1CSInstance<T256HyraxEngine>`
+pub const INSTANCE_LAYOUT: Layout = Layout::Struct(
+    "SplitR1CSInstance",
+    &[
+        ("comm_W_shared", Layout::Option(&COMMITMENT)),
+        ("comm_W_precommitted", Layout::Option(&COMMITMENT)),
+        ("comm_W_rest", COMMITMENT),
+        ("public_values", Layout::Plain),
+        ("challenges", Layout::Plain),
+    ],
+);
+
+/// Layout of `R1CSSNARK<T256HyraxEngine>`
+pub const PROOF_LAYOUT: Layout = Layout::Struct(
+    "R1CSSNARK",
+    &[
+        ("U", INSTANCE_LAYOUT),
+        (
+            "U_verifier",
+            Layout::Struct(
+                "SplitMultiRoundR1CSInstance",
+                &[
+                    ("comm_w_per_round", Layout::Seq(&COMMITMENT)),
+                    ("public_values", Layout::Plain),
+                    ("challenges_per_round", Layout::Plain),
+                ],
+            ),
+        ),
+        (
+            "random_U",
+            Layout::Struct(
+                "RelaxedR1CSInstance",
+                &[
+                    ("comm_W", COMMITMENT),
+                    ("comm_E", COMMITMENT),
+                    ("X", Layout::Plain),
+                    ("u", Layout::Plain),
+                ],
+            ),
+        ),
+        (
+            "nifs",
+            Layout::Struct("NovaNIFS", &[("comm_T", COMMITMENT)]),
+        ),
+        ("relaxed_snark", Layout::Plain),
+        (
+            "eval_arg",
+            Layout::Struct(
+                "HyraxEvaluationArgument",
+                &[(
+                    "ipa",
+                    Layout::Struct(
+                        "InnerProductArgumentLinear",
+                        &[
+                            ("delta", Layout::Point),
+                            ("beta", Layout::Point),
+                            ("z_vec", Layout::Plain),
+                            ("z_delta", Layout::Plain),
+                            ("z_beta", Layout::Plain),
+                        ],
+                    ),
+                )],
+            ),
+        ),
+    ],
+);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalError {
+    /// The input ended inside a value
+    UnexpectedEnd,
+    /// Bytes remain after the top-level value
+    TrailingBytes(usize),
+    /// Missing magic, unknown version or another artifact kind
+    Header(&'static str),
+    /// A bool or option tag other than 0 or 1
+    InvalidTag(u8),
+    /// An element count larger than the remaining input
+    InvalidLength(u32),
+    /// A length that does not fit the `u32` count
+    LengthOverflow(usize),
+    InvalidUtf8,
+    InvalidChar(u32),
+    /// Bytes that are not the canonical encoding of a point
+    InvalidPoint,
+    /// A value that does not have the layout expected at its position
+    Layout(String),
+    /// A serde feature outside the encoding (floats, unsized sequences, self-describing input)
+    Unsupported(&'static str),
+    Custom(String),
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanonicalError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            CanonicalError::TrailingBytes(n) => write!(f, "{n} trailing bytes after the value"),
+            CanonicalError::Header(reason) => write!(f, "invalid header: {reason}"),
+            CanonicalError::InvalidTag(tag) => write!(f, "invalid tag {tag:#04x}, expected 0 or 1"),
+            CanonicalError::InvalidLength(len) => {
+                write!(f, "count {len} exceeds the remaining input")
+            }
+            CanonicalError::LengthOverflow(len) => {
+                write!(f, "length {len} does not fit in a u32 count")
+            }
+            CanonicalError::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            CanonicalError::InvalidChar(c) => write!(f, "{c:#x} is not a Unicode scalar value"),
+            CanonicalError::InvalidPoint => write!(f, "invalid point encoding"),
+            CanonicalError::Layout(reason) => write!(f, "unexpected layout: {reason}"),
+            CanonicalError::Unsupported(what) => write!(f, "{what} is not part of the encoding"),
+            CanonicalError::Custom(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+impl ser::Error for CanonicalError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CanonicalError::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for CanonicalError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CanonicalError::Custom(msg.to_string())
+    }
+}
+
+/// Encode a proof with its header
+pub fn encode_proof(proof: &Proof) -> Result<Vec<u8>, CanonicalError> {
+    encode_artifact(ArtifactKind::Proof, proof, PointEncoding::Uncompressed)
+}
+
+/// Encode a proof with its header, writing points as `points` says
+pub fn encode_proof_with(proof: &Proof, points: PointEncoding) -> Result<Vec<u8>, CanonicalError> {
+    encode_artifact(ArtifactKind::Proof, proof, points)
+}
+
+/// Decode a proof in either point encoding, rejecting any other artifact or version
+pub fn decode_proof(bytes: &[u8]) -> Result<Proof, CanonicalError> {
+    decode_artifact(ArtifactKind::Proof, bytes)
+}
+
+/// Encode a proven instance, whose commitments include `comm_W_shared`, with its header
+pub fn encode_instance(instance: &SplitR1CSInstance<E>) -> Result<Vec<u8>, CanonicalError> {
+    encode_artifact(
+        ArtifactKind::Instance,
+        instance,
+        PointEncoding::Uncompressed,
+    )
+}
+
+pub fn encode_instance_with(
+    instance: &SplitR1CSInstance<E>,
+    points: PointEncoding,
+) -> Result<Vec<u8>, CanonicalError> {
+    encode_artifact(ArtifactKind::Instance, instance, points)
+}
+
+pub fn decode_instance(bytes: &[u8]) -> Result<SplitR1CSInstance<E>, CanonicalError> {
+    decode_artifact(ArtifactKind::Instance, bytes)
+}
+
+impl ArtifactKind {
+    fn layout(self) -> Layout {
+        match self {
+            ArtifactKind::Proof => PROOF_LAYOUT,
+            ArtifactKind::Instance => INSTANCE_LAYOUT,
+        }
+    }
+}
+
+fn encode_artifact<T: Serialize>(
+    kind: ArtifactKind,
+    value: &T,
+    points: PointEncoding,
+) -> Result<Vec<u8>, CanonicalError> {
+    let mut out = Vec::from(MAGIC);
+    out.push(VERSION);
+    out.push(match points {
+        PointEncoding::Uncompressed => kind as u8,
+        PointEncoding::Compressed => kind as u8 | COMPRESSED_POINTS,
+    });
+    let body = to_bytes_with(value, kind.layout(), points)?;
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+fn decode_artifact<T: DeserializeOwned>(
+    kind: ArtifactKind,
+    bytes: &[u8],
+) -> Result<T, CanonicalError> {
+    let body = bytes
+        .strip_prefix(&MAGIC)
+        .ok_or(CanonicalError::Header("missing ZKID magic"))?;
+    match body {
+        [VERSION, k, rest @ ..] if *k == kind as u8 => {
+            from_bytes_with(rest, kind.layout(), PointEncoding::Uncompressed)
+        }
+        [VERSION, k, rest @ ..] if *k == kind as u8 | COMPRESSED_POINTS => {
+            from_bytes_with(rest, kind.layout(), PointEncoding::Compressed)
+        }
+        [VERSION, _, ..] => Err(CanonicalError::Header("unexpected artifact kind")),
+        [_, _, ..] => Err(CanonicalError::Header("unsupported version")),
+        _ => Err(CanonicalError::UnexpectedEnd),
+    }
+}
+
+/// Encode a value without points with the canonical rules, without a header
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CanonicalError> {
+    to_bytes_with(value, Layout::Plain, PointEncoding::Uncompressed)
+}
+
+/// Encode a value laid out as `layout`, writing its points as `points` says, without a header
+pub fn to_bytes_with<T: Serialize + ?Sized>(
+    value: &T,
+    layout: Layout,
+    points: PointEncoding,
+) -> Result<Vec<u8>, CanonicalError> {
+    let mut out = Vec::new();
+    Encoder::new(&mut out, points).value(layout, value)?;
+    Ok(out)
+}
+
+/// Encoded size of each field of a struct laid out as `layout`, in declaration order, e.g. the
+/// components of a proof with uncompressed points
+pub fn field_sizes<T: Serialize + ?Sized>(
+    value: &T,
+    layout: Layout,
+) -> Result<Vec<(&'static str, usize)>, CanonicalError> {
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out, PointEncoding::Uncompressed);
+    encoder.fields = Some(Vec::new());
+    encoder.value(layout, value)?;
+    Ok(encoder.fields.unwrap_or_default())
+}
+
+/// Decode a value without points encoded with the canonical rules, without a header
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CanonicalError> {
+    from_bytes_with(bytes, Layout::Plain, PointEncoding::Uncompressed)
+}
+
+/// Decode a value laid out as `layout` whose points are written as `points` says, without a
+/// header
+pub fn from_bytes_with<T: DeserializeOwned>(
+    bytes: &[u8],
+    layout: Layout,
+    points: PointEncoding,
+) -> Result<T, CanonicalError> {
+    let mut decoder = Decoder {
+        input: bytes,
+        points,
+        expect: layout,
+    };
+    let value = decoder.value(layout, std::marker::PhantomData::<T>)?;
+    match decoder.input.len() {
+        0 => Ok(value),
+        n => Err(CanonicalError::TrailingBytes(n)),
+    }
+}
+
+/// bincode options a decoded point is handed to its `Deserialize` implementation with
+fn point_options() -> impl Options {
+    bincode::DefaultOptions::new().with_fixint_encoding()
+}
+
+fn mismatch(expected: Layout, found: impl fmt::Display) -> CanonicalError {
+    CanonicalError::Layout(format!("expected {expected}, found {found}"))
+}
+
+/// Layout of the elements of an open compound value
+#[derive(Debug, Clone, Copy)]
+enum Children {
+    Plain,
+    /// Struct fields, and how many of them were seen
+    Fields(&'static [(&'static str, Layout)], usize),
+    Repeat(Layout),
+}
+
+impl Children {
+    /// Layout of the next element, checking the name of a struct field against the layout
+    fn next(&mut self, key: Option<&'static str>) -> Result<Layout, CanonicalError> {
+        match self {
+            Children::Plain => Ok(Layout::Plain),
+            Children::Repeat(layout) => Ok(*layout),
+            Children::Fields(fields, seen) => {
+                let (name, layout) = *fields.get(*seen).ok_or_else(|| {
+                    CanonicalError::Layout(format!("more than {} fields", fields.len()))
+                })?;
+                if let Some(key) = key.filter(|key| *key != name) {
+                    return Err(CanonicalError::Layout(format!(
+                        "expected field {name}, found {key}"
+                    )));
+                }
+                *seen += 1;
+                Ok(layout)
+            }
+        }
+    }
+}
+
+struct Encoder<'a> {
+    out: &'a mut Vec<u8>,
+    points: PointEncoding,
+    /// Layout of the value being encoded
+    expect: Layout,
+    /// Compound values currently open
+    open: Vec<Children>,
+    /// Set to record the size of each field of the top-level struct
+    fields: Option<Vec<(&'static str, usize)>>,
+}
+
+impl<'a> Encoder<'a> {
+    fn new(out: &'a mut Vec<u8>, points: PointEncoding) -> Self {
+        Self {
+            out,
+            points,
+            expect: Layout::Plain,
+            open: Vec::new(),
+            fields: None,
+        }
+    }
+
+    fn count(&mut self, len: usize) -> Result<(), CanonicalError> {
+        let len = u32::try_from(len).map_err(|_| CanonicalError::LengthOverflow(len))?;
+        self.out.extend_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    /// Encode `value` laid out as `layout`; a point is read back from its serde form and
+    /// written in the form of the artifact
+    fn value<T: Serialize + ?Sized>(
+        &mut self,
+        layout: Layout,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        if layout == Layout::Point {
+            let point: Point = point_options()
+                .serialize(value)
+                .and_then(|bytes| point_options().deserialize(&bytes))
+                .map_err(|e| CanonicalError::Layout(format!("expected a point: {e}")))?;
+            self.points.write(&point, self.out);
+            return Ok(());
+        }
+        self.expect = layout;
+        value.serialize(self)
+    }
+
+    /// Encode the next element of the open compound value
+    fn element<T: Serialize + ?Sized>(
+        &mut self,
+        key: Option<&'static str>,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        let layout = match self.open.last_mut() {
+            Some(children) => children.next(key)?,
+            None => Layout::Plain,
+        };
+        self.value(layout, value)
+    }
+
+    /// Check that a value without points is expected here
+    fn plain(&self, found: &str) -> Result<(), CanonicalError> {
+        match self.expect {
+            Layout::Plain => Ok(()),
+            expected => Err(mismatch(expected, found)),
+        }
+    }
+
+    /// Start a compound value other than a struct or a sequence, which cannot hold points
+    fn open_plain(&mut self, found: &str) -> Result<(), CanonicalError> {
+        self.plain(found)?;
+        self.open.push(Children::Plain);
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), CanonicalError> {
+        self.open.pop();
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), CanonicalError> {
+        self.plain("bool")?;
+        self.out.push(u8::from(v));
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CanonicalError> {
+        self.plain("i8")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CanonicalError> {
+        self.plain("i16")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CanonicalError> {
+        self.plain("i32")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CanonicalError> {
+        self.plain("i64")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CanonicalError> {
+        self.plain("u8")?;
+        self.out.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CanonicalError> {
+        self.plain("u16")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CanonicalError> {
+        self.plain("u32")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CanonicalError> {
+        self.plain("u64")?;
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), CanonicalError> {
+        Err(CanonicalError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), CanonicalError> {
+        Err(CanonicalError::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CanonicalError> {
+        self.serialize_u32(u32::from(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CanonicalError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CanonicalError> {
+        self.plain("bytes")?;
+        self.count(v.len())?;
+        self.out.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), CanonicalError> {
+        match self.expect {
+            Layout::Plain | Layout::Option(_) => {
+                self.out.push(0);
+                Ok(())
+            }
+            expected => Err(mismatch(expected, "an option")),
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CanonicalError> {
+        let layout = match self.expect {
+            Layout::Plain => Layout::Plain,
+            Layout::Option(layout) => *layout,
+            expected => return Err(mismatch(expected, "an option")),
+        };
+        self.out.push(1);
+        self.value(layout, value)
+    }
+
+    fn serialize_unit(self) -> Result<(), CanonicalError> {
+        self.plain("unit")
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), CanonicalError> {
+        self.plain(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+    ) -> Result<(), CanonicalError> {
+        self.serialize_u32(variant_index)
+    }
+
+    /// Newtypes are transparent: the inner value takes the layout of the newtype
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), CanonicalError> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, CanonicalError> {
+        let children = match self.expect {
+            Layout::Plain => Children::Plain,
+            Layout::Seq(element) => Children::Repeat(*element),
+            expected => return Err(mismatch(expected, "a sequence")),
+        };
+        self.count(len.ok_or(CanonicalError::Unsupported("sequence of unknown length"))?)?;
+        self.open.push(children);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, CanonicalError> {
+        self.open_plain("a tuple")?;
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _: usize) -> Result<Self, CanonicalError> {
+        self.open_plain(name)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, CanonicalError> {
+        self.serialize_u32(variant_index)?;
+        self.open_plain("a tuple variant")?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, CanonicalError> {
+        self.plain("a map")?;
+        self.count(len.ok_or(CanonicalError::Unsupported("map of unknown length"))?)?;
+        self.open.push(Children::Plain);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self, CanonicalError> {
+        let children = match self.expect {
+            Layout::Plain => Children::Plain,
+            Layout::Struct(expected, fields) if expected == name && fields.len() == len => {
+                Children::Fields(fields, 0)
+            }
+            expected => {
+                return Err(mismatch(
+                    expected,
+                    format_args!("struct {name} of {len} fields"),
+                ))
+            }
+        };
+        self.open.push(children);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, CanonicalError> {
+        self.serialize_u32(variant_index)?;
+        self.open_plain("a struct variant")?;
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTuple for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeMap for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.element(None, key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStruct for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let start = self.out.len();
+        self.element(Some(key), value)?;
+        if self.open.len() == 1 {
+            let len = self.out.len() - start;
+            if let Some(fields) = &mut self.fields {
+                fields.push((key, len));
+            }
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Encoder<'_> {
+    type Ok = ();
+    type Error = CanonicalError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.element(None, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.close()
+    }
+}
+
+struct Decoder<'de> {
+    input: &'de [u8],
+    points: PointEncoding,
+    /// Layout of the value being decoded
+    expect: Layout,
+}
+
+impl<'de> Decoder<'de> {
+    fn take(&mut self, n: usize) -> Result<&'de [u8], CanonicalError> {
+        if self.input.len() < n {
+            return Err(CanonicalError::UnexpectedEnd);
+        }
+        let (head, rest) = self.input.split_at(n);
+        self.input = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CanonicalError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    fn tag(&mut self) -> Result<bool, CanonicalError> {
+        match self.array::<1>()?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(CanonicalError::InvalidTag(tag)),
+        }
+    }
+
+    /// An element count; every element takes at least one byte, so it cannot exceed the input
+    fn count(&mut self) -> Result<usize, CanonicalError> {
+        let len = u32::from_le_bytes(self.array()?);
+        if len as usize > self.input.len() {
+            return Err(CanonicalError::InvalidLength(len));
+        }
+        Ok(len as usize)
+    }
+
+    fn bytes(&mut self) -> Result<&'de [u8], CanonicalError> {
+        let len = self.count()?;
+        self.take(len)
+    }
+
+    /// Decode a value laid out as `layout`; a point is decoded from the form of the artifact
+    /// and handed to its `Deserialize` implementation in its serde form
+    fn value<T: DeserializeSeed<'de>>(
+        &mut self,
+        layout: Layout,
+        seed: T,
+    ) -> Result<T::Value, CanonicalError> {
+        if layout == Layout::Point {
+            let point = self.points.read(self.take(self.points.len())?)?;
+            let bytes = point_options()
+                .serialize(&point)
+                .map_err(|e| CanonicalError::Custom(e.to_string()))?;
+            let mut replay = bincode::Deserializer::with_reader(bytes.as_slice(), point_options());
+            return seed
+                .deserialize(&mut replay)
+                .map_err(|e| CanonicalError::Layout(format!("expected a point: {e}")));
+        }
+        self.expect = layout;
+        seed.deserialize(self)
+    }
+
+    /// Check that a value without points is expected here
+    fn plain(&self, found: &str) -> Result<(), CanonicalError> {
+        match self.expect {
+            Layout::Plain => Ok(()),
+            expected => Err(mismatch(expected, found)),
+        }
+    }
+
+    fn elements(&mut self, remaining: usize, children: Children) -> Elements<'_, 'de> {
+        Elements {
+            decoder: self,
+            remaining,
+            children,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = CanonicalError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, CanonicalError> {
+        Err(CanonicalError::Unsupported("self-describing input"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, CanonicalError> {
+        Err(CanonicalError::Unsupported("ignored fields"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("bool")?;
+        visitor.visit_bool(self.tag()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("i8")?;
+        visitor.visit_i8(i8::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("i16")?;
+        visitor.visit_i16(i16::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("i32")?;
+        visitor.visit_i32(i32::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("i64")?;
+        visitor.visit_i64(i64::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("u8")?;
+        visitor.visit_u8(self.array::<1>()?[0])
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("u16")?;
+        visitor.visit_u16(u16::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("u32")?;
+        visitor.visit_u32(u32::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("u64")?;
+        visitor.visit_u64(u64::from_le_bytes(self.array()?))
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, CanonicalError> {
+        Err(CanonicalError::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, CanonicalError> {
+        Err(CanonicalError::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("char")?;
+        let code = u32::from_le_bytes(self.array()?);
+        visitor.visit_char(char::from_u32(code).ok_or(CanonicalError::InvalidChar(code))?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("str")?;
+        let bytes = self.bytes()?;
+        visitor.visit_borrowed_str(
+            std::str::from_utf8(bytes).map_err(|_| CanonicalError::InvalidUtf8)?,
+        )
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("bytes")?;
+        visitor.visit_borrowed_bytes(self.bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        let layout = match self.expect {
+            Layout::Plain => Layout::Plain,
+            Layout::Option(layout) => *layout,
+            expected => return Err(mismatch(expected, "an option")),
+        };
+        if self.tag()? {
+            self.expect = layout;
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("unit")?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.plain(name)?;
+        visitor.visit_unit()
+    }
+
+    /// Newtypes are transparent: the inner value takes the layout of the newtype
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        let children = match self.expect {
+            Layout::Plain => Children::Plain,
+            Layout::Seq(element) => Children::Repeat(*element),
+            expected => return Err(mismatch(expected, "a sequence")),
+        };
+        let len = self.count()?;
+        visitor.visit_seq(self.elements(len, children))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.plain("a tuple")?;
+        visitor.visit_seq(self.elements(len, Children::Plain))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.plain(name)?;
+        visitor.visit_seq(self.elements(len, Children::Plain))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CanonicalError> {
+        self.plain("a map")?;
+        let len = self.count()?;
+        visitor.visit_map(self.elements(len, Children::Plain))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        let children = match self.expect {
+            Layout::Plain => Children::Plain,
+            Layout::Struct(expected, layout)
+                if expected == name
+                    && layout.len() == fields.len()
+                    && layout.iter().zip(fields).all(|((a, _), b)| a == b) =>
+            {
+                Children::Fields(layout, 0)
+            }
+            expected => {
+                return Err(mismatch(
+                    expected,
+                    format_args!("struct {name} with fields {}", fields.join(", ")),
+                ))
+            }
+        };
+        visitor.visit_seq(self.elements(fields.len(), children))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.plain(name)?;
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.deserialize_u32(visitor)
+    }
+}
+
+/// Elements of a sequence, tuple, struct or map, `remaining` of them still to decode
+struct Elements<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    remaining: usize,
+    children: Children,
+}
+
+impl<'de> Elements<'_, 'de> {
+    fn next<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, CanonicalError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        // Field names were checked against the layout when the struct was opened
+        let layout = self.children.next(None)?;
+        self.decoder.value(layout, seed).map(Some)
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = CanonicalError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, CanonicalError> {
+        self.next(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Elements<'_, 'de> {
+    type Error = CanonicalError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, CanonicalError> {
+        self.next(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, CanonicalError> {
+        self.decoder.value(Layout::Plain, seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Decoder<'de> {
+    type Error = CanonicalError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), CanonicalError> {
+        let index = u32::from_le_bytes(self.array()?);
+        let value =
+            seed.deserialize(IntoDeserializer::<CanonicalError>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Decoder<'de> {
+    type Error = CanonicalError;
+
+    fn unit_variant(self) -> Result<(), CanonicalError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, CanonicalError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CanonicalError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -0,0 +1,421 @@
+//! Deterministic CBOR presentation bundle for ISO 18013-5 style transports (NFC, BLE).
+//!
+//! The bundle is a CBOR map that can be carried in an mdoc `DeviceResponse`:
+//!
+//! ```text
+//! {
+//!   "vkId":         { "prepare": bstr .size 32, "show": bstr .size 32 },
+//!   "nonce":        bstr,
+//!   "format":       "zkid-spartan2",
+//!   "proofs":       { "prepare": bstr, "show": bstr },
+//!   "version":      1,
+//!   "publicInputs": { "prepare": [* bstr], "show": [* bstr] }
+//! }
+//! ```
+//!
+//! Proofs use the canonical encoding of [`crate::canonical`], with points in either encoding, and
+//! public inputs are
+//! `PrimeField::to_repr` bytes. Encoding follows the core deterministic rules of RFC 8949 §4.2:
+//! shortest-form integers and lengths, definite lengths only, and map keys sorted by their encoded
+//! bytes. Decoding rejects any input that does not re-encode to the same bytes.
+
+use std::fmt;
+
+use ciborium::Value;
+use ff::PrimeField;
+
+use crate::{
+    canonical::{
+        decode_proof, encode_proof_with, CanonicalError, PointEncoding, COMPRESSED_POINTS, MAGIC,
+    },
+    envelope::ENVELOPE_FORMAT,
+    keys::vk_digest,
+    limits::DeserializeLimits,
+    presentation::Presentation,
+    Proof, Scalar,
+};
+
+/// Current bundle version; decoding rejects any other
+pub const BUNDLE_VERSION: u64 = 1;
+
+/// Room for the nonce, vk ids and public inputs on top of the two proofs
+const METADATA_LIMIT: u64 = 64 * 1024;
+
+/// A presentation with everything a relying party needs to pick keys and check freshness
+#[derive(Clone)]
+pub struct PresentationBundle {
+    pub prepare_proof: Proof,
+    pub show_proof: Proof,
+    pub prepare_public_values: Vec<Scalar>,
+    pub show_public_values: Vec<Scalar>,
+    /// Challenge of the relying party this presentation answers
+    pub nonce: Vec<u8>,
+    /// SHA-256 of the Prepare and Show verifying keys, see [`vk_digest`]
+    pub prepare_vk_id: [u8; 32],
+    pub show_vk_id: [u8; 32],
+}
+
+/// Bundle fields and component sizes, read without decoding the proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleInfo {
+    pub version: u64,
+    pub prepare_vk_id: [u8; 32],
+    pub show_vk_id: [u8; 32],
+    pub nonce: Vec<u8>,
+    pub prepare_public_values: Vec<Scalar>,
+    pub show_public_values: Vec<Scalar>,
+    pub prepare_proof_bytes: usize,
+    pub show_proof_bytes: usize,
+    pub bundle_bytes: usize,
+    /// The Prepare proof is encoded with compressed points
+    pub compressed_points: bool,
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    Cbor(String),
+    /// The input is valid CBOR but not in deterministic encoding
+    NotDeterministic,
+    /// A field is missing, duplicated, unexpected or of the wrong type
+    Structure(String),
+    Format(String),
+    Version(u64),
+    TooLarge {
+        size: u64,
+        limit: u64,
+    },
+    Proof(CanonicalError),
+    PublicValue,
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Cbor(e) => write!(f, "invalid CBOR: {e}"),
+            BundleError::NotDeterministic => write!(f, "bundle is not deterministically encoded"),
+            BundleError::Structure(reason) => write!(f, "malformed bundle: {reason}"),
+            BundleError::Format(format) => {
+                write!(
+                    f,
+                    "unsupported bundle format '{format}', expected '{ENVELOPE_FORMAT}'"
+                )
+            }
+            BundleError::Version(version) => write!(
+                f,
+                "unsupported bundle version {version}, expected {BUNDLE_VERSION}"
+            ),
+            BundleError::TooLarge { size, limit } => {
+                write!(f, "bundle of {size} bytes exceeds the {limit} byte limit")
+            }
+            BundleError::Proof(e) => write!(f, "invalid proof: {e}"),
+            BundleError::PublicValue => write!(f, "public input is not a field element"),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl PresentationBundle {
+    /// Bundle a presentation for the relying party that sent `nonce`
+    pub fn new(
+        presentation: Presentation,
+        prepare_vk_bytes: &[u8],
+        show_vk_bytes: &[u8],
+        prepare_public_values: Vec<Scalar>,
+        show_public_values: Vec<Scalar>,
+        nonce: Vec<u8>,
+    ) -> Self {
+        Self {
+            prepare_proof: presentation.prepare_proof,
+            show_proof: presentation.show_proof,
+            prepare_public_values,
+            show_public_values,
+            nonce,
+            prepare_vk_id: vk_digest(prepare_vk_bytes),
+            show_vk_id: vk_digest(show_vk_bytes),
+        }
+    }
+
+    pub fn presentation(&self) -> Presentation {
+        Presentation::new(self.prepare_proof.clone(), self.show_proof.clone())
+    }
+
+    pub fn to_cbor(&self) -> Result<Vec<u8>, BundleError> {
+        self.to_cbor_with(PointEncoding::Uncompressed)
+    }
+
+    /// Encode the bundle with the points of both proofs written as `points` says
+    pub fn to_cbor_with(&self, points: PointEncoding) -> Result<Vec<u8>, BundleError> {
+        let proof = |proof: &Proof| encode_proof_with(proof, points).map_err(BundleError::Proof);
+        let public_inputs = |values: &[Scalar]| {
+            Value::Array(
+                values
+                    .iter()
+                    .map(|value| Value::Bytes(value.to_repr().as_ref().to_vec()))
+                    .collect(),
+            )
+        };
+        let bundle = map(vec![
+            ("format", Value::Text(ENVELOPE_FORMAT.to_string())),
+            ("version", Value::Integer(BUNDLE_VERSION.into())),
+            (
+                "vkId",
+                map(vec![
+                    ("prepare", Value::Bytes(self.prepare_vk_id.to_vec())),
+                    ("show", Value::Bytes(self.show_vk_id.to_vec())),
+                ]),
+            ),
+            (
+                "proofs",
+                map(vec![
+                    ("prepare", Value::Bytes(proof(&self.prepare_proof)?)),
+                    ("show", Value::Bytes(proof(&self.show_proof)?)),
+                ]),
+            ),
+            (
+                "publicInputs",
+                map(vec![
+                    ("prepare", public_inputs(&self.prepare_public_values)),
+                    ("show", public_inputs(&self.show_public_values)),
+                ]),
+            ),
+            ("nonce", Value::Bytes(self.nonce.clone())),
+        ]);
+        to_deterministic_cbor(bundle)
+    }
+
+    /// Decode a bundle received from a wallet, rejecting bundles over the presentation limit
+    pub fn from_cbor(bytes: &[u8], limits: &DeserializeLimits) -> Result<Self, BundleError> {
+        let fields = BundleFields::parse(bytes, limits)?;
+        Ok(Self {
+            prepare_proof: decode_proof(&fields.prepare_proof).map_err(BundleError::Proof)?,
+            show_proof: decode_proof(&fields.show_proof).map_err(BundleError::Proof)?,
+            prepare_public_values: fields.prepare_public_values,
+            show_public_values: fields.show_public_values,
+            nonce: fields.nonce,
+            prepare_vk_id: fields.prepare_vk_id,
+            show_vk_id: fields.show_vk_id,
+        })
+    }
+}
+
+/// Largest bundle accepted under `limits`, e.g. to bound its decompression
+pub fn bundle_limit(limits: &DeserializeLimits) -> u64 {
+    limits.presentation().saturating_add(METADATA_LIMIT)
+}
+
+/// Read the fields and component sizes of a bundle, leaving the proofs encoded
+pub fn inspect_bundle(bytes: &[u8], limits: &DeserializeLimits) -> Result<BundleInfo, BundleError> {
+    let fields = BundleFields::parse(bytes, limits)?;
+    Ok(BundleInfo {
+        version: BUNDLE_VERSION,
+        prepare_vk_id: fields.prepare_vk_id,
+        show_vk_id: fields.show_vk_id,
+        nonce: fields.nonce,
+        prepare_public_values: fields.prepare_public_values,
+        show_public_values: fields.show_public_values,
+        prepare_proof_bytes: fields.prepare_proof.len(),
+        show_proof_bytes: fields.show_proof.len(),
+        bundle_bytes: bytes.len(),
+        compressed_points: fields
+            .prepare_proof
+            .get(MAGIC.len() + 1)
+            .is_some_and(|kind| kind & COMPRESSED_POINTS != 0),
+    })
+}
+
+/// Fields of a checked bundle, with the proofs still encoded
+struct BundleFields {
+    prepare_vk_id: [u8; 32],
+    show_vk_id: [u8; 32],
+    nonce: Vec<u8>,
+    prepare_proof: Vec<u8>,
+    show_proof: Vec<u8>,
+    prepare_public_values: Vec<Scalar>,
+    show_public_values: Vec<Scalar>,
+}
+
+impl BundleFields {
+    fn parse(bytes: &[u8], limits: &DeserializeLimits) -> Result<Self, BundleError> {
+        let limit = bundle_limit(limits);
+        if bytes.len() as u64 > limit {
+            return Err(BundleError::TooLarge {
+                size: bytes.len() as u64,
+                limit,
+            });
+        }
+        let value: Value =
+            ciborium::from_reader(bytes).map_err(|e| BundleError::Cbor(e.to_string()))?;
+        if to_deterministic_cbor(value.clone())? != bytes {
+            return Err(BundleError::NotDeterministic);
+        }
+        Self::from_value(&value, limits)
+    }
+
+    fn from_value(value: &Value, limits: &DeserializeLimits) -> Result<Self, BundleError> {
+        let bundle = Fields::new(
+            value,
+            "bundle",
+            &[
+                "format",
+                "version",
+                "vkId",
+                "proofs",
+                "publicInputs",
+                "nonce",
+            ],
+        )?;
+
+        match bundle.get("format")? {
+            Value::Text(format) if format == ENVELOPE_FORMAT => {}
+            Value::Text(format) => return Err(BundleError::Format(format.clone())),
+            _ => return Err(structure("format must be a text string")),
+        }
+        let version = bundle
+            .get("version")?
+            .as_integer()
+            .and_then(|version| u64::try_from(version).ok())
+            .ok_or_else(|| structure("version must be an unsigned integer"))?;
+        if version != BUNDLE_VERSION {
+            return Err(BundleError::Version(version));
+        }
+
+        let vk_id = Fields::new(bundle.get("vkId")?, "vkId", &["prepare", "show"])?;
+        let proofs = Fields::new(bundle.get("proofs")?, "proofs", &["prepare", "show"])?;
+        let public_inputs = Fields::new(
+            bundle.get("publicInputs")?,
+            "publicInputs",
+            &["prepare", "show"],
+        )?;
+
+        let proof = |name: &str| -> Result<Vec<u8>, BundleError> {
+            let proof = bytes_field(proofs.get(name)?, "proofs")?;
+            if proof.len() as u64 > limits.proof {
+                return Err(BundleError::TooLarge {
+                    size: proof.len() as u64,
+                    limit: limits.proof,
+                });
+            }
+            Ok(proof.to_vec())
+        };
+
+        Ok(Self {
+            prepare_vk_id: digest_field(vk_id.get("prepare")?)?,
+            show_vk_id: digest_field(vk_id.get("show")?)?,
+            nonce: bytes_field(bundle.get("nonce")?, "nonce")?.to_vec(),
+            prepare_proof: proof("prepare")?,
+            show_proof: proof("show")?,
+            prepare_public_values: scalars_field(public_inputs.get("prepare")?)?,
+            show_public_values: scalars_field(public_inputs.get("show")?)?,
+        })
+    }
+}
+
+/// Entries of a map with exactly the expected text keys
+struct Fields<'a> {
+    entries: &'a [(Value, Value)],
+    name: &'static str,
+}
+
+impl<'a> Fields<'a> {
+    fn new(value: &'a Value, name: &'static str, keys: &[&str]) -> Result<Self, BundleError> {
+        let entries = value
+            .as_map()
+            .ok_or_else(|| structure(format!("{name} must be a map")))?;
+        let fields = Self { entries, name };
+        if entries.len() != keys.len() {
+            return Err(structure(format!("{name} must have the fields {keys:?}")));
+        }
+        for key in keys {
+            fields.get(key)?;
+        }
+        Ok(fields)
+    }
+
+    fn get(&self, key: &str) -> Result<&'a Value, BundleError> {
+        let mut matches = self
+            .entries
+            .iter()
+            .filter(|(k, _)| k.as_text() == Some(key))
+            .map(|(_, v)| v);
+        match (matches.next(), matches.next()) {
+            (Some(value), None) => Ok(value),
+            (None, _) => Err(structure(format!("{} has no field '{key}'", self.name))),
+            (Some(_), Some(_)) => Err(structure(format!("{} repeats '{key}'", self.name))),
+        }
+    }
+}
+
+fn structure(reason: impl Into<String>) -> BundleError {
+    BundleError::Structure(reason.into())
+}
+
+fn bytes_field<'a>(value: &'a Value, name: &str) -> Result<&'a [u8], BundleError> {
+    value
+        .as_bytes()
+        .map(Vec::as_slice)
+        .ok_or_else(|| structure(format!("{name} must be a byte string")))
+}
+
+fn digest_field(value: &Value) -> Result<[u8; 32], BundleError> {
+    bytes_field(value, "vkId")?
+        .try_into()
+        .map_err(|_| structure("vkId entries must be 32-byte SHA-256 digests"))
+}
+
+fn scalars_field(value: &Value) -> Result<Vec<Scalar>, BundleError> {
+    let values = value
+        .as_array()
+        .ok_or_else(|| structure("publicInputs entries must be arrays"))?;
+    values
+        .iter()
+        .map(|value| {
+            let bytes = bytes_field(value, "publicInputs")?;
+            let mut repr = <Scalar as PrimeField>::Repr::default();
+            if bytes.len() != repr.as_ref().len() {
+                return Err(BundleError::PublicValue);
+            }
+            repr.as_mut().copy_from_slice(bytes);
+            Option::from(Scalar::from_repr(repr)).ok_or(BundleError::PublicValue)
+        })
+        .collect()
+}
+
+fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::Text(key.to_string()), value))
+            .collect(),
+    )
+}
+
+/// Encode with map keys sorted by their encoded bytes (RFC 8949 §4.2.1)
+fn to_deterministic_cbor(mut value: Value) -> Result<Vec<u8>, BundleError> {
+    sort_maps(&mut value)?;
+    let mut out = Vec::new();
+    ciborium::into_writer(&value, &mut out).map_err(|e| BundleError::Cbor(e.to_string()))?;
+    Ok(out)
+}
+
+fn sort_maps(value: &mut Value) -> Result<(), BundleError> {
+    match value {
+        Value::Array(values) => values.iter_mut().try_for_each(sort_maps),
+        Value::Tag(_, inner) => sort_maps(inner),
+        Value::Map(entries) => {
+            let mut keyed = Vec::with_capacity(entries.len());
+            for (mut key, mut value) in entries.drain(..) {
+                sort_maps(&mut key)?;
+                sort_maps(&mut value)?;
+                let mut encoded = Vec::new();
+                ciborium::into_writer(&key, &mut encoded)
+                    .map_err(|e| BundleError::Cbor(e.to_string()))?;
+                keyed.push((encoded, key, value));
+            }
+            keyed.sort_by(|a, b| a.0.cmp(&b.0));
+            entries.extend(keyed.into_iter().map(|(_, key, value)| (key, value)));
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,59 @@
+//! zstd compression of encoded presentations, for channels where every byte counts (QR codes).
+//!
+//! Proofs are mostly field elements and points, which do not compress, so most of the saving
+//! comes from [`crate::canonical::PointEncoding::Compressed`]; zstd removes what structure is
+//! left. The compressed data is a plain zstd frame, recognized by its magic number.
+
+use std::{fmt, io::Read};
+
+/// Level used by [`compress`]; presentations are small, so the slow levels cost little
+pub const LEVEL: i32 = 19;
+
+/// First bytes of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug)]
+pub enum CompressionError {
+    Zstd(std::io::Error),
+    /// The decompressed data would exceed the limit
+    TooLarge {
+        limit: u64,
+    },
+}
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::Zstd(e) => write!(f, "invalid zstd data: {e}"),
+            CompressionError::TooLarge { limit } => {
+                write!(f, "decompressed data exceeds the {limit} byte limit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+pub fn compress(bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    zstd::bulk::compress(bytes, LEVEL).map_err(CompressionError::Zstd)
+}
+
+/// Whether `bytes` start like a zstd frame
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// Decompress at most `limit` bytes, failing as soon as the output grows past it rather than
+/// trusting the size declared in the frame
+pub fn decompress(bytes: &[u8], limit: u64) -> Result<Vec<u8>, CompressionError> {
+    let decoder = zstd::stream::read::Decoder::new(bytes).map_err(CompressionError::Zstd)?;
+    let mut out = Vec::new();
+    decoder
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut out)
+        .map_err(CompressionError::Zstd)?;
+    if out.len() as u64 > limit {
+        return Err(CompressionError::TooLarge { limit });
+    }
+    Ok(out)
+}
@@ -0,0 +1,107 @@
+//! Calendar dates of Show proofs.
+//!
+//! The Show circuit computes `ageAbove18` on the date it is given and exposes that date as its
+//! last three public values, `currentYear`, `currentMonth` and `currentDay`. A relying party
+//! compares it with its own date, see [`crate::VerificationPolicy::show_date_tolerance_days`].
+
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::Scalar;
+
+/// A day of the proleptic Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// `None` unless `month` and `day` name a day of `year`
+    pub fn new(year: u32, month: u32, day: u32) -> Option<Self> {
+        let date = Self { year, month, day };
+        let in_range = (1..=12).contains(&month) && (1..=31).contains(&day);
+        // Days past the end of the month roll over into the next one
+        (in_range && Self::from_days(date.days()) == Some(date)).then_some(date)
+    }
+
+    /// Today in UTC, from the system clock. Not available on `wasm32-unknown-unknown`, where the
+    /// caller passes the date in.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self::from_days((seconds / 86_400) as i64).expect("the system clock is past year 0")
+    }
+
+    /// The date as the `currentYear`, `currentMonth` and `currentDay` public values
+    pub fn to_scalars(self) -> [Scalar; 3] {
+        [self.year, self.month, self.day].map(|value| Scalar::from(u64::from(value)))
+    }
+
+    /// Read `currentYear`, `currentMonth` and `currentDay` public values, `None` unless they form
+    /// a date
+    pub fn from_scalars(values: &[Scalar]) -> Option<Self> {
+        let [year, month, day] = values else {
+            return None;
+        };
+        Self::new(small(year)?, small(month)?, small(day)?)
+    }
+
+    /// Number of days from `other` to `self`
+    pub fn days_since(self, other: Date) -> i64 {
+        self.days() - other.days()
+    }
+
+    /// Days since 1970-01-01
+    fn days(self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        // Years start in March, so that the leap day is the last day of the year
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    fn from_days(days: i64) -> Option<Self> {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = (shifted_month + 2) % 12 + 1;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Some(Self {
+            year: u32::try_from(year).ok()?,
+            month: month as u32,
+            day: day as u32,
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A public value below 2^32
+fn small(value: &Scalar) -> Option<u32> {
+    let repr = value.to_repr();
+    let (low, high) = repr.as_ref().split_at(4);
+    if high.iter().any(|&byte| byte != 0) {
+        return None;
+    }
+    Some(u32::from_le_bytes(low.try_into().ok()?))
+}
@@ -0,0 +1,189 @@
+//! JSON envelope carrying a presentation over text transports (OpenID4VP `vp_token`, QR codes).
+//!
+//! ```json
+//! {"format":"zkid-spartan2","version":1,"vk_id":{"prepare":"..","show":".."},
+//!  "proof":"<base64url presentation>","public":{"prepare":[],"show":[]}}
+//! ```
+//!
+//! `proof` is the bincode presentation in unpadded base64url. The public values are hex encodings
+//! of `PrimeField::to_repr`; they tell the relying party what the proofs claim, but a verifier
+//! must still compare them with the values returned by verification.
+
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+use crate::{hex::scalar_to_hex, limits::DeserializeLimits, presentation::Presentation, Scalar};
+
+/// Value of the `format` field
+pub const ENVELOPE_FORMAT: &str = "zkid-spartan2";
+
+/// Current envelope version; decoding rejects any other
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// [`crate::vk_id`] of the Prepare and Show verifying keys the presentation was proven against
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VkIds {
+    pub prepare: String,
+    pub show: String,
+}
+
+/// Public values of each proof, hex-encoded
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopePublicValues {
+    pub prepare: Vec<String>,
+    pub show: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub vk_id: VkIds,
+    pub proof: String,
+    pub public: EnvelopePublicValues,
+}
+
+/// Byte sizes of an envelope, to choose a transport per channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EnvelopeSizes {
+    /// Binary presentation
+    pub proof_bytes: usize,
+    /// `proof` field, base64url
+    pub proof_base64_bytes: usize,
+    /// Whole JSON envelope
+    pub envelope_bytes: usize,
+}
+
+#[derive(Debug)]
+pub enum EnvelopeError {
+    Json(serde_json::Error),
+    /// The `format` field is not [`ENVELOPE_FORMAT`]
+    Format(String),
+    Version(u32),
+    Base64(base64::DecodeError),
+    /// The decoded proof is not a presentation within the limits
+    Presentation(String),
+    /// A public value is not the hex encoding of a field element
+    PublicValue(String),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Json(e) => write!(f, "invalid envelope JSON: {e}"),
+            EnvelopeError::Format(format) => {
+                write!(
+                    f,
+                    "unsupported envelope format '{format}', expected '{ENVELOPE_FORMAT}'"
+                )
+            }
+            EnvelopeError::Version(version) => write!(
+                f,
+                "unsupported envelope version {version}, expected {ENVELOPE_VERSION}"
+            ),
+            EnvelopeError::Base64(e) => write!(f, "proof is not unpadded base64url: {e}"),
+            EnvelopeError::Presentation(e) => write!(f, "invalid presentation: {e}"),
+            EnvelopeError::PublicValue(value) => {
+                write!(f, "public value '{value}' is not a field element")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+impl PresentationEnvelope {
+    /// Wrap a presentation with the ids of its verifying keys and the public values of its proofs
+    pub fn new(
+        presentation: &Presentation,
+        vk_id: VkIds,
+        prepare_public_values: &[Scalar],
+        show_public_values: &[Scalar],
+    ) -> Result<Self, EnvelopeError> {
+        let bytes = presentation
+            .to_bytes()
+            .map_err(|e| EnvelopeError::Presentation(e.to_string()))?;
+        let public = EnvelopePublicValues {
+            prepare: prepare_public_values.iter().map(scalar_to_hex).collect(),
+            show: show_public_values.iter().map(scalar_to_hex).collect(),
+        };
+        Ok(Self::from_presentation_bytes(&bytes, vk_id, public))
+    }
+
+    /// Wrap an already encoded presentation
+    pub fn from_presentation_bytes(
+        bytes: &[u8],
+        vk_id: VkIds,
+        public: EnvelopePublicValues,
+    ) -> Self {
+        Self {
+            format: ENVELOPE_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            vk_id,
+            proof: URL_SAFE_NO_PAD.encode(bytes),
+            public,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, EnvelopeError> {
+        serde_json::to_string(self).map_err(EnvelopeError::Json)
+    }
+
+    /// Parse an envelope, rejecting other formats and versions
+    pub fn from_json(json: &str) -> Result<Self, EnvelopeError> {
+        let envelope: Self = serde_json::from_str(json).map_err(EnvelopeError::Json)?;
+        if envelope.format != ENVELOPE_FORMAT {
+            return Err(EnvelopeError::Format(envelope.format));
+        }
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::Version(envelope.version));
+        }
+        Ok(envelope)
+    }
+
+    /// The encoded presentation carried in `proof`
+    pub fn presentation_bytes(&self) -> Result<Vec<u8>, EnvelopeError> {
+        URL_SAFE_NO_PAD
+            .decode(&self.proof)
+            .map_err(EnvelopeError::Base64)
+    }
+
+    /// Decode the presentation, rejecting encodings over the limits
+    pub fn presentation(&self, limits: &DeserializeLimits) -> Result<Presentation, EnvelopeError> {
+        Presentation::from_bytes(&self.presentation_bytes()?, limits)
+            .map_err(|e| EnvelopeError::Presentation(e.to_string()))
+    }
+
+    /// Prepare and Show public values claimed by the envelope
+    pub fn public_values(&self) -> Result<(Vec<Scalar>, Vec<Scalar>), EnvelopeError> {
+        let parse = |values: &[String]| -> Result<Vec<Scalar>, EnvelopeError> {
+            values.iter().map(|value| scalar_from_hex(value)).collect()
+        };
+        Ok((parse(&self.public.prepare)?, parse(&self.public.show)?))
+    }
+
+    pub fn sizes(&self) -> Result<EnvelopeSizes, EnvelopeError> {
+        Ok(EnvelopeSizes {
+            proof_bytes: self.presentation_bytes()?.len(),
+            proof_base64_bytes: self.proof.len(),
+            envelope_bytes: self.to_json()?.len(),
+        })
+    }
+}
+
+fn scalar_from_hex(value: &str) -> Result<Scalar, EnvelopeError> {
+    let invalid = || EnvelopeError::PublicValue(value.to_string());
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    let bytes = repr.as_mut();
+    if !value.bytes().all(|b| b.is_ascii_hexdigit()) || value.len() != 2 * bytes.len() {
+        return Err(invalid());
+    }
+    for (byte, digits) in bytes.iter_mut().zip(value.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+    }
+    Option::from(Scalar::from_repr(repr)).ok_or_else(invalid)
+}
@@ -0,0 +1,19 @@
+//! Lowercase hex, as used for vk ids, nonces, commitments and public values in every report and
+//! envelope.
+
+use ff::PrimeField;
+
+use crate::Scalar;
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// A scalar as the hex of its `PrimeField::to_repr` (little-endian)
+pub fn scalar_to_hex(value: &Scalar) -> String {
+    to_hex(value.to_repr().as_ref())
+}
+
+pub fn scalars_to_hex(values: &[Scalar]) -> Vec<String> {
+    values.iter().map(scalar_to_hex).collect()
+}
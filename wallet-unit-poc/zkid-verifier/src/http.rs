@@ -0,0 +1,77 @@
+//! Local HTTP stand-in for an OpenID4VP relying party, serving a [`RelyingParty`] with axum.
+//!
+//! - `GET /authorize?predicate=age_over_18` returns an [`AuthorizationRequest`] as JSON
+//! - `POST /response` takes the wallet's form-encoded [`AuthorizationResponse`] (`direct_post`)
+//!   and returns a [`VerificationResponse`], or `400` with an [`ErrorResponse`]
+//!
+//! [`AuthorizationRequest`]: crate::verifier::AuthorizationRequest
+//! [`AuthorizationResponse`]: crate::verifier::AuthorizationResponse
+//! [`VerificationResponse`]: crate::verifier::VerificationResponse
+//! [`ErrorResponse`]: crate::verifier::ErrorResponse
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+
+use crate::verifier::{AuthorizationResponse, ErrorResponse, Predicate, RelyingParty};
+
+/// Path the wallet posts its response to
+pub const RESPONSE_PATH: &str = "/response";
+
+#[derive(Deserialize)]
+struct AuthorizeParams {
+    predicate: Option<Predicate>,
+}
+
+pub fn router(relying_party: Arc<RelyingParty>) -> Router {
+    Router::new()
+        .route("/authorize", get(authorize))
+        .route(RESPONSE_PATH, post(respond))
+        .with_state(relying_party)
+}
+
+/// Serve the relying party on `listener` until the server fails
+pub async fn serve(listener: TcpListener, relying_party: Arc<RelyingParty>) -> std::io::Result<()> {
+    axum::serve(listener, router(relying_party)).await
+}
+
+async fn authorize(
+    State(relying_party): State<Arc<RelyingParty>>,
+    Query(params): Query<AuthorizeParams>,
+) -> Response {
+    let predicate = params.predicate.unwrap_or(Predicate::AgeOver18);
+    match relying_party.authorization_request(predicate) {
+        Ok(request) => Json(request).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, "server_error", e),
+    }
+}
+
+async fn respond(
+    State(relying_party): State<Arc<RelyingParty>>,
+    Form(response): Form<AuthorizationResponse>,
+) -> Response {
+    // Verification is CPU-bound, keep it off the async workers
+    let result =
+        tokio::task::spawn_blocking(move || relying_party.handle_response(&response)).await;
+    match result {
+        Ok(Ok(verified)) => Json(verified).into_response(),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, Json(e.to_response())).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, "server_error", e),
+    }
+}
+
+fn error(status: StatusCode, code: &str, description: impl ToString) -> Response {
+    let body = ErrorResponse {
+        error: code.to_string(),
+        error_description: description.to_string(),
+    };
+    (status, Json(body)).into_response()
+}
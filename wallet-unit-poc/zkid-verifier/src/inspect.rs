@@ -0,0 +1,449 @@
+//! Reports of what a proof, instance or presentation contains, for debugging verifier rejections.
+//!
+//! [`inspect`] recognizes every encoding this crate produces: bincode proofs, instances and
+//! presentations, the canonical encoding, the JSON envelope, the CBOR bundle and, with the
+//! `compression` feature, zstd around any of them. Reports serialize to JSON; points are shown
+//! hex-encoded in compressed form and scalars as hex `PrimeField::to_repr`.
+
+use std::fmt;
+
+use group::GroupEncoding;
+use serde::Serialize;
+use spartan2::r1cs::SplitR1CSInstance;
+
+use crate::{
+    canonical::{self, ArtifactKind, CanonicalError, COMPRESSED_POINTS, MAGIC},
+    cbor::{inspect_bundle, BundleError, PresentationBundle, BUNDLE_VERSION},
+    envelope::{EnvelopeError, PresentationEnvelope},
+    hex::{scalars_to_hex, to_hex},
+    limits::{deserialize_with_limit, DeserializeLimits},
+    presentation::Presentation,
+    verify::{check_linkage, verify},
+    Proof, VerifierKey, E,
+};
+
+/// First bytes of a zstd frame, recognized even without the `compression` feature
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// A verifying key to identify and check proofs with
+pub struct KnownKey<'a> {
+    /// `prepare` or `show`
+    pub circuit: &'static str,
+    pub vk: &'a VerifierKey,
+    /// [`crate::vk_id`] of the encoded key
+    pub vk_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Format {
+    /// `bincode`, `canonical`, `envelope` or `bundle`
+    pub name: &'static str,
+    /// Version of the versioned formats
+    pub version: Option<u64>,
+    pub compressed_points: bool,
+    /// The input was zstd-compressed
+    pub zstd: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentSize {
+    pub name: &'static str,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProofReport {
+    /// `prepare` or `show`, from the container or the known key the proof verifies against
+    pub circuit: Option<&'static str>,
+    pub vk_id: Option<String>,
+    /// Public values returned by verification
+    pub public_values: Option<Vec<String>>,
+    /// Public values the envelope or bundle claims for the proof
+    pub claimed_public_values: Option<Vec<String>>,
+    /// `ok`, or why the proof does not verify; `None` when no known key applies
+    pub verification: Option<String>,
+    pub shared_rows: usize,
+    /// `comm_W_shared`, one point per shared row
+    pub comm_w_shared: Vec<String>,
+    /// Size of the proof as encoded in the input
+    pub bytes: usize,
+    /// Size of each proof component in the canonical encoding with uncompressed points
+    pub components: Vec<ComponentSize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstanceReport {
+    pub shared_rows: usize,
+    pub comm_w_shared: Vec<String>,
+    pub bytes: usize,
+    pub components: Vec<ComponentSize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PresentationReport {
+    /// Relying party challenge carried by a bundle
+    pub nonce: Option<String>,
+    /// Both proofs have the same `comm_W_shared`
+    pub linked: bool,
+    pub prepare: ProofReport,
+    pub show: ProofReport,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Artifact {
+    Proof(ProofReport),
+    Instance(InstanceReport),
+    Presentation(PresentationReport),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub format: Format,
+    /// Size of the input
+    pub bytes: usize,
+    pub artifact: Artifact,
+}
+
+#[derive(Debug)]
+pub enum InspectError {
+    /// The input is in none of the known encodings
+    Unrecognized,
+    Canonical(CanonicalError),
+    Envelope(EnvelopeError),
+    Bundle(BundleError),
+    Compression(String),
+}
+
+impl fmt::Display for InspectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InspectError::Unrecognized => write!(
+                f,
+                "not a proof, instance, presentation, envelope or bundle within the limits"
+            ),
+            InspectError::Canonical(e) => write!(f, "invalid canonical encoding: {e}"),
+            InspectError::Envelope(e) => write!(f, "{e}"),
+            InspectError::Bundle(e) => write!(f, "{e}"),
+            InspectError::Compression(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for InspectError {}
+
+/// Decode `bytes` in whichever encoding they are and report their contents, verifying proofs
+/// against the matching `keys`
+pub fn inspect(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+    keys: &[KnownKey<'_>],
+) -> Result<Report, InspectError> {
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return inspect_uncompressed(bytes, limits, keys);
+    }
+    let mut report = inspect_uncompressed(&decompress(bytes, limits)?, limits, keys)?;
+    report.format.zstd = true;
+    report.bytes = bytes.len();
+    Ok(report)
+}
+
+#[cfg(feature = "compression")]
+fn decompress(bytes: &[u8], limits: &DeserializeLimits) -> Result<Vec<u8>, InspectError> {
+    crate::compression::decompress(bytes, crate::cbor::bundle_limit(limits))
+        .map_err(|e| InspectError::Compression(e.to_string()))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_: &[u8], _: &DeserializeLimits) -> Result<Vec<u8>, InspectError> {
+    Err(InspectError::Compression(
+        "zstd input needs the `compression` feature".into(),
+    ))
+}
+
+fn inspect_uncompressed(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+    keys: &[KnownKey<'_>],
+) -> Result<Report, InspectError> {
+    if bytes.trim_ascii_start().starts_with(b"{") {
+        return inspect_envelope(bytes, limits, keys);
+    }
+    if bytes.starts_with(&MAGIC) {
+        return inspect_canonical(bytes, keys);
+    }
+    if let Some(report) = inspect_bincode(bytes, limits, keys)? {
+        return Ok(report);
+    }
+    match bytes.first() {
+        // A CBOR map header
+        Some(0xa0..=0xbb) => inspect_cbor(bytes, limits, keys),
+        _ => Err(InspectError::Unrecognized),
+    }
+}
+
+fn inspect_envelope(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+    keys: &[KnownKey<'_>],
+) -> Result<Report, InspectError> {
+    let json = std::str::from_utf8(bytes).map_err(|_| InspectError::Unrecognized)?;
+    let envelope = PresentationEnvelope::from_json(json).map_err(InspectError::Envelope)?;
+    let presentation = envelope
+        .presentation(limits)
+        .map_err(InspectError::Envelope)?;
+
+    let mut prepare = ProofReport::new(
+        &presentation.prepare_proof,
+        bincode_size(&presentation.prepare_proof),
+    )?;
+    prepare.claim(
+        "prepare",
+        &envelope.vk_id.prepare,
+        envelope.public.prepare.clone(),
+    );
+    prepare.check(&presentation.prepare_proof, keys);
+    let mut show = ProofReport::new(
+        &presentation.show_proof,
+        bincode_size(&presentation.show_proof),
+    )?;
+    show.claim("show", &envelope.vk_id.show, envelope.public.show.clone());
+    show.check(&presentation.show_proof, keys);
+
+    Ok(Report {
+        format: Format {
+            name: "envelope",
+            version: Some(envelope.version.into()),
+            compressed_points: false,
+            zstd: false,
+        },
+        bytes: bytes.len(),
+        artifact: Artifact::Presentation(PresentationReport {
+            nonce: None,
+            linked: check_linkage(&presentation.prepare_proof, &presentation.show_proof).is_ok(),
+            prepare,
+            show,
+        }),
+    })
+}
+
+fn inspect_cbor(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+    keys: &[KnownKey<'_>],
+) -> Result<Report, InspectError> {
+    let info = inspect_bundle(bytes, limits).map_err(InspectError::Bundle)?;
+    let bundle = PresentationBundle::from_cbor(bytes, limits).map_err(InspectError::Bundle)?;
+
+    let mut prepare = ProofReport::new(&bundle.prepare_proof, info.prepare_proof_bytes)?;
+    prepare.claim(
+        "prepare",
+        &to_hex(&bundle.prepare_vk_id),
+        scalars_to_hex(&bundle.prepare_public_values),
+    );
+    prepare.check(&bundle.prepare_proof, keys);
+    let mut show = ProofReport::new(&bundle.show_proof, info.show_proof_bytes)?;
+    show.claim(
+        "show",
+        &to_hex(&bundle.show_vk_id),
+        scalars_to_hex(&bundle.show_public_values),
+    );
+    show.check(&bundle.show_proof, keys);
+
+    Ok(Report {
+        format: Format {
+            name: "bundle",
+            version: Some(BUNDLE_VERSION),
+            compressed_points: info.compressed_points,
+            zstd: false,
+        },
+        bytes: bytes.len(),
+        artifact: Artifact::Presentation(PresentationReport {
+            nonce: Some(to_hex(&bundle.nonce)),
+            linked: check_linkage(&bundle.prepare_proof, &bundle.show_proof).is_ok(),
+            prepare,
+            show,
+        }),
+    })
+}
+
+fn inspect_canonical(bytes: &[u8], keys: &[KnownKey<'_>]) -> Result<Report, InspectError> {
+    let (version, kind) = match bytes.get(MAGIC.len()..MAGIC.len() + 2) {
+        Some(&[version, kind]) => (version, kind),
+        _ => return Err(InspectError::Canonical(CanonicalError::UnexpectedEnd)),
+    };
+    let format = Format {
+        name: "canonical",
+        version: Some(version.into()),
+        compressed_points: kind & COMPRESSED_POINTS != 0,
+        zstd: false,
+    };
+    let artifact = if kind & !COMPRESSED_POINTS == ArtifactKind::Instance as u8 {
+        let instance = canonical::decode_instance(bytes).map_err(InspectError::Canonical)?;
+        Artifact::Instance(InstanceReport::new(&instance, bytes.len())?)
+    } else {
+        let proof = canonical::decode_proof(bytes).map_err(InspectError::Canonical)?;
+        let mut report = ProofReport::new(&proof, bytes.len())?;
+        report.check(&proof, keys);
+        Artifact::Proof(report)
+    };
+    Ok(Report {
+        format,
+        bytes: bytes.len(),
+        artifact,
+    })
+}
+
+/// A bincode presentation, proof or instance, tried in that order; each must span the whole
+/// input since bincode decoding stops after the value
+fn inspect_bincode(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+    keys: &[KnownKey<'_>],
+) -> Result<Option<Report>, InspectError> {
+    let format = Format {
+        name: "bincode",
+        version: None,
+        compressed_points: false,
+        zstd: false,
+    };
+    let report = |artifact| {
+        Ok(Some(Report {
+            format: format.clone(),
+            bytes: bytes.len(),
+            artifact,
+        }))
+    };
+
+    if let Some(presentation) =
+        decode_exact::<Presentation>(bytes, "presentation", limits.presentation())
+    {
+        let mut prepare = ProofReport::new(
+            &presentation.prepare_proof,
+            bincode_size(&presentation.prepare_proof),
+        )?;
+        prepare.circuit = Some("prepare");
+        prepare.check(&presentation.prepare_proof, keys);
+        let mut show = ProofReport::new(
+            &presentation.show_proof,
+            bincode_size(&presentation.show_proof),
+        )?;
+        show.circuit = Some("show");
+        show.check(&presentation.show_proof, keys);
+        return report(Artifact::Presentation(PresentationReport {
+            nonce: None,
+            linked: check_linkage(&presentation.prepare_proof, &presentation.show_proof).is_ok(),
+            prepare,
+            show,
+        }));
+    }
+    if let Some(proof) = decode_exact::<Proof>(bytes, "proof", limits.proof) {
+        let mut proof_report = ProofReport::new(&proof, bytes.len())?;
+        proof_report.check(&proof, keys);
+        return report(Artifact::Proof(proof_report));
+    }
+    if let Some(instance) = decode_exact::<SplitR1CSInstance<E>>(bytes, "instance", limits.instance)
+    {
+        return report(Artifact::Instance(InstanceReport::new(
+            &instance,
+            bytes.len(),
+        )?));
+    }
+    Ok(None)
+}
+
+fn decode_exact<T: serde::de::DeserializeOwned + Serialize>(
+    bytes: &[u8],
+    artifact: &str,
+    limit: u64,
+) -> Option<T> {
+    let value: T = deserialize_with_limit(bytes, artifact, limit).ok()?;
+    (bincode_size(&value) == bytes.len()).then_some(value)
+}
+
+fn bincode_size<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).map_or(0, |size| size as usize)
+}
+
+impl ProofReport {
+    fn new(proof: &Proof, bytes: usize) -> Result<Self, InspectError> {
+        let comm_w_shared = proof
+            .comm_W_shared()
+            .map(|comm| comm.comm.iter().map(point_to_hex).collect::<Vec<_>>())
+            .unwrap_or_default();
+        Ok(Self {
+            circuit: None,
+            vk_id: None,
+            public_values: None,
+            claimed_public_values: None,
+            verification: None,
+            shared_rows: comm_w_shared.len(),
+            comm_w_shared,
+            bytes,
+            components: components(proof, canonical::PROOF_LAYOUT)?,
+        })
+    }
+
+    /// What the envelope or bundle carrying the proof says about it
+    fn claim(&mut self, circuit: &'static str, vk_id: &str, public_values: Vec<String>) {
+        self.circuit = Some(circuit);
+        self.vk_id = Some(vk_id.to_string());
+        self.claimed_public_values = Some(public_values);
+    }
+
+    /// Verify against the key named by `vk_id`, or against each known key when it is unknown
+    fn check(&mut self, proof: &Proof, keys: &[KnownKey<'_>]) {
+        let candidates: Vec<_> = keys
+            .iter()
+            .filter(|key| self.vk_id.as_ref().is_none_or(|id| *id == key.vk_id))
+            .filter(|key| self.circuit.is_none_or(|circuit| circuit == key.circuit))
+            .collect();
+        let mut failures = Vec::new();
+        for key in &candidates {
+            match verify(proof, key.vk) {
+                Ok(public_values) => {
+                    self.circuit = Some(key.circuit);
+                    self.vk_id = Some(key.vk_id.clone());
+                    self.public_values = Some(scalars_to_hex(&public_values));
+                    self.verification = Some("ok".into());
+                    return;
+                }
+                Err(e) => failures.push(format!("{} key: {e}", key.circuit)),
+            }
+        }
+        if !failures.is_empty() {
+            self.verification = Some(failures.join("; "));
+        }
+    }
+}
+
+impl InstanceReport {
+    fn new(instance: &SplitR1CSInstance<E>, bytes: usize) -> Result<Self, InspectError> {
+        let comm_w_shared: Vec<String> = instance
+            .comm_W_shared
+            .as_ref()
+            .map(|comm| comm.comm.iter().map(point_to_hex).collect())
+            .unwrap_or_default();
+        Ok(Self {
+            shared_rows: instance.num_shared_rows(),
+            comm_w_shared,
+            bytes,
+            components: components(instance, canonical::INSTANCE_LAYOUT)?,
+        })
+    }
+}
+
+fn components<T: Serialize>(
+    value: &T,
+    layout: canonical::Layout,
+) -> Result<Vec<ComponentSize>, InspectError> {
+    Ok(canonical::field_sizes(value, layout)
+        .map_err(InspectError::Canonical)?
+        .into_iter()
+        .map(|(name, bytes)| ComponentSize { name, bytes })
+        .collect())
+}
+
+fn point_to_hex<P: GroupEncoding>(point: &P) -> String {
+    to_hex(point.to_bytes().as_ref())
+}
@@ -0,0 +1,63 @@
+use std::{fs::File, io::BufReader};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    hex::to_hex,
+    limits::{deserialize_from_with_limit, deserialize_with_limit, DeserializeLimits},
+    Proof, VerifierKey,
+};
+
+/// Deserialize a verifying key from its bincode encoding, rejecting encodings over the limit
+pub fn verifying_key_from_bytes(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+) -> Result<VerifierKey, Box<dyn std::error::Error>> {
+    deserialize_with_limit(bytes, "verifying key", limits.verifying_key)
+}
+
+/// Deserialize a proof from its bincode encoding, rejecting encodings over the limit.
+///
+/// Suitable for proofs received over the network.
+pub fn proof_from_bytes(
+    bytes: &[u8],
+    limits: &DeserializeLimits,
+) -> Result<Proof, Box<dyn std::error::Error>> {
+    deserialize_with_limit(bytes, "proof", limits.proof)
+}
+
+pub fn load_verifying_key(vk_path: &str) -> Result<VerifierKey, Box<dyn std::error::Error>> {
+    load_verifying_key_with_limits(vk_path, &DeserializeLimits::default())
+}
+
+pub fn load_verifying_key_with_limits(
+    vk_path: &str,
+    limits: &DeserializeLimits,
+) -> Result<VerifierKey, Box<dyn std::error::Error>> {
+    let vk_file = BufReader::new(File::open(vk_path)?);
+    deserialize_from_with_limit(vk_file, "verifying key", limits.verifying_key)
+}
+
+pub fn load_proof(proof_path: &str) -> Result<Proof, Box<dyn std::error::Error>> {
+    load_proof_with_limits(proof_path, &DeserializeLimits::default())
+}
+
+pub fn load_proof_with_limits(
+    proof_path: &str,
+    limits: &DeserializeLimits,
+) -> Result<Proof, Box<dyn std::error::Error>> {
+    let proof_file = BufReader::new(File::open(proof_path)?);
+    deserialize_from_with_limit(proof_file, "proof", limits.proof)
+}
+
+/// Identifier of a verifying key: the hex-encoded SHA-256 of its serialized bytes.
+///
+/// Relying parties use this to pick the right key for a presentation without parsing the key.
+pub fn vk_id(vk_bytes: &[u8]) -> String {
+    to_hex(&vk_digest(vk_bytes))
+}
+
+/// SHA-256 of a serialized verifying key, the binary form of [`vk_id`]
+pub fn vk_digest(vk_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(vk_bytes).into()
+}
@@ -0,0 +1,76 @@
+//! Verifier-side support for zkID presentations
+//!
+//! This crate contains everything a relying party needs to check a presentation produced by
+//! `ecdsa-spartan2`, without pulling in the witness generators or the circom tooling:
+//! - presentation parsing, a JSON/base64url envelope for text transports and a deterministic CBOR
+//!   bundle for ISO 18013-5 style transports
+//! - a canonical, documented proof encoding for verifiers outside Rust (`ENCODING.md`), with
+//!   optionally compressed points
+//! - verifying key and proof loading, with size limits for untrusted input
+//! - `comm_W_shared` linkage checking between the Prepare and Show proofs
+//! - inspection reports of encoded proofs, instances and presentations
+//! - policy verification over the public values of each proof
+//! - the relying-party side of an OpenID4VP presentation (`verifier`)
+//!
+//! It only depends on Spartan2, ff, group, serde, serde_json, ciborium, bincode, base64, sha2 and
+//! getrandom, so it builds for `wasm32-unknown-unknown` as well as for native relying-party
+//! backends. The `wasm` feature adds wasm-bindgen bindings for relying parties verifying
+//! presentations in the browser, the `compression` feature zstd compression of presentations and
+//! the `http` feature an axum stand-in serving the OpenID4VP relying party. The `test-utils`
+//! feature adds the stand-in circuits the tests of this crate and `ecdsa-spartan2` prove with.
+
+use spartan2::{
+    provider::T256HyraxEngine,
+    traits::{snark::R1CSSNARKTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+
+pub type E = T256HyraxEngine;
+pub type Scalar = <E as Engine>::Scalar;
+pub type Proof = R1CSSNARK<E>;
+pub type VerifierKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey;
+
+pub mod canonical;
+pub mod cbor;
+#[cfg(feature = "compression")]
+pub mod compression;
+pub mod date;
+pub mod envelope;
+pub mod hex;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod inspect;
+pub mod keys;
+pub mod limits;
+pub mod presentation;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+pub mod verifier;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Re-export commonly used types and functions
+pub use canonical::{
+    decode_instance, decode_proof, encode_instance, encode_instance_with, encode_proof,
+    encode_proof_with, CanonicalError, PointEncoding,
+};
+pub use cbor::{bundle_limit, inspect_bundle, BundleError, BundleInfo, PresentationBundle};
+pub use date::Date;
+pub use envelope::{EnvelopeError, EnvelopeSizes, PresentationEnvelope, VkIds};
+pub use hex::{scalar_to_hex, scalars_to_hex, to_hex};
+pub use inspect::{inspect, InspectError, KnownKey, Report};
+pub use keys::{
+    load_proof, load_proof_with_limits, load_verifying_key, load_verifying_key_with_limits,
+    proof_from_bytes, verifying_key_from_bytes, vk_digest, vk_id,
+};
+pub use limits::DeserializeLimits;
+pub use presentation::Presentation;
+pub use verifier::{
+    challenge_message_hash, vp_token, AuthorizationRequest, AuthorizationResponse, ErrorResponse,
+    Predicate, RelyingParty, ResponseError, VerificationResponse, DATE_TOLERANCE_DAYS,
+};
+pub use verify::{
+    check_linkage, shared_commitment_bytes, verify, verify_presentation, VerificationPolicy,
+    VerifiedPresentation, VerifyError,
+};
@@ -0,0 +1,154 @@
+//! Size limits for decoding untrusted proofs, verifying keys and instances.
+//!
+//! bincode allocates a vector from its length prefix, so a crafted input claiming a huge length
+//! could exhaust memory before any verification happens. Every decoder here reads at most a
+//! fixed number of bytes per artifact type and fails as soon as a prefix claims more.
+
+use std::io::Read;
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+
+const KIB: u64 = 1024;
+const MIB: u64 = 1024 * KIB;
+const GIB: u64 = 1024 * MIB;
+
+/// Largest encoding accepted for each artifact type, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializeLimits {
+    pub proof: u64,
+    pub verifying_key: u64,
+    /// A proven `SplitR1CSInstance`, as cached by the wallet between Prepare and reblind
+    pub instance: u64,
+}
+
+impl DeserializeLimits {
+    /// A presentation holds one Prepare and one Show proof
+    pub fn presentation(&self) -> u64 {
+        self.proof.saturating_mul(2)
+    }
+}
+
+impl Default for DeserializeLimits {
+    /// Enough for every measured Prepare variant up to an 8 KB payload (308 KB proofs, 1.5 GB
+    /// keys); derive tighter limits from the circuit parameters when they are known
+    fn default() -> Self {
+        Self {
+            proof: MIB,
+            verifying_key: 2 * GIB,
+            instance: 2 * MIB,
+        }
+    }
+}
+
+/// Options matching `bincode::serialize`, reading at most `limit` bytes
+fn options(limit: u64) -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+/// Decode an in-memory encoding of at most `limit` bytes.
+///
+/// Never reads past `bytes` either, so a length prefix larger than the input fails before
+/// anything is allocated.
+pub fn deserialize_with_limit<T: DeserializeOwned>(
+    bytes: &[u8],
+    artifact: &str,
+    limit: u64,
+) -> Result<T, Box<dyn std::error::Error>> {
+    if bytes.len() as u64 > limit {
+        return Err(format!(
+            "{artifact} is {} bytes, over the {limit}-byte limit",
+            bytes.len()
+        )
+        .into());
+    }
+    Ok(options(bytes.len() as u64).deserialize(bytes)?)
+}
+
+/// Decode from a reader (e.g. a file), reading at most `limit` bytes
+pub fn deserialize_from_with_limit<T: DeserializeOwned, R: Read>(
+    reader: R,
+    artifact: &str,
+    limit: u64,
+) -> Result<T, Box<dyn std::error::Error>> {
+    options(limit)
+        .deserialize_from(reader)
+        .map_err(|e| format!("Failed to decode {artifact}: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// bincode encoding of a `String` or `Vec<u8>` claiming `len` bytes, followed by `body`
+    fn length_prefixed(len: u64, body: &[u8]) -> Vec<u8> {
+        let mut bytes = len.to_le_bytes().to_vec();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn round_trips_within_the_limit() {
+        let value = vec![7u8; 100];
+        let bytes = bincode::serialize(&value).unwrap();
+        let limit = bytes.len() as u64;
+
+        let decoded: Vec<u8> = deserialize_with_limit(&bytes, "proof", limit).unwrap();
+        assert_eq!(decoded, value);
+        let decoded: Vec<u8> =
+            deserialize_from_with_limit(bytes.as_slice(), "proof", limit).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn over_limit_input_is_rejected_before_decoding() {
+        let bytes = bincode::serialize(&vec![7u8; 100]).unwrap();
+        let err = deserialize_with_limit::<Vec<u8>>(&bytes, "proof", 64).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("proof is {} bytes, over the 64-byte limit", bytes.len())
+        );
+
+        assert!(deserialize_from_with_limit::<Vec<u8>, _>(bytes.as_slice(), "proof", 64).is_err());
+    }
+
+    // Decoding a string from a reader sizes its buffer from the length prefix: without the limit
+    // these would try to allocate a terabyte or more before reading a byte of it
+
+    #[test]
+    fn huge_length_prefix_is_rejected_without_allocating() {
+        for len in [1 << 40, u64::MAX] {
+            let bytes = length_prefixed(len, b"short");
+
+            assert!(deserialize_with_limit::<String>(&bytes, "instance", MIB).is_err());
+            assert!(deserialize_with_limit::<Vec<u8>>(&bytes, "instance", MIB).is_err());
+
+            let err = deserialize_from_with_limit::<String, _>(bytes.as_slice(), "instance", MIB)
+                .unwrap_err();
+            assert!(err.to_string().starts_with("Failed to decode instance"));
+        }
+    }
+
+    #[test]
+    fn length_prefix_past_the_input_is_rejected_under_a_loose_limit() {
+        // Claims 100 bytes, carries 5, with a limit that would allow 100
+        let bytes = length_prefixed(100, b"short");
+        assert!(deserialize_with_limit::<String>(&bytes, "proof", GIB).is_err());
+        assert!(deserialize_from_with_limit::<String, _>(bytes.as_slice(), "proof", GIB).is_err());
+    }
+
+    #[test]
+    fn presentation_limit_covers_two_proofs_without_overflowing() {
+        let limits = DeserializeLimits::default();
+        assert_eq!(limits.presentation(), 2 * limits.proof);
+
+        let unbounded = DeserializeLimits {
+            proof: u64::MAX,
+            ..limits
+        };
+        assert_eq!(unbounded.presentation(), u64::MAX);
+    }
+}
@@ -0,0 +1,51 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    limits::{deserialize_with_limit, DeserializeLimits},
+    Proof,
+};
+
+/// A presentation as sent by the wallet to a relying party.
+///
+/// Both proofs must be the reblinded variants produced with the same shared blinds, so that
+/// their `comm_W_shared` commitments are equal.
+#[derive(Serialize, Deserialize)]
+pub struct Presentation {
+    pub prepare_proof: Proof,
+    pub show_proof: Proof,
+}
+
+impl Presentation {
+    pub fn new(prepare_proof: Proof, show_proof: Proof) -> Self {
+        Self {
+            prepare_proof,
+            show_proof,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Decode a presentation received from a wallet, rejecting encodings over the limit
+    pub fn from_bytes(
+        bytes: &[u8],
+        limits: &DeserializeLimits,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        deserialize_with_limit(bytes, "presentation", limits.presentation())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_bytes(&fs::read(path)?, &DeserializeLimits::default())
+    }
+}
@@ -0,0 +1,212 @@
+//! Small Spartan2 circuits standing in for Prepare and Show, so that tests can make real proofs
+//! and linked presentations in milliseconds, without the circom circuits. Enabled by the
+//! `test-utils` feature; `ecdsa-spartan2` proves the same stand-in in its prover tests.
+//!
+//! Both circuits commit to the same shared values and are reblinded with the same blinds, which
+//! is what links the Prepare and Show proofs of a real presentation.
+
+use std::sync::OnceLock;
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::Field;
+use spartan2::{
+    bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
+    r1cs::SplitR1CSInstance,
+    traits::{
+        circuit::SpartanCircuit, snark::R1CSSNARKTrait, transcript::TranscriptEngineTrait, Engine,
+    },
+    zk_spartan::R1CSSNARK,
+};
+
+use crate::{Presentation, Proof, Scalar, VerifierKey, E};
+
+pub type ProverKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey;
+
+/// Shared values of the fixture, standing in for the device key and the claim
+pub const SHARED: [u64; 4] = [3, 5, 7, 11];
+
+/// Proves knowledge of the shared values, whose product squared `rounds` times is optionally
+/// exposed as a public value, followed by the constants `exposed`
+#[derive(Debug, Clone)]
+pub struct ToyCircuit {
+    pub shared: Vec<Scalar>,
+    pub rounds: usize,
+    pub public: bool,
+    /// Public values standing in for the `ageAbove18` and `messageHash` of the Show circuit
+    pub exposed: Vec<Scalar>,
+}
+
+impl ToyCircuit {
+    /// Prepare stand-in: no public values, like the Prepare circuit
+    pub fn prepare(shared: &[u64]) -> Self {
+        Self {
+            shared: shared.iter().map(|&v| Scalar::from(v)).collect(),
+            rounds: 2,
+            public: false,
+            exposed: vec![],
+        }
+    }
+
+    /// Show stand-in: a different circuit over the same shared values, with one public value
+    pub fn show(shared: &[u64]) -> Self {
+        Self {
+            shared: shared.iter().map(|&v| Scalar::from(v)).collect(),
+            rounds: 5,
+            public: true,
+            exposed: vec![],
+        }
+    }
+
+    fn result(&self) -> Scalar {
+        let product = self.shared.iter().product::<Scalar>();
+        (0..self.rounds).fold(product, |acc, _| acc.square())
+    }
+}
+
+impl SpartanCircuit<E> for ToyCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let mut acc = AllocatedNum::alloc(cs.namespace(|| "one"), || Ok(Scalar::ONE))?;
+        for (i, value) in shared.iter().enumerate() {
+            acc = acc.mul(cs.namespace(|| format!("product_{i}")), value)?;
+        }
+        for i in 0..self.rounds {
+            acc = acc.square(cs.namespace(|| format!("square_{i}")))?;
+        }
+        if self.public {
+            acc.inputize(cs.namespace(|| "result"))?;
+        }
+        for (i, &value) in self.exposed.iter().enumerate() {
+            AllocatedNum::alloc(cs.namespace(|| format!("exposed_{i}")), || Ok(value))?
+                .inputize(cs.namespace(|| format!("exposed_{i}_input")))?;
+        }
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        let result = self.public.then(|| self.result());
+        Ok(result.into_iter().chain(self.exposed.clone()).collect())
+    }
+
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        self.shared
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("shared_{i}")), || Ok(value))
+            })
+            .collect()
+    }
+
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _: &mut CS,
+        _: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+pub fn setup(circuit: &ToyCircuit) -> (ProverKey, VerifierKey) {
+    R1CSSNARK::<E>::setup(circuit.clone()).expect("toy setup")
+}
+
+/// A reblinded proof and its instance
+pub struct Proven {
+    pub instance: SplitR1CSInstance<E>,
+    pub proof: Proof,
+}
+
+/// Prove `circuit`, then reblind it with blinds derived from `seed`, as the wallet does for each
+/// presentation: two circuits proven with the same shared values and seed are linked
+pub fn prove(circuit: &ToyCircuit, pk: &ProverKey, seed: u64) -> Proven {
+    let public_values = circuit.public_values().unwrap();
+    let transcript = || {
+        let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
+        transcript.absorb(b"vk", &pk.vk_digest);
+        transcript.absorb(b"public_values", &public_values.as_slice());
+        transcript
+    };
+
+    let mut prep = R1CSSNARK::<E>::prep_prove(pk, circuit.clone(), false).expect("prep_prove");
+    let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
+        &mut prep.ps,
+        &pk.S,
+        &pk.ck,
+        circuit,
+        false,
+        &mut transcript(),
+    )
+    .expect("toy witness");
+
+    let blinds: Vec<Scalar> = (0..instance.num_shared_rows() as u64)
+        .map(|i| Scalar::from(seed) + Scalar::from(i))
+        .collect();
+    let mut transcript = transcript();
+    let (instance, witness) = SatisfyingAssignment::reblind_r1cs_instance_and_witness(
+        &blinds,
+        instance,
+        witness,
+        &pk.ck,
+        &mut transcript,
+    )
+    .expect("toy reblind");
+    let proof =
+        R1CSSNARK::<E>::prove_inner(pk, &instance, &witness, &mut transcript).expect("toy prove");
+    Proven { instance, proof }
+}
+
+/// Keys and one linked presentation, shared by the tests of a binary
+pub struct Fixture {
+    pub prepare_pk: ProverKey,
+    pub show_pk: ProverKey,
+    pub prepare_vk: VerifierKey,
+    pub show_vk: VerifierKey,
+    pub prepare_vk_bytes: Vec<u8>,
+    pub show_vk_bytes: Vec<u8>,
+    pub prepare: Proven,
+    pub show: Proven,
+}
+
+impl Fixture {
+    pub fn presentation(&self) -> Presentation {
+        Presentation::new(self.prepare.proof.clone(), self.show.proof.clone())
+    }
+
+    /// Public value of the Show stand-in
+    pub fn show_public_values(&self) -> Vec<Scalar> {
+        ToyCircuit::show(&SHARED).public_values().unwrap()
+    }
+}
+
+pub fn fixture() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| {
+        let (prepare_pk, prepare_vk) = setup(&ToyCircuit::prepare(&SHARED));
+        let (show_pk, show_vk) = setup(&ToyCircuit::show(&SHARED));
+        let prepare = prove(&ToyCircuit::prepare(&SHARED), &prepare_pk, 1);
+        let show = prove(&ToyCircuit::show(&SHARED), &show_pk, 1);
+        Fixture {
+            prepare_vk_bytes: bincode::serialize(&prepare_vk).unwrap(),
+            show_vk_bytes: bincode::serialize(&show_vk).unwrap(),
+            prepare_pk,
+            show_pk,
+            prepare_vk,
+            show_vk,
+            prepare,
+            show,
+        }
+    })
+}
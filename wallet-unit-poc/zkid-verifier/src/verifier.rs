@@ -0,0 +1,381 @@
+//! Relying-party side of an OpenID4VP presentation of zkID proofs.
+//!
+//! [`RelyingParty::authorization_request`] issues a request with a fresh `nonce` and `state` and
+//! the predicate to prove. The wallet answers with `direct_post`: an [`AuthorizationResponse`]
+//! whose `vp_token` is the unpadded base64url of a [`PresentationBundle`] carrying that nonce.
+//! [`RelyingParty::handle_response`] checks the nonce, verifies both proofs, their linkage and the
+//! predicate's policy, and returns the body of the OpenID4VP response: a
+//! [`VerificationResponse`] or, on rejection, an [`ErrorResponse`].
+//!
+//! The Show circuit exposes the `messageHash` of the challenge signed by the device key, so the
+//! policy of a request binds the Show proof to its nonce: a presentation made for one request
+//! does not verify for another. It also exposes the date its `ageAbove18` is computed on, which
+//! must be the relying party's within [`DATE_TOLERANCE_DAYS`]. Each request is also accepted at
+//! most once, within [`DEFAULT_REQUEST_TTL`].
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    cbor::{BundleError, PresentationBundle},
+    date::Date,
+    envelope::VkIds,
+    keys::{verifying_key_from_bytes, vk_digest, vk_id},
+    limits::DeserializeLimits,
+    verify::{verify_presentation, VerificationPolicy, VerifyError},
+    Scalar, VerifierKey,
+};
+
+/// `response_type` of the requests: the presentation comes back as a `vp_token`
+pub const RESPONSE_TYPE: &str = "vp_token";
+
+/// `response_mode` of the requests: the wallet posts the response to `response_uri`
+pub const RESPONSE_MODE: &str = "direct_post";
+
+/// How long an authorization request can be answered
+pub const DEFAULT_REQUEST_TTL: Duration = Duration::from_secs(300);
+
+/// How many days the date of a Show proof may be from the relying party's: a wallet dates its
+/// proof in its own time zone, up to a day away from the relying party's
+pub const DATE_TOLERANCE_DAYS: u32 = 1;
+
+/// Bytes of randomness in a nonce and in a state
+const NONCE_BYTES: usize = 32;
+const STATE_BYTES: usize = 16;
+
+/// Order of the P-256 group, big-endian
+const P256_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+/// What a presentation proves about the credential
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Predicate {
+    /// The holder is 18 or older on the date of the Show proof. The circuit computes
+    /// `ageAbove18` from the credential's birth date and that date, and exposes both as public
+    /// values; the policy requires `ageAbove18 = 1` and the date to be the relying party's within
+    /// [`DATE_TOLERANCE_DAYS`], so a holder can claim at most that many days of age they do not
+    /// have
+    #[serde(rename = "age_over_18")]
+    AgeOver18,
+}
+
+impl Predicate {
+    /// Public values both proofs must return for the predicate to hold in a presentation answering
+    /// `challenge` on `date`, the relying party's date. The Show proof returns
+    /// `[ageAbove18, messageHash, currentYear, currentMonth, currentDay]`.
+    pub fn policy(self, challenge: &[u8], date: Date) -> VerificationPolicy {
+        let age_above_18 = match self {
+            Predicate::AgeOver18 => Scalar::ONE,
+        };
+        let mut show_public_values = vec![age_above_18, challenge_message_hash(challenge)];
+        show_public_values.extend(date.to_scalars());
+        VerificationPolicy {
+            prepare_public_values: vec![],
+            show_public_values,
+            show_date_tolerance_days: Some(DATE_TOLERANCE_DAYS),
+        }
+    }
+}
+
+/// `messageHash` public value of a Show proof whose device signature is over `challenge`:
+/// `sha256(challenge)` reduced modulo the P-256 group order
+pub fn challenge_message_hash(challenge: &[u8]) -> Scalar {
+    let mut hash: [u8; 32] = Sha256::digest(challenge).into();
+    // Below 2^256, hence below twice the order: one subtraction reduces it
+    if hash >= P256_ORDER {
+        let mut borrow = 0;
+        for (byte, order) in hash.iter_mut().zip(P256_ORDER).rev() {
+            let (difference, under) = byte.overflowing_sub(order);
+            let (difference, under_borrow) = difference.overflowing_sub(borrow);
+            *byte = difference;
+            borrow = u8::from(under || under_borrow);
+        }
+    }
+
+    // `to_repr` is little-endian; the order is below the base field modulus
+    hash.reverse();
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    repr.as_mut().copy_from_slice(&hash);
+    Option::from(Scalar::from_repr(repr)).expect("reduced hash is below the field modulus")
+}
+
+/// Authorization request sent to the wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationRequest {
+    pub client_id: String,
+    pub response_type: String,
+    pub response_mode: String,
+    pub response_uri: String,
+    /// Challenge the presentation must carry, unpadded base64url
+    pub nonce: String,
+    /// Identifies the request in the response
+    pub state: String,
+    pub predicate: Predicate,
+    /// Verifying keys the proofs must verify against
+    pub vk_id: VkIds,
+}
+
+/// Response the wallet posts to `response_uri`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationResponse {
+    /// Unpadded base64url CBOR bundle, see [`vp_token`]
+    pub vp_token: String,
+    pub state: String,
+}
+
+/// Body returned to the wallet when the presentation is accepted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationResponse {
+    pub state: String,
+    pub predicate: Predicate,
+    pub verified: bool,
+}
+
+/// OpenID4VP error body returned when the presentation is rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub error_description: String,
+}
+
+#[derive(Debug)]
+pub enum VerifierError {
+    /// A verifying key could not be loaded
+    VerifyingKey(String),
+    /// The system random number generator failed
+    Random(String),
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::VerifyingKey(e) => write!(f, "invalid verifying key: {e}"),
+            VerifierError::Random(e) => write!(f, "failed to sample a nonce: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifierError {}
+
+#[derive(Debug)]
+pub enum ResponseError {
+    /// No pending request has this state: unknown, already answered or expired
+    UnknownState,
+    /// The `vp_token` is not base64url
+    VpToken(base64::DecodeError),
+    Bundle(BundleError),
+    /// The bundle answers another request
+    NonceMismatch,
+    /// The proofs were made for other verifying keys
+    VerifyingKeyMismatch {
+        circuit: &'static str,
+    },
+    Verification(VerifyError),
+}
+
+impl ResponseError {
+    /// OpenID4VP error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResponseError::Verification(_) => "access_denied",
+            _ => "invalid_request",
+        }
+    }
+
+    pub fn to_response(&self) -> ErrorResponse {
+        ErrorResponse {
+            error: self.code().to_string(),
+            error_description: self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseError::UnknownState => {
+                write!(f, "no pending request with this state, or it expired")
+            }
+            ResponseError::VpToken(e) => write!(f, "vp_token is not unpadded base64url: {e}"),
+            ResponseError::Bundle(e) => write!(f, "invalid vp_token: {e}"),
+            ResponseError::NonceMismatch => {
+                write!(f, "the presentation does not carry the request nonce")
+            }
+            ResponseError::VerifyingKeyMismatch { circuit } => {
+                write!(f, "{circuit} proof is for another verifying key")
+            }
+            ResponseError::Verification(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+struct PendingRequest {
+    nonce: String,
+    predicate: Predicate,
+    expires_at: Instant,
+}
+
+/// A relying party accepting presentations for one pair of Prepare and Show keys
+pub struct RelyingParty {
+    client_id: String,
+    response_uri: String,
+    prepare_vk: VerifierKey,
+    show_vk: VerifierKey,
+    vk_id: VkIds,
+    prepare_vk_digest: [u8; 32],
+    show_vk_digest: [u8; 32],
+    limits: DeserializeLimits,
+    request_ttl: Duration,
+    /// Date Show proofs are checked against, today's UTC date when `None`
+    date: Option<Date>,
+    pending: Mutex<HashMap<String, PendingRequest>>,
+}
+
+impl RelyingParty {
+    /// Relying party identified by `client_id`, receiving responses at `response_uri`, for the
+    /// serialized Prepare and Show verifying keys. `limits` bound the decoding of both the keys
+    /// and the presentations, so they must fit the larger circuit.
+    pub fn new(
+        client_id: impl Into<String>,
+        response_uri: impl Into<String>,
+        prepare_vk_bytes: &[u8],
+        show_vk_bytes: &[u8],
+        limits: DeserializeLimits,
+    ) -> Result<Self, VerifierError> {
+        let load = |bytes: &[u8]| {
+            verifying_key_from_bytes(bytes, &limits)
+                .map_err(|e| VerifierError::VerifyingKey(e.to_string()))
+        };
+        Ok(Self {
+            client_id: client_id.into(),
+            response_uri: response_uri.into(),
+            prepare_vk: load(prepare_vk_bytes)?,
+            show_vk: load(show_vk_bytes)?,
+            vk_id: VkIds {
+                prepare: vk_id(prepare_vk_bytes),
+                show: vk_id(show_vk_bytes),
+            },
+            prepare_vk_digest: vk_digest(prepare_vk_bytes),
+            show_vk_digest: vk_digest(show_vk_bytes),
+            limits,
+            request_ttl: DEFAULT_REQUEST_TTL,
+            date: None,
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Override how long requests can be answered
+    pub fn with_request_ttl(mut self, request_ttl: Duration) -> Self {
+        self.request_ttl = request_ttl;
+        self
+    }
+
+    /// Check Show proofs against `date` instead of today's UTC date
+    pub fn with_date(mut self, date: Date) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// Start a presentation: a request with a fresh nonce and state, pending until answered
+    pub fn authorization_request(
+        &self,
+        predicate: Predicate,
+    ) -> Result<AuthorizationRequest, VerifierError> {
+        let nonce = random_base64url(NONCE_BYTES)?;
+        let state = random_base64url(STATE_BYTES)?;
+        let now = Instant::now();
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, request| request.expires_at > now);
+        pending.insert(
+            state.clone(),
+            PendingRequest {
+                nonce: nonce.clone(),
+                predicate,
+                expires_at: now + self.request_ttl,
+            },
+        );
+
+        Ok(AuthorizationRequest {
+            client_id: self.client_id.clone(),
+            response_type: RESPONSE_TYPE.to_string(),
+            response_mode: RESPONSE_MODE.to_string(),
+            response_uri: self.response_uri.clone(),
+            nonce,
+            state,
+            predicate,
+            vk_id: self.vk_id.clone(),
+        })
+    }
+
+    /// Check a wallet response against its pending request, which is consumed whatever the
+    /// outcome
+    pub fn handle_response(
+        &self,
+        response: &AuthorizationResponse,
+    ) -> Result<VerificationResponse, ResponseError> {
+        let request = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&response.state)
+            .filter(|request| request.expires_at > Instant::now())
+            .ok_or(ResponseError::UnknownState)?;
+
+        let bytes = URL_SAFE_NO_PAD
+            .decode(&response.vp_token)
+            .map_err(ResponseError::VpToken)?;
+        let bundle =
+            PresentationBundle::from_cbor(&bytes, &self.limits).map_err(ResponseError::Bundle)?;
+        if bundle.nonce != request.nonce.as_bytes() {
+            return Err(ResponseError::NonceMismatch);
+        }
+        if bundle.prepare_vk_id != self.prepare_vk_digest {
+            return Err(ResponseError::VerifyingKeyMismatch { circuit: "Prepare" });
+        }
+        if bundle.show_vk_id != self.show_vk_digest {
+            return Err(ResponseError::VerifyingKeyMismatch { circuit: "Show" });
+        }
+
+        verify_presentation(
+            &bundle.presentation(),
+            &self.prepare_vk,
+            &self.show_vk,
+            &request.predicate.policy(
+                request.nonce.as_bytes(),
+                self.date.unwrap_or_else(Date::today),
+            ),
+        )
+        .map_err(ResponseError::Verification)?;
+
+        Ok(VerificationResponse {
+            state: response.state.clone(),
+            predicate: request.predicate,
+            verified: true,
+        })
+    }
+}
+
+/// `vp_token` for an encoded bundle, whose nonce must be the UTF-8 bytes of the request `nonce`
+pub fn vp_token(bundle_cbor: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bundle_cbor)
+}
+
+fn random_base64url(len: usize) -> Result<String, VerifierError> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|e| VerifierError::Random(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use spartan2::{errors::SpartanError, traits::snark::R1CSSNARKTrait};
+
+use crate::{date::Date, presentation::Presentation, Proof, Scalar, VerifierKey, E};
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The Prepare proof does not verify against the Prepare verifying key
+    PrepareProof(SpartanError),
+    /// The Show proof does not verify against the Show verifying key
+    ShowProof(SpartanError),
+    /// A proof verified, but its public values are not the ones required by the policy
+    PublicValues { circuit: &'static str },
+    /// A proof carries no `comm_W_shared`, so it cannot be linked to the other one
+    MissingSharedCommitment { circuit: &'static str },
+    /// The Prepare and Show proofs commit to different shared values
+    LinkageMismatch,
+    /// A proof component could not be encoded for comparison
+    Encoding(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::PrepareProof(e) => write!(f, "Prepare proof verification failed: {e}"),
+            VerifyError::ShowProof(e) => write!(f, "Show proof verification failed: {e}"),
+            VerifyError::PublicValues { circuit } => {
+                write!(f, "{circuit} public values do not satisfy the policy")
+            }
+            VerifyError::MissingSharedCommitment { circuit } => {
+                write!(f, "{circuit} proof has no comm_W_shared")
+            }
+            VerifyError::LinkageMismatch => {
+                write!(f, "Prepare and Show proofs have different comm_W_shared")
+            }
+            VerifyError::Encoding(reason) => {
+                write!(f, "Failed to encode proof component: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Public values a relying party requires from each proof of a presentation.
+///
+/// Prepare exposes no public IO; Show exposes `ageAbove18`, the `messageHash` of the challenge
+/// the device key signed and the date `ageAbove18` is computed on, see
+/// [`Predicate::policy`](crate::Predicate::policy). The default policy expects both lists to be
+/// empty.
+#[derive(Debug, Clone, Default)]
+pub struct VerificationPolicy {
+    pub prepare_public_values: Vec<Scalar>,
+    pub show_public_values: Vec<Scalar>,
+    /// When set, the last three Show public values are a [`Date`], which may be up to this many
+    /// days before or after the one of `show_public_values`; the other values must match exactly
+    pub show_date_tolerance_days: Option<u32>,
+}
+
+impl VerificationPolicy {
+    fn accepts_show(&self, values: &[Scalar]) -> bool {
+        let Some(tolerance) = self.show_date_tolerance_days else {
+            return values == self.show_public_values;
+        };
+        let split = |values: &[Scalar]| {
+            let at = values.len().checked_sub(3)?;
+            let (rest, date) = values.split_at(at);
+            Some((rest, Date::from_scalars(date)?))
+        };
+        match (split(values), split(&self.show_public_values)) {
+            (Some((rest, date)), Some((expected_rest, expected_date))) => {
+                rest == expected_rest
+                    && date.days_since(expected_date).unsigned_abs() <= u64::from(tolerance)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Outcome of a successful presentation verification
+#[derive(Debug, Clone)]
+pub struct VerifiedPresentation {
+    /// Serialized `comm_W_shared` common to both proofs
+    pub shared_commitment: Vec<u8>,
+    pub prepare_public_values: Vec<Scalar>,
+    pub show_public_values: Vec<Scalar>,
+}
+
+/// Verify a single proof and return its public values
+pub fn verify(proof: &Proof, vk: &VerifierKey) -> Result<Vec<Scalar>, SpartanError> {
+    <Proof as R1CSSNARKTrait<E>>::verify(proof, vk)
+}
+
+/// Serialized `comm_W_shared` of a proof, used to compare commitments across circuits
+pub fn shared_commitment_bytes(
+    proof: &Proof,
+    circuit: &'static str,
+) -> Result<Vec<u8>, VerifyError> {
+    let comm_w_shared = proof
+        .comm_W_shared()
+        .ok_or(VerifyError::MissingSharedCommitment { circuit })?;
+    bincode::serialize(&comm_w_shared).map_err(|e| VerifyError::Encoding(e.to_string()))
+}
+
+/// Check that the Prepare and Show proofs were produced over the same shared witness
+/// commitment, i.e. that they talk about the same credential and device key.
+pub fn check_linkage(prepare_proof: &Proof, show_proof: &Proof) -> Result<Vec<u8>, VerifyError> {
+    let prepare_shared = shared_commitment_bytes(prepare_proof, "Prepare")?;
+    let show_shared = shared_commitment_bytes(show_proof, "Show")?;
+
+    if prepare_shared != show_shared {
+        return Err(VerifyError::LinkageMismatch);
+    }
+
+    Ok(prepare_shared)
+}
+
+/// Verify both proofs of a presentation, their linkage, and the policy on their public values
+pub fn verify_presentation(
+    presentation: &Presentation,
+    prepare_vk: &VerifierKey,
+    show_vk: &VerifierKey,
+    policy: &VerificationPolicy,
+) -> Result<VerifiedPresentation, VerifyError> {
+    let prepare_public_values =
+        verify(&presentation.prepare_proof, prepare_vk).map_err(VerifyError::PrepareProof)?;
+    let show_public_values =
+        verify(&presentation.show_proof, show_vk).map_err(VerifyError::ShowProof)?;
+
+    if prepare_public_values != policy.prepare_public_values {
+        return Err(VerifyError::PublicValues { circuit: "Prepare" });
+    }
+    if !policy.accepts_show(&show_public_values) {
+        return Err(VerifyError::PublicValues { circuit: "Show" });
+    }
+
+    let shared_commitment = check_linkage(&presentation.prepare_proof, &presentation.show_proof)?;
+
+    Ok(VerifiedPresentation {
+        shared_commitment,
+        prepare_public_values,
+        show_public_values,
+    })
+}
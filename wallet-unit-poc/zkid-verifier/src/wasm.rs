@@ -0,0 +1,133 @@
+//! wasm-bindgen bindings for relying parties verifying presentations client-side.
+//!
+//! Every binding takes raw bytes (as produced by `Presentation::to_bytes` and the `.key` files
+//! written by `ecdsa-spartan2`) and returns a plain JS object shaped like [`VerificationResult`];
+//! failures are reported in the result instead of being thrown.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    date::Date,
+    hex::{scalars_to_hex, to_hex},
+    keys::{proof_from_bytes, verifying_key_from_bytes},
+    limits::DeserializeLimits,
+    presentation::Presentation,
+    verifier::Predicate,
+    verify::{verify, verify_presentation},
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationResult {
+    pub verified: bool,
+    /// Human-readable reason when `verified` is false
+    pub error: Option<String>,
+    /// Hex-encoded `comm_W_shared` common to both proofs of a presentation
+    pub shared_commitment: Option<String>,
+    /// Hex-encoded public values of the Prepare proof (or of the single verified proof)
+    pub prepare_public_values: Vec<String>,
+    /// Hex-encoded public values of the Show proof
+    pub show_public_values: Vec<String>,
+}
+
+impl VerificationResult {
+    fn failed(error: impl ToString) -> Self {
+        Self {
+            verified: false,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn into_js(self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self).unwrap_or(JsValue::NULL)
+    }
+}
+
+/// Verify a full presentation answering `challenge`: both proofs, their `comm_W_shared` linkage
+/// and the [`Predicate::AgeOver18`] policy for `challenge` on their public values. `year`,
+/// `month` and `day` are the relying party's date, which the browser has and wasm does not.
+#[wasm_bindgen(js_name = verifyPresentation)]
+pub fn verify_presentation_bytes(
+    presentation: &[u8],
+    prepare_vk: &[u8],
+    show_vk: &[u8],
+    challenge: &[u8],
+    year: u32,
+    month: u32,
+    day: u32,
+) -> JsValue {
+    let Some(date) = Date::new(year, month, day) else {
+        return VerificationResult::failed(format!("Invalid date {year}-{month}-{day}")).into_js();
+    };
+    let limits = DeserializeLimits::default();
+    let presentation = match Presentation::from_bytes(presentation, &limits) {
+        Ok(presentation) => presentation,
+        Err(e) => {
+            return VerificationResult::failed(format!("Invalid presentation: {e}")).into_js()
+        }
+    };
+    let prepare_vk = match verifying_key_from_bytes(prepare_vk, &limits) {
+        Ok(vk) => vk,
+        Err(e) => {
+            return VerificationResult::failed(format!("Invalid Prepare verifying key: {e}"))
+                .into_js()
+        }
+    };
+    let show_vk = match verifying_key_from_bytes(show_vk, &limits) {
+        Ok(vk) => vk,
+        Err(e) => {
+            return VerificationResult::failed(format!("Invalid Show verifying key: {e}")).into_js()
+        }
+    };
+
+    match verify_presentation(
+        &presentation,
+        &prepare_vk,
+        &show_vk,
+        &Predicate::AgeOver18.policy(challenge, date),
+    ) {
+        Ok(verified) => VerificationResult {
+            verified: true,
+            error: None,
+            shared_commitment: Some(to_hex(&verified.shared_commitment)),
+            prepare_public_values: scalars_to_hex(&verified.prepare_public_values),
+            show_public_values: scalars_to_hex(&verified.show_public_values),
+        }
+        .into_js(),
+        Err(e) => VerificationResult::failed(e).into_js(),
+    }
+}
+
+/// Verify a single Prepare or Show proof against its verifying key
+#[wasm_bindgen(js_name = verifyProof)]
+pub fn verify_proof_bytes(proof: &[u8], vk: &[u8]) -> JsValue {
+    let limits = DeserializeLimits::default();
+    let proof = match proof_from_bytes(proof, &limits) {
+        Ok(proof) => proof,
+        Err(e) => return VerificationResult::failed(format!("Invalid proof: {e}")).into_js(),
+    };
+    let vk = match verifying_key_from_bytes(vk, &limits) {
+        Ok(vk) => vk,
+        Err(e) => {
+            return VerificationResult::failed(format!("Invalid verifying key: {e}")).into_js()
+        }
+    };
+
+    match verify(&proof, &vk) {
+        Ok(public_values) => VerificationResult {
+            verified: true,
+            prepare_public_values: scalars_to_hex(&public_values),
+            ..Default::default()
+        }
+        .into_js(),
+        Err(e) => VerificationResult::failed(e).into_js(),
+    }
+}
+
+/// Identifier of a verifying key, see [`crate::vk_id`]
+#[wasm_bindgen(js_name = vkId)]
+pub fn vk_id_bytes(vk: &[u8]) -> String {
+    crate::keys::vk_id(vk)
+}
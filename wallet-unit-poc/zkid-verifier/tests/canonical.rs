@@ -0,0 +1,297 @@
+//! Golden vectors for the canonical encoding in `ENCODING.md`.
+//!
+//! Each vector pins one rule of the spec with a structure shaped like the proof components
+//! (scalars as fixed-size arrays, commitment vectors, optional and enum fields), so an
+//! implementation in another language can be checked against the same bytes. Points are checked
+//! on a hand-derived instance, and real (toy circuit) proofs and instances must round trip.
+
+use group::{Curve, GroupEncoding, UncompressedEncoding};
+use serde::{Deserialize, Serialize};
+use spartan2::traits::Engine;
+use zkid_verifier::{
+    canonical::{
+        decode_instance, decode_proof, encode_instance, encode_instance_with, encode_proof_with,
+        field_sizes, from_bytes, from_bytes_with, to_bytes, to_bytes_with, CanonicalError, Layout,
+        PointEncoding, INSTANCE_LAYOUT, MAGIC, VERSION,
+    },
+    test_utils::fixture,
+    verify, E,
+};
+
+type Point = <E as Engine>::GE;
+
+/// Stand-in for a proof component: a commitment vector, an evaluation and an optional blind
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Component {
+    flag: bool,
+    rows: u64,
+    comm: Vec<[u8; 4]>,
+    blind: Option<u16>,
+    kind: Kind,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Kind {
+    Empty,
+    Tagged(u8),
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    let s: String = s.split_whitespace().collect();
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn component() -> Component {
+    Component {
+        flag: true,
+        rows: 0x0102,
+        comm: vec![[1, 2, 3, 4], [5, 6, 7, 8]],
+        blind: Some(0xbeef),
+        kind: Kind::Tagged(9),
+    }
+}
+
+const COMPONENT_HEX: &str = "01 0201000000000000 02000000 01020304 05060708 01 efbe 01000000 09";
+
+/// x and y of the T-256 generator, big-endian
+const GENERATOR_X: &str = "0000000000000000000000000000000000000000000000000000000000000003";
+const GENERATOR_Y: &str = "5a6dd32df58708e64e97345cbe66600decd9d538a351bb3c30b4954925b1f02d";
+
+/// Instance with `comm_W_shared = [G]`, no `comm_W_precommitted`, `comm_W_rest = [identity]`,
+/// `public_values = [1]` and no challenges, with `points` points
+fn instance_hex(points: PointEncoding) -> String {
+    let zero = "00".repeat(32);
+    let one = format!("01{}", "00".repeat(31));
+    let (kind, generator, identity) = match points {
+        PointEncoding::Uncompressed => {
+            ("02", format!("{GENERATOR_X}{GENERATOR_Y}"), zero.repeat(2))
+        }
+        PointEncoding::Compressed => ("82", format!("80{GENERATOR_X}"), format!("40{zero}")),
+    };
+    format!(
+        "5a4b4944 01 {kind} 01 01000000 {generator} 00 01000000 {identity} 01000000 {one} 00000000"
+    )
+}
+
+#[test]
+fn golden_struct() {
+    assert_eq!(to_bytes(&component()).unwrap(), hex(COMPONENT_HEX));
+    assert_eq!(
+        from_bytes::<Component>(&hex(COMPONENT_HEX)).unwrap(),
+        component()
+    );
+}
+
+#[test]
+fn golden_leaves() {
+    // Integers are fixed-width little-endian
+    assert_eq!(to_bytes(&0x01020304u32).unwrap(), hex("04030201"));
+    assert_eq!(to_bytes(&-2i16).unwrap(), hex("feff"));
+    // Fixed-size arrays, as used for scalars, carry no length
+    assert_eq!(to_bytes(&[0xaau8; 3]).unwrap(), hex("aaaaaa"));
+    // Vectors and strings carry a u32 count
+    assert_eq!(to_bytes(&Vec::<u64>::new()).unwrap(), hex("00000000"));
+    assert_eq!(to_bytes("zk").unwrap(), hex("02000000 7a6b"));
+    assert_eq!(to_bytes(&None::<u8>).unwrap(), hex("00"));
+    assert_eq!(to_bytes(&Kind::Empty).unwrap(), hex("00000000"));
+}
+
+#[test]
+fn field_sizes_cover_the_encoding() {
+    let sizes = field_sizes(&component(), Layout::Plain).unwrap();
+    assert_eq!(
+        sizes,
+        vec![
+            ("flag", 1),
+            ("rows", 8),
+            ("comm", 12),
+            ("blind", 3),
+            ("kind", 5)
+        ]
+    );
+    let total: usize = sizes.iter().map(|(_, size)| size).sum();
+    assert_eq!(total, hex(COMPONENT_HEX).len());
+}
+
+#[test]
+fn golden_header() {
+    assert_eq!(MAGIC, *b"ZKID");
+    assert_eq!(VERSION, 1);
+}
+
+#[test]
+fn rejects_non_canonical_input() {
+    let bytes = hex(COMPONENT_HEX);
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        from_bytes::<Component>(&trailing),
+        Err(CanonicalError::TrailingBytes(1))
+    );
+
+    let mut bad_bool = bytes.clone();
+    bad_bool[0] = 2;
+    assert_eq!(
+        from_bytes::<Component>(&bad_bool),
+        Err(CanonicalError::InvalidTag(2))
+    );
+
+    assert_eq!(
+        from_bytes::<Component>(&bytes[..bytes.len() - 1]),
+        Err(CanonicalError::UnexpectedEnd)
+    );
+
+    // A count past the end of the input is rejected before anything is allocated
+    assert_eq!(
+        from_bytes::<Vec<u8>>(&hex("ffffffff 00")),
+        Err(CanonicalError::InvalidLength(u32::MAX))
+    );
+    assert!(to_bytes(&1.0f64).is_err());
+}
+
+#[test]
+fn rejects_wrong_header() {
+    assert!(matches!(
+        decode_proof(b"ZKIX\x01\x01"),
+        Err(CanonicalError::Header(_))
+    ));
+    assert!(matches!(
+        decode_proof(b"ZKID\x02\x01"),
+        Err(CanonicalError::Header(_))
+    ));
+    // An instance header where a proof is expected
+    assert!(matches!(
+        decode_proof(b"ZKID\x01\x02"),
+        Err(CanonicalError::Header(_))
+    ));
+    assert!(matches!(
+        decode_proof(b"ZKID\x01\x82"),
+        Err(CanonicalError::Header(_))
+    ));
+    assert!(matches!(
+        decode_proof(b"ZKID"),
+        Err(CanonicalError::UnexpectedEnd)
+    ));
+}
+
+#[test]
+fn real_proofs_round_trip_in_both_point_forms() {
+    let fixture = fixture();
+    for points in [PointEncoding::Uncompressed, PointEncoding::Compressed] {
+        let bytes = encode_proof_with(&fixture.show.proof, points).unwrap();
+        let proof = decode_proof(&bytes).expect("decoded proof");
+        verify(&proof, &fixture.show_vk).expect("decoded proof verifies");
+        assert_eq!(encode_proof_with(&proof, points).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn shared_commitment_points_follow_the_header() {
+    let instance = &fixture().show.instance;
+    // bincode writes `comm_W_shared` as a `Some` tag, a u64 count and compressed points
+    let bincode = bincode::serialize(instance).unwrap();
+    let count = u64::from_le_bytes(bincode[1..9].try_into().unwrap()) as usize;
+    assert!(count > 0);
+    let shared: Vec<Point> = bincode[9..9 + 33 * count]
+        .chunks(33)
+        .map(|chunk| {
+            let mut repr = <Point as GroupEncoding>::Repr::default();
+            repr.as_mut().copy_from_slice(chunk);
+            Point::from_bytes(&repr).unwrap()
+        })
+        .collect();
+
+    for points in [PointEncoding::Uncompressed, PointEncoding::Compressed] {
+        let bytes = encode_instance_with(instance, points).unwrap();
+        // Header, `Some` tag, then the u32 count of `comm`
+        assert_eq!(bytes[6], 1);
+        assert_eq!(bytes[7..11], (count as u32).to_le_bytes());
+        let size = match points {
+            PointEncoding::Uncompressed => 64,
+            PointEncoding::Compressed => 33,
+        };
+        for (i, point) in shared.iter().enumerate() {
+            let expected = match points {
+                PointEncoding::Uncompressed => {
+                    point.to_affine().to_uncompressed().as_ref().to_vec()
+                }
+                PointEncoding::Compressed => point.to_bytes().as_ref().to_vec(),
+            };
+            assert_eq!(bytes[11 + size * i..11 + size * (i + 1)], expected[..]);
+        }
+        let decoded = decode_instance(&bytes).expect("decoded instance");
+        assert_eq!(encode_instance_with(&decoded, points).unwrap(), bytes);
+
+        // A point off the curve, or a compressed point with an unused flag set, is rejected
+        let mut corrupt = bytes.clone();
+        match points {
+            PointEncoding::Uncompressed => corrupt[11 + size - 1] ^= 1,
+            PointEncoding::Compressed => corrupt[11] ^= 0x20,
+        }
+        assert!(matches!(
+            decode_instance(&corrupt),
+            Err(CanonicalError::InvalidPoint)
+        ));
+    }
+}
+
+#[test]
+fn layout_changes_fail_loudly() {
+    // An instance whose first field was renamed
+    #[derive(Debug, Serialize, Deserialize)]
+    #[allow(non_snake_case)]
+    struct SplitR1CSInstance {
+        comm_W_shard: Option<u8>,
+    }
+
+    let renamed = SplitR1CSInstance { comm_W_shard: None };
+    assert!(matches!(
+        to_bytes_with(&renamed, INSTANCE_LAYOUT, PointEncoding::Uncompressed),
+        Err(CanonicalError::Layout(_))
+    ));
+
+    let bytes =
+        encode_instance_with(&fixture().show.instance, PointEncoding::Uncompressed).unwrap();
+    assert!(matches!(
+        from_bytes_with::<SplitR1CSInstance>(
+            &bytes[6..],
+            INSTANCE_LAYOUT,
+            PointEncoding::Uncompressed
+        ),
+        Err(CanonicalError::Layout(_))
+    ));
+
+    // Bytes where a point is expected
+    assert!(matches!(
+        to_bytes_with(
+            &vec![1u8],
+            Layout::Seq(&Layout::Point),
+            PointEncoding::Uncompressed
+        ),
+        Err(CanonicalError::Layout(_))
+    ));
+}
+
+#[test]
+fn golden_instance() {
+    let bytes = hex(&instance_hex(PointEncoding::Uncompressed));
+    let compressed = hex(&instance_hex(PointEncoding::Compressed));
+    let instance = decode_instance(&bytes).expect("instance vector");
+    assert_eq!(instance.num_shared_rows(), 1);
+    // bincode of the same instance: `Some`, a u64 count and the compressed generator
+    assert!(bincode::serialize(&instance)
+        .unwrap()
+        .starts_with(&hex(&format!("01 0100000000000000 80{GENERATOR_X}"))));
+
+    assert_eq!(encode_instance(&instance).unwrap(), bytes);
+    assert_eq!(
+        encode_instance_with(&instance, PointEncoding::Compressed).unwrap(),
+        compressed
+    );
+    let instance = decode_instance(&compressed).expect("compressed instance vector");
+    assert_eq!(encode_instance(&instance).unwrap(), bytes);
+}
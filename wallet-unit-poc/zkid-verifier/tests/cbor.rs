@@ -0,0 +1,186 @@
+use ciborium::Value;
+use ff::{Field, PrimeField};
+use zkid_verifier::{inspect_bundle, BundleError, DeserializeLimits, PresentationBundle, Scalar};
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+fn pair(prepare: Value, show: Value) -> Value {
+    Value::Map(vec![(text("show"), show), (text("prepare"), prepare)])
+}
+
+/// A bundle with stand-in proof bytes, its keys in deterministic order
+fn bundle(version: u64, format: &str) -> Value {
+    Value::Map(vec![
+        (
+            text("vkId"),
+            pair(Value::Bytes(vec![0xaa; 32]), Value::Bytes(vec![0xbb; 32])),
+        ),
+        (text("nonce"), Value::Bytes(b"challenge".to_vec())),
+        (text("format"), text(format)),
+        (
+            text("proofs"),
+            pair(Value::Bytes(vec![1; 300]), Value::Bytes(vec![2; 40])),
+        ),
+        (text("version"), Value::Integer(version.into())),
+        (
+            text("publicInputs"),
+            pair(
+                Value::Array(vec![]),
+                Value::Array(vec![Value::Bytes(Scalar::ONE.to_repr().as_ref().to_vec())]),
+            ),
+        ),
+    ])
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).unwrap();
+    out
+}
+
+fn set(value: &mut Value, key: &str, field: Value) {
+    let Value::Map(entries) = value else {
+        unreachable!()
+    };
+    entries
+        .iter_mut()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .unwrap()
+        .1 = field;
+}
+
+#[test]
+fn inspect_reads_fields_and_sizes() {
+    let bytes = encode(&bundle(1, "zkid-spartan2"));
+    let info = inspect_bundle(&bytes, &DeserializeLimits::default()).unwrap();
+
+    assert_eq!(info.version, 1);
+    assert_eq!(info.prepare_vk_id, [0xaa; 32]);
+    assert_eq!(info.show_vk_id, [0xbb; 32]);
+    assert_eq!(info.nonce, b"challenge");
+    assert_eq!(info.prepare_public_values, vec![]);
+    assert_eq!(info.show_public_values, vec![Scalar::ONE]);
+    assert_eq!(info.prepare_proof_bytes, 300);
+    assert_eq!(info.show_proof_bytes, 40);
+    assert_eq!(info.bundle_bytes, bytes.len());
+}
+
+#[test]
+fn rejects_non_deterministic_encoding() {
+    let limits = DeserializeLimits::default();
+
+    let Value::Map(mut entries) = bundle(1, "zkid-spartan2") else {
+        unreachable!()
+    };
+    entries.reverse();
+    assert!(matches!(
+        inspect_bundle(&encode(&Value::Map(entries)), &limits),
+        Err(BundleError::NotDeterministic)
+    ));
+
+    // `version: 1` with a one-byte argument instead of the shortest form
+    let bytes = encode(&bundle(1, "zkid-spartan2"));
+    let shortest = b"\x67version\x01";
+    let position = bytes
+        .windows(shortest.len())
+        .position(|window| window == shortest)
+        .unwrap();
+    let mut longer = bytes[..position + shortest.len() - 1].to_vec();
+    longer.extend_from_slice(&[0x18, 0x01]);
+    longer.extend_from_slice(&bytes[position + shortest.len()..]);
+    assert!(matches!(
+        inspect_bundle(&longer, &limits),
+        Err(BundleError::NotDeterministic)
+    ));
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(inspect_bundle(&trailing, &limits).is_err());
+}
+
+#[test]
+fn rejects_other_format_and_version() {
+    let limits = DeserializeLimits::default();
+    assert!(matches!(
+        inspect_bundle(&encode(&bundle(1, "zkid-groth16")), &limits),
+        Err(BundleError::Format(format)) if format == "zkid-groth16"
+    ));
+    assert!(matches!(
+        inspect_bundle(&encode(&bundle(2, "zkid-spartan2")), &limits),
+        Err(BundleError::Version(2))
+    ));
+}
+
+#[test]
+fn rejects_malformed_fields() {
+    let limits = DeserializeLimits::default();
+
+    let mut short_vk_id = bundle(1, "zkid-spartan2");
+    set(
+        &mut short_vk_id,
+        "vkId",
+        pair(Value::Bytes(vec![0xaa; 31]), Value::Bytes(vec![0xbb; 32])),
+    );
+    assert!(matches!(
+        inspect_bundle(&encode(&short_vk_id), &limits),
+        Err(BundleError::Structure(_))
+    ));
+
+    let mut text_nonce = bundle(1, "zkid-spartan2");
+    set(&mut text_nonce, "nonce", text("challenge"));
+    assert!(matches!(
+        inspect_bundle(&encode(&text_nonce), &limits),
+        Err(BundleError::Structure(_))
+    ));
+
+    let mut bad_public = bundle(1, "zkid-spartan2");
+    set(
+        &mut bad_public,
+        "publicInputs",
+        pair(
+            Value::Array(vec![Value::Bytes(vec![0xff; 32])]),
+            Value::Array(vec![]),
+        ),
+    );
+    assert!(matches!(
+        inspect_bundle(&encode(&bad_public), &limits),
+        Err(BundleError::PublicValue)
+    ));
+
+    let Value::Map(mut entries) = bundle(1, "zkid-spartan2") else {
+        unreachable!()
+    };
+    entries.retain(|(key, _)| key.as_text() != Some("nonce"));
+    assert!(matches!(
+        inspect_bundle(&encode(&Value::Map(entries)), &limits),
+        Err(BundleError::Structure(_))
+    ));
+}
+
+#[test]
+fn rejects_bundles_over_the_limits() {
+    let bytes = encode(&bundle(1, "zkid-spartan2"));
+    let limits = DeserializeLimits {
+        proof: 100,
+        ..DeserializeLimits::default()
+    };
+    // The whole bundle fits the metadata allowance, but the 300-byte Prepare proof does not
+    assert!(matches!(
+        inspect_bundle(&bytes, &limits),
+        Err(BundleError::TooLarge {
+            size: 300,
+            limit: 100
+        })
+    ));
+}
+
+#[test]
+fn rejects_proofs_not_in_canonical_encoding() {
+    let bytes = encode(&bundle(1, "zkid-spartan2"));
+    assert!(matches!(
+        PresentationBundle::from_cbor(&bytes, &DeserializeLimits::default()),
+        Err(BundleError::Proof(_))
+    ));
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "compression")]
+
+use zkid_verifier::compression::{compress, decompress, is_compressed, CompressionError};
+
+#[test]
+fn round_trip_within_limit() {
+    let bytes: Vec<u8> = (0..4096u32).map(|i| (i % 13) as u8).collect();
+    let compressed = compress(&bytes).unwrap();
+    assert!(is_compressed(&compressed));
+    assert!(compressed.len() < bytes.len());
+    assert_eq!(decompress(&compressed, bytes.len() as u64).unwrap(), bytes);
+}
+
+#[test]
+fn stops_at_the_limit() {
+    let compressed = compress(&[0u8; 1 << 20]).unwrap();
+    assert!(matches!(
+        decompress(&compressed, 1024),
+        Err(CompressionError::TooLarge { limit: 1024 })
+    ));
+}
+
+#[test]
+fn rejects_invalid_frames() {
+    let compressed = compress(b"presentation").unwrap();
+    assert!(!is_compressed(b"presentation"));
+    assert!(decompress(&compressed[..compressed.len() - 1], 1024).is_err());
+
+    let mut trailing = compressed;
+    trailing.push(0);
+    assert!(decompress(&trailing, 1024).is_err());
+}
@@ -0,0 +1,111 @@
+use ff::Field;
+use zkid_verifier::{
+    envelope::{EnvelopePublicValues, ENVELOPE_FORMAT, ENVELOPE_VERSION},
+    scalar_to_hex, DeserializeLimits, EnvelopeError, PresentationEnvelope, Scalar, VkIds,
+};
+
+fn vk_ids() -> VkIds {
+    VkIds {
+        prepare: "aa".repeat(32),
+        show: "bb".repeat(32),
+    }
+}
+
+fn envelope(bytes: &[u8]) -> PresentationEnvelope {
+    PresentationEnvelope::from_presentation_bytes(
+        bytes,
+        vk_ids(),
+        EnvelopePublicValues {
+            prepare: vec![],
+            show: vec![scalar_to_hex(&Scalar::ONE)],
+        },
+    )
+}
+
+#[test]
+fn json_round_trip() {
+    let bytes: Vec<u8> = (0..=255).collect();
+    let original = envelope(&bytes);
+    let json = original.to_json().unwrap();
+
+    let decoded = PresentationEnvelope::from_json(&json).unwrap();
+    assert_eq!(decoded, original);
+    assert_eq!(decoded.format, ENVELOPE_FORMAT);
+    assert_eq!(decoded.version, ENVELOPE_VERSION);
+    assert_eq!(decoded.presentation_bytes().unwrap(), bytes);
+    assert_eq!(
+        decoded.public_values().unwrap(),
+        (vec![], vec![Scalar::ONE])
+    );
+}
+
+#[test]
+fn proof_is_unpadded_base64url() {
+    // 0xfb 0xff encodes to "-_8" in base64url, "+/8=" in padded base64
+    let envelope = envelope(&[0xfb, 0xff]);
+    assert_eq!(envelope.proof, "-_8");
+
+    let mut padded = envelope.clone();
+    padded.proof = "-_8=".into();
+    assert!(matches!(
+        padded.presentation_bytes(),
+        Err(EnvelopeError::Base64(_))
+    ));
+    padded.proof = "+/8".into();
+    assert!(matches!(
+        padded.presentation_bytes(),
+        Err(EnvelopeError::Base64(_))
+    ));
+}
+
+#[test]
+fn sizes_report_each_encoding() {
+    let envelope = envelope(&[0u8; 300]);
+    let sizes = envelope.sizes().unwrap();
+    assert_eq!(sizes.proof_bytes, 300);
+    assert_eq!(sizes.proof_base64_bytes, 400);
+    assert_eq!(sizes.envelope_bytes, envelope.to_json().unwrap().len());
+}
+
+#[test]
+fn rejects_other_format_and_version() {
+    let json = envelope(&[1, 2, 3]).to_json().unwrap();
+
+    let other_format = json.replace(ENVELOPE_FORMAT, "zkid-groth16");
+    assert!(matches!(
+        PresentationEnvelope::from_json(&other_format),
+        Err(EnvelopeError::Format(format)) if format == "zkid-groth16"
+    ));
+
+    let other_version = json.replace("\"version\":1", "\"version\":2");
+    assert!(matches!(
+        PresentationEnvelope::from_json(&other_version),
+        Err(EnvelopeError::Version(2))
+    ));
+
+    assert!(matches!(
+        PresentationEnvelope::from_json("{\"format\":\"zkid-spartan2\"}"),
+        Err(EnvelopeError::Json(_))
+    ));
+}
+
+#[test]
+fn rejects_invalid_public_values() {
+    for value in ["", "zz", &"0".repeat(63), &format!("+{}", "0".repeat(63))] {
+        let mut envelope = envelope(&[]);
+        envelope.public.prepare = vec![value.to_string()];
+        assert!(matches!(
+            envelope.public_values(),
+            Err(EnvelopeError::PublicValue(_))
+        ));
+    }
+}
+
+#[test]
+fn rejects_proof_that_is_not_a_presentation() {
+    let envelope = envelope(&[0xff; 16]);
+    assert!(matches!(
+        envelope.presentation(&DeserializeLimits::default()),
+        Err(EnvelopeError::Presentation(_))
+    ));
+}
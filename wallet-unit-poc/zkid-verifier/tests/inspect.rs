@@ -0,0 +1,45 @@
+use zkid_verifier::{
+    envelope::EnvelopePublicValues, inspect, DeserializeLimits, InspectError, PresentationEnvelope,
+    VkIds,
+};
+
+#[test]
+fn rejects_unrecognized_input() {
+    let limits = DeserializeLimits::default();
+    assert!(matches!(
+        inspect(b"not a proof", &limits, &[]),
+        Err(InspectError::Unrecognized)
+    ));
+    assert!(matches!(
+        inspect(&[], &limits, &[]),
+        Err(InspectError::Unrecognized)
+    ));
+}
+
+#[test]
+fn reports_envelope_errors() {
+    let envelope = PresentationEnvelope::from_presentation_bytes(
+        &[1, 2, 3],
+        VkIds {
+            prepare: "aa".repeat(32),
+            show: "bb".repeat(32),
+        },
+        EnvelopePublicValues {
+            prepare: vec![],
+            show: vec![],
+        },
+    );
+    let json = envelope.to_json().unwrap();
+    assert!(matches!(
+        inspect(json.as_bytes(), &DeserializeLimits::default(), &[]),
+        Err(InspectError::Envelope(_))
+    ));
+}
+
+#[test]
+fn rejects_truncated_canonical_header() {
+    assert!(matches!(
+        inspect(b"ZKID", &DeserializeLimits::default(), &[]),
+        Err(InspectError::Canonical(_))
+    ));
+}
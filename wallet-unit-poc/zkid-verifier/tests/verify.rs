@@ -0,0 +1,274 @@
+//! Presentation parsing, key loading, linkage and policy checks over real (toy circuit) proofs.
+
+use ff::Field;
+use spartan2::traits::circuit::SpartanCircuit;
+use zkid_verifier::{
+    challenge_message_hash, check_linkage, proof_from_bytes, scalar_to_hex,
+    test_utils::{fixture, prove, setup, ToyCircuit, SHARED},
+    verify, verify_presentation, verifying_key_from_bytes, vk_id, Date, DeserializeLimits,
+    Predicate, Presentation, Scalar, VerificationPolicy, VerifierKey, VerifyError, E,
+};
+
+fn policy(show_public_values: Vec<Scalar>) -> VerificationPolicy {
+    VerificationPolicy {
+        prepare_public_values: vec![],
+        show_public_values,
+        show_date_tolerance_days: None,
+    }
+}
+
+fn date(year: u32, month: u32, day: u32) -> Date {
+    Date::new(year, month, day).unwrap()
+}
+
+#[test]
+fn linked_presentation_verifies() {
+    let fixture = fixture();
+    let verified = verify_presentation(
+        &fixture.presentation(),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(fixture.show_public_values()),
+    )
+    .expect("linked presentation");
+
+    assert!(verified.prepare_public_values.is_empty());
+    assert_eq!(verified.show_public_values, fixture.show_public_values());
+    assert_eq!(
+        verified.shared_commitment,
+        check_linkage(&fixture.prepare.proof, &fixture.show.proof).unwrap()
+    );
+}
+
+#[test]
+fn public_values_must_match_the_policy() {
+    let fixture = fixture();
+    let result = verify_presentation(
+        &fixture.presentation(),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &VerificationPolicy::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(VerifyError::PublicValues { circuit: "Show" })
+    ));
+
+    let mut prepare_values = policy(fixture.show_public_values());
+    prepare_values.prepare_public_values = fixture.show_public_values();
+    let result = verify_presentation(
+        &fixture.presentation(),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &prepare_values,
+    );
+    assert!(matches!(
+        result,
+        Err(VerifyError::PublicValues { circuit: "Prepare" })
+    ));
+}
+
+#[test]
+fn challenge_message_hash_matches_the_show_inputs() {
+    // `messageHash` of `circom/inputs/show/default.json`, signed over "zkid-default-challenge"
+    assert_eq!(
+        scalar_to_hex(&challenge_message_hash(b"zkid-default-challenge")),
+        "f29ab1228fd9ab3cb71bc5076c43afbe7f38c6ba958aeeed5ae63e34eafc3cf8"
+    );
+}
+
+#[test]
+fn predicate_policy_binds_the_challenge_and_the_date() {
+    let fixture = fixture();
+    let presentation = |age_above_18: Scalar, challenge: &[u8], date: Date| {
+        let mut exposed = vec![age_above_18, challenge_message_hash(challenge)];
+        exposed.extend(date.to_scalars());
+        let show = ToyCircuit {
+            public: false,
+            exposed,
+            ..ToyCircuit::show(&SHARED)
+        };
+        let (show_pk, show_vk) = setup(&show);
+        let show = prove(&show, &show_pk, 1);
+        (
+            Presentation::new(fixture.prepare.proof.clone(), show.proof),
+            show_vk,
+        )
+    };
+    let today = date(2025, 3, 1);
+    let denied =
+        |presentation: &Presentation, show_vk: &VerifierKey, policy: &VerificationPolicy| {
+            matches!(
+                verify_presentation(presentation, &fixture.prepare_vk, show_vk, policy),
+                Err(VerifyError::PublicValues { circuit: "Show" })
+            )
+        };
+
+    let (honest, show_vk) = presentation(Scalar::ONE, b"nonce-1", today);
+    let policy = Predicate::AgeOver18.policy(b"nonce-1", today);
+    verify_presentation(&honest, &fixture.prepare_vk, &show_vk, &policy).expect("same nonce");
+
+    // Replayed under another nonce, or without the predicate holding, the Show values differ
+    let replayed = Predicate::AgeOver18.policy(b"nonce-2", today);
+    assert!(denied(&honest, &show_vk, &replayed));
+
+    let (under_18, show_vk) = presentation(Scalar::ZERO, b"nonce-1", today);
+    assert!(denied(&under_18, &show_vk, &policy));
+
+    // A proof dated within a day of the relying party's date is accepted, across a leap day
+    for (proof_date, accepted) in [
+        (date(2025, 2, 28), true),
+        (date(2025, 3, 2), true),
+        (date(2025, 2, 27), false),
+        // A future date, on which an underage holder would be over 18
+        (date(2043, 3, 1), false),
+    ] {
+        let (dated, show_vk) = presentation(Scalar::ONE, b"nonce-1", proof_date);
+        let result = verify_presentation(&dated, &fixture.prepare_vk, &show_vk, &policy);
+        assert_eq!(result.is_ok(), accepted, "proof dated {proof_date}");
+    }
+    let (dated, show_vk) = presentation(Scalar::ONE, b"nonce-1", date(2024, 2, 29));
+    let leap_day = Predicate::AgeOver18.policy(b"nonce-1", date(2024, 3, 1));
+    verify_presentation(&dated, &fixture.prepare_vk, &show_vk, &leap_day).expect("leap day");
+}
+
+#[test]
+fn dates_are_validated_and_counted() {
+    assert!(Date::new(2024, 2, 29).is_some());
+    assert!(Date::new(2023, 2, 29).is_none());
+    assert!(Date::new(2025, 13, 1).is_none());
+    assert!(Date::new(2025, 4, 31).is_none());
+    assert_eq!(date(1970, 1, 1).days_since(date(1969, 12, 31)), 1);
+    assert_eq!(date(2025, 1, 1).days_since(date(2024, 1, 1)), 366);
+    assert_eq!(date(2000, 3, 1).days_since(date(2000, 2, 28)), 2);
+    assert_eq!(
+        Date::from_scalars(&date(2025, 6, 5).to_scalars()),
+        Some(date(2025, 6, 5))
+    );
+    assert_eq!(
+        Date::from_scalars(&[Scalar::from(2025), Scalar::from(2), -Scalar::ONE]),
+        None
+    );
+    assert!(Date::today() > date(2024, 1, 1));
+    assert_eq!(date(2025, 6, 5).to_string(), "2025-06-05");
+}
+
+#[test]
+fn proofs_fail_against_each_others_keys() {
+    let fixture = fixture();
+    assert!(verify(&fixture.prepare.proof, &fixture.show_vk).is_err());
+    assert!(verify(&fixture.show.proof, &fixture.prepare_vk).is_err());
+
+    let result = verify_presentation(
+        &fixture.presentation(),
+        &fixture.show_vk,
+        &fixture.prepare_vk,
+        &policy(fixture.show_public_values()),
+    );
+    assert!(matches!(result, Err(VerifyError::PrepareProof(_))));
+
+    let swapped = Presentation::new(fixture.prepare.proof.clone(), fixture.prepare.proof.clone());
+    let result = verify_presentation(
+        &swapped,
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(fixture.show_public_values()),
+    );
+    assert!(matches!(result, Err(VerifyError::ShowProof(_))));
+}
+
+#[test]
+fn unlinked_proofs_are_rejected() {
+    let fixture = fixture();
+
+    // Same values, other blinds: each proof verifies but the commitments differ
+    let reblinded = prove(&ToyCircuit::show(&SHARED), &fixture.show_pk, 2);
+    verify(&reblinded.proof, &fixture.show_vk).expect("reblinded show proof");
+    assert!(matches!(
+        check_linkage(&fixture.prepare.proof, &reblinded.proof),
+        Err(VerifyError::LinkageMismatch)
+    ));
+
+    // Same blinds, another credential
+    let mut other = SHARED;
+    other[0] += 1;
+    let other_show = ToyCircuit::show(&other);
+    let other = prove(&other_show, &fixture.show_pk, 1);
+    let result = verify_presentation(
+        &Presentation::new(fixture.prepare.proof.clone(), other.proof),
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(SpartanCircuit::<E>::public_values(&other_show).unwrap()),
+    );
+    assert!(matches!(result, Err(VerifyError::LinkageMismatch)));
+}
+
+#[test]
+fn proof_without_shared_values_cannot_be_linked() {
+    let fixture = fixture();
+    let circuit = ToyCircuit {
+        shared: vec![],
+        rounds: 1,
+        public: false,
+        exposed: vec![],
+    };
+    let (pk, vk) = setup(&circuit);
+    let proven = prove(&circuit, &pk, 1);
+    verify(&proven.proof, &vk).expect("proof");
+    assert!(matches!(
+        check_linkage(&fixture.prepare.proof, &proven.proof),
+        Err(VerifyError::MissingSharedCommitment { circuit: "Show" })
+    ));
+}
+
+#[test]
+fn presentation_bytes_round_trip() {
+    let fixture = fixture();
+    let bytes = fixture.presentation().to_bytes().unwrap();
+    let limits = DeserializeLimits::default();
+
+    let decoded = Presentation::from_bytes(&bytes, &limits).expect("presentation");
+    verify_presentation(
+        &decoded,
+        &fixture.prepare_vk,
+        &fixture.show_vk,
+        &policy(fixture.show_public_values()),
+    )
+    .expect("decoded presentation");
+
+    assert!(Presentation::from_bytes(&bytes[..bytes.len() - 1], &limits).is_err());
+    let tight = DeserializeLimits {
+        proof: bytes.len() as u64 / 2 - 1,
+        ..limits
+    };
+    assert!(Presentation::from_bytes(&bytes, &tight).is_err());
+}
+
+#[test]
+fn keys_and_proofs_load_from_bytes() {
+    let fixture = fixture();
+    let limits = DeserializeLimits::default();
+
+    let vk = verifying_key_from_bytes(&fixture.show_vk_bytes, &limits).expect("verifying key");
+    let proof_bytes = bincode::serialize(&fixture.show.proof).unwrap();
+    let proof = proof_from_bytes(&proof_bytes, &limits).expect("proof");
+    verify(&proof, &vk).expect("loaded proof");
+
+    let truncated = &fixture.show_vk_bytes[..fixture.show_vk_bytes.len() / 2];
+    assert!(verifying_key_from_bytes(truncated, &limits).is_err());
+    assert!(proof_from_bytes(&proof_bytes[..proof_bytes.len() - 1], &limits).is_err());
+}
+
+#[test]
+fn vk_id_is_sha256_of_the_key_bytes() {
+    let fixture = fixture();
+    assert_eq!(
+        vk_id(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(vk_id(&fixture.prepare_vk_bytes).len(), 64);
+    assert_ne!(
+        vk_id(&fixture.prepare_vk_bytes),
+        vk_id(&fixture.show_vk_bytes)
+    );
+}
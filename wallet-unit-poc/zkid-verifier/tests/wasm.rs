@@ -0,0 +1,178 @@
+//! Run with `wasm-pack test --node -- --features wasm`
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use ff::Field;
+use wasm_bindgen_test::wasm_bindgen_test;
+use zkid_verifier::{
+    challenge_message_hash, scalars_to_hex,
+    test_utils::{prove, setup, ToyCircuit, SHARED},
+    wasm::{verify_presentation_bytes, verify_proof_bytes, vk_id_bytes, VerificationResult},
+    Date, Presentation, Scalar,
+};
+
+/// Challenge the Show stand-in answers
+const CHALLENGE: &[u8] = b"wasm-challenge";
+
+/// Date of the Show stand-in
+const DATE: Date = Date {
+    year: 2025,
+    month: 1,
+    day: 1,
+};
+
+fn result(value: wasm_bindgen::JsValue) -> VerificationResult {
+    serde_wasm_bindgen::from_value(value).expect("verification result should be a plain object")
+}
+
+/// `verifyPresentation` by a relying party on `DATE`
+fn verify_presentation(
+    bytes: &[u8],
+    prepare_vk: &[u8],
+    show_vk: &[u8],
+    challenge: &[u8],
+) -> VerificationResult {
+    result(verify_presentation_bytes(
+        bytes, prepare_vk, show_vk, challenge, DATE.year, DATE.month, DATE.day,
+    ))
+}
+
+/// Public values of a Show proof of age over 18 answering `CHALLENGE` on `DATE`
+fn show_public_values() -> Vec<Scalar> {
+    let mut values = vec![Scalar::ONE, challenge_message_hash(CHALLENGE)];
+    values.extend(DATE.to_scalars());
+    values
+}
+
+/// Show stand-in returning the public values of the Show circuit
+fn show(shared: &[u64]) -> ToyCircuit {
+    ToyCircuit {
+        public: false,
+        exposed: show_public_values(),
+        ..ToyCircuit::show(shared)
+    }
+}
+
+/// Presentation bytes and the Prepare and Show verifying keys, with the Show proof over `shared`
+fn presentation(shared: &[u64], seed: u64) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (prepare_pk, prepare_vk) = setup(&ToyCircuit::prepare(&SHARED));
+    let (show_pk, show_vk) = setup(&show(&SHARED));
+    let prepare = prove(&ToyCircuit::prepare(&SHARED), &prepare_pk, 1);
+    let show = prove(&show(shared), &show_pk, seed);
+    (
+        Presentation::new(prepare.proof, show.proof)
+            .to_bytes()
+            .unwrap(),
+        bincode::serialize(&prepare_vk).unwrap(),
+        bincode::serialize(&show_vk).unwrap(),
+    )
+}
+
+#[wasm_bindgen_test]
+fn verifies_presentation() {
+    let (bytes, prepare_vk, show_vk) = presentation(&SHARED, 1);
+    let res = verify_presentation(&bytes, &prepare_vk, &show_vk, CHALLENGE);
+    assert!(res.verified, "{:?}", res.error);
+    assert!(res.error.is_none());
+    assert!(res.shared_commitment.is_some_and(|hex| !hex.is_empty()));
+    assert!(res.prepare_public_values.is_empty());
+    assert_eq!(
+        res.show_public_values,
+        scalars_to_hex(&show_public_values())
+    );
+
+    // Each proof also verifies on its own, and not against the other key
+    let presentation = Presentation::from_bytes(&bytes, &Default::default()).unwrap();
+    let show_proof = bincode::serialize(&presentation.show_proof).unwrap();
+    assert!(result(verify_proof_bytes(&show_proof, &show_vk)).verified);
+    assert!(!result(verify_proof_bytes(&show_proof, &prepare_vk)).verified);
+}
+
+#[wasm_bindgen_test]
+fn rejects_tampered_presentation() {
+    // A Show proof about another credential, or with other blinds, is not linked to Prepare
+    for (shared, seed) in [([4, 5, 7, 11], 1), (SHARED, 2)] {
+        let (bytes, prepare_vk, show_vk) = presentation(&shared, seed);
+        let res = verify_presentation(&bytes, &prepare_vk, &show_vk, CHALLENGE);
+        assert!(!res.verified);
+        assert!(res.error.unwrap().contains("comm_W_shared"));
+        assert!(res.shared_commitment.is_none());
+    }
+
+    // Altered bytes either fail to decode or fail verification
+    let (bytes, prepare_vk, show_vk) = presentation(&SHARED, 1);
+    for offset in [bytes.len() / 3, bytes.len() - 1] {
+        let mut tampered = bytes.clone();
+        tampered[offset] ^= 1;
+        let res = verify_presentation(&tampered, &prepare_vk, &show_vk, CHALLENGE);
+        assert!(
+            !res.verified,
+            "presentation tampered at byte {offset} verified"
+        );
+        assert!(res.error.is_some());
+    }
+}
+
+#[wasm_bindgen_test]
+fn rejects_presentation_for_another_challenge() {
+    let (bytes, prepare_vk, show_vk) = presentation(&SHARED, 1);
+    let res = verify_presentation(&bytes, &prepare_vk, &show_vk, b"other-challenge");
+    assert!(!res.verified);
+    assert!(res.error.unwrap().contains("Show public values"));
+}
+
+#[wasm_bindgen_test]
+fn checks_the_date_within_a_day() {
+    let (bytes, prepare_vk, show_vk) = presentation(&SHARED, 1);
+    let on = |year, month, day| {
+        result(verify_presentation_bytes(
+            &bytes,
+            &prepare_vk,
+            &show_vk,
+            CHALLENGE,
+            year,
+            month,
+            day,
+        ))
+    };
+    assert!(on(2024, 12, 31).verified);
+    assert!(on(2025, 1, 2).verified);
+
+    let res = on(2024, 12, 30);
+    assert!(!res.verified);
+    assert!(res.error.unwrap().contains("Show public values"));
+
+    let res = on(2025, 2, 30);
+    assert!(!res.verified);
+    assert!(res.error.unwrap().starts_with("Invalid date"));
+}
+
+#[wasm_bindgen_test]
+fn rejects_malformed_presentation() {
+    let res = verify_presentation(&[0xff; 16], &[], &[], CHALLENGE);
+    assert!(!res.verified);
+    assert!(res.error.unwrap().starts_with("Invalid presentation"));
+    assert!(res.shared_commitment.is_none());
+}
+
+#[wasm_bindgen_test]
+fn rejects_empty_presentation() {
+    let res = verify_presentation(&[], &[], &[], CHALLENGE);
+    assert!(!res.verified);
+    assert!(res.error.is_some());
+}
+
+#[wasm_bindgen_test]
+fn rejects_malformed_proof() {
+    let res = result(verify_proof_bytes(&[1, 2, 3], &[]));
+    assert!(!res.verified);
+    assert!(res.error.unwrap().starts_with("Invalid proof"));
+    assert!(res.prepare_public_values.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn vk_id_is_sha256_hex() {
+    assert_eq!(
+        vk_id_bytes(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}